@@ -23,6 +23,7 @@ pub enum Error {
     WaitingForTarget(io::Error),
     NotADir(String),
     NotEmpty(String),
+    EmptyCorpus(String),
     JoiningThread,
     Fatal(String),
     ConversionError,
@@ -83,6 +84,7 @@ impl fmt::Display for Error {
             Self::WaitingForTarget(e) => write!(f, "Waiting for target {e}"),
             Self::NotADir(e) => write!(f, "Not a directory: {e}"),
             Self::NotEmpty(e) => write!(f, "Directory not empty: {e}"),
+            Self::EmptyCorpus(e) => write!(f, "No seeds in corpus directory: {e}"),
             Self::JoiningThread => write!(f, "Joining threads"),
             Self::Fatal(e) => write!(f, "Fatal error: {e}"),
             Self::ConversionError => write!(f, "Conversion error: "),
@@ -0,0 +1,115 @@
+// Edge coverage feedback through an AFL style shared memory bitmap. The worker creates a System V
+// shared memory segment and passes its id to the target via `__AFL_SHM_ID`. An instrumented
+// target, e.g. built with SanitizerCoverage and the AFL runtime, attaches the segment as its
+// `__afl_area_ptr` and increments one byte per edge it executes.
+//
+// Like AFL, hit counts are bucketed into powers of two, so an input is only interesting if it
+// hits a new edge or an edge a notably different number of times.
+use errors::{Error, Result};
+use std::ptr;
+
+/// Environment variable the id of the shared memory segment is passed in.
+pub const SHM_ENV_VAR: &str = "__AFL_SHM_ID";
+/// Size of the edge bitmap in bytes.
+pub const MAP_SIZE: usize = 1 << 16;
+
+/// The edge bitmap shared with the target along with the edges seen so far.
+pub struct CoverageMap {
+    shm_id: i32,
+    map: *mut u8,
+    // Bucketed hit counts seen in any previous run, one bit per bucket
+    seen: Vec<u8>,
+}
+
+impl CoverageMap {
+    /// Creates and attaches a new shared memory segment. The segment is marked for removal right
+    /// away, so it is freed once the last process detaches from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the segment cannot be created or attached.
+    pub fn new() -> Result<Self> {
+        // SAFETY: Creates a private segment that is only accessed through `map`
+        unsafe {
+            let shm_id = libc::shmget(libc::IPC_PRIVATE, MAP_SIZE, libc::IPC_CREAT | 0o600);
+            if shm_id < 0 {
+                return Err(Error::new(&format!(
+                    "Creating the coverage map: {}",
+                    std::io::Error::last_os_error()
+                )));
+            }
+            let map = libc::shmat(shm_id, ptr::null(), 0);
+            libc::shmctl(shm_id, libc::IPC_RMID, ptr::null_mut());
+            if map as isize == -1 {
+                return Err(Error::new(&format!(
+                    "Attaching the coverage map: {}",
+                    std::io::Error::last_os_error()
+                )));
+            }
+            Ok(Self {
+                shm_id,
+                map: map.cast(),
+                seen: vec![0; MAP_SIZE],
+            })
+        }
+    }
+
+    /// Returns the id the target attaches the map with.
+    pub const fn id(&self) -> i32 {
+        self.shm_id
+    }
+
+    fn map(&mut self) -> &mut [u8] {
+        // SAFETY: The segment is `MAP_SIZE` bytes large and attached as long as `self` lives
+        unsafe { std::slice::from_raw_parts_mut(self.map, MAP_SIZE) }
+    }
+
+    /// Resets the hit counts before running the target.
+    pub fn clear(&mut self) {
+        self.map().fill(0);
+    }
+
+    /// Checks the hit counts of the last run for edges or hit count buckets not seen before and
+    /// marks them as seen.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the last run covered anything new.
+    pub fn has_new_coverage(&mut self) -> bool {
+        let mut seen = std::mem::take(&mut self.seen);
+        let mut new = false;
+        for (count, seen) in self.map().iter().zip(seen.iter_mut()) {
+            let bucket = bucket(*count);
+            if bucket & !*seen != 0 {
+                *seen |= bucket;
+                new = true;
+            }
+        }
+        self.seen = seen;
+        new
+    }
+}
+
+impl Drop for CoverageMap {
+    fn drop(&mut self) {
+        // SAFETY: `map` was attached in `new` and is not used afterwards
+        unsafe {
+            libc::shmdt(self.map.cast());
+        }
+    }
+}
+
+// Maps a hit count to a single bit for the ranges 1, 2, 3, 4-7, 8-15, 16-31, 32-127 and 128-255
+const fn bucket(count: u8) -> u8 {
+    match count {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 4,
+        4..=7 => 8,
+        8..=15 => 16,
+        16..=31 => 32,
+        32..=127 => 64,
+        128..=255 => 128,
+    }
+}
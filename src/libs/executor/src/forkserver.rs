@@ -0,0 +1,121 @@
+// Client for the AFL forkserver protocol. An instrumented target stops right before `main`,
+// forks a child for every input it is asked to run and reports the child's pid and wait status,
+// so the costly exec and setup of the target only happen once.
+//
+// The fuzzer writes to the control pipe, which the target reads from fd 198, and reads from the
+// status pipe, which the target writes to on fd 199. After a 4 byte hello from the target, every
+// run consists of:
+//
+// 1. the fuzzer writing 4 bytes to start a child,
+// 2. the target writing the pid of the child,
+// 3. the target writing the wait status of the child once it terminated.
+use errors::{Error, Result};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::{
+        fd::{AsRawFd, FromRawFd, RawFd},
+        unix::process::{CommandExt, ExitStatusExt},
+    },
+    process::{Child, Command, ExitStatus, Stdio},
+};
+
+// File descriptor the target reads the control pipe from
+const CONTROL_FD: RawFd = 198;
+// File descriptor the target writes the status pipe to
+const STATUS_FD: RawFd = 199;
+
+/// A running forkserver of the target.
+pub struct Forkserver {
+    server: Child,
+    // Write end of the control pipe
+    control: File,
+    // Read end of the status pipe
+    status: File,
+}
+
+impl Forkserver {
+    /// Starts the forkserver by running `put` and waits for its hello.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target cannot be started or does not speak the forkserver protocol.
+    pub fn start(mut put: Command) -> Result<Self> {
+        let (control_read, control) = pipe()?;
+        let (status, status_write) = pipe()?;
+        let fds = (control_read.as_raw_fd(), status_write.as_raw_fd());
+        // SAFETY: `dup2` is async-signal-safe, the duplicates do not inherit `O_CLOEXEC`
+        unsafe {
+            put.pre_exec(move || {
+                if libc::dup2(fds.0, CONTROL_FD) < 0 || libc::dup2(fds.1, STATUS_FD) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let server = put
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(Error::SpawningTarget)?;
+        // The target holds its own copies now, so a dying target closes the pipes
+        drop((control_read, status_write));
+
+        let fsrv = Self {
+            server,
+            control,
+            status,
+        };
+        fsrv.read_u32()
+            .map_err(|_| Error::new("The target did not start a forkserver"))?;
+        Ok(fsrv)
+    }
+
+    /// Runs the target once. The input has to be in place, e.g., in the file passed via `@@`.
+    ///
+    /// # Returns
+    ///
+    /// The wait status of the child.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the forkserver died.
+    pub fn run(&self) -> Result<ExitStatus> {
+        let died = |_| Error::new("The forkserver died");
+        (&self.control)
+            .write_all(&0u32.to_ne_bytes())
+            .map_err(died)?;
+        let _pid = self.read_u32().map_err(died)?;
+        let status = self.read_u32().map_err(died)?;
+        Ok(ExitStatus::from_raw(status as i32))
+    }
+
+    fn read_u32(&self) -> std::io::Result<u32> {
+        let mut buf = [0u8; 4];
+        (&self.status).read_exact(&mut buf)?;
+        Ok(u32::from_ne_bytes(buf))
+    }
+}
+
+impl Drop for Forkserver {
+    fn drop(&mut self) {
+        let _ = self.server.kill();
+        let _ = self.server.wait();
+    }
+}
+
+// Creates a pipe that is closed on exec, returning its read and write end
+fn pipe() -> Result<(File, File)> {
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for both ends, which are owned by the returned files
+    unsafe {
+        if libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) != 0 {
+            return Err(Error::new(&format!(
+                "Creating a pipe: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok((File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])))
+    }
+}
@@ -1,26 +1,173 @@
 use core_affinity::CoreId;
 use errors::{Error, Result};
 use std::{
-    collections::BTreeSet,
+    cell::Cell,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fmt::Write as _,
     fs,
-    path::Path,
-    process::{Child, Command, Stdio},
-    sync::{atomic::AtomicUsize, Arc},
-    thread,
+    hash::{Hash, Hasher},
+    io::{Read, Write},
+    net::TcpListener,
+    os::unix::{ffi::OsStrExt, process::CommandExt, process::ExitStatusExt},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc, Mutex, OnceLock,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
-use test_case::TestCase;
 
 use grammar_mutator::GrammarTemplate;
-use mutation_engine::{CustomMutators, MutationEngine};
-use prng::Generators;
+use mutation_engine::{CustomMutators, MutationEngine, StandardMutators};
+use prng::{Generator, Generators, Rng};
+use serde::Serialize;
 use utils::{get_core_affinity, set_core_affinity};
 
+mod coverage;
+mod forkserver;
+mod persistent;
+use coverage::{CoverageMap, SHM_ENV_VAR};
+use forkserver::Forkserver;
+use persistent::{CrashGuard, Harness};
+
+// File name prefix of the crashing inputs written to the crash directory
+const CRASH_FILE_PREFIX: &str = ".crash_";
+// Exit codes of the target, and signals terminating it, that are treated as a crash
+const CRASH_EXIT_CODES: [i32; 12] = [4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+// Added to the number of a signal terminating the target to tell it apart from an exit code,
+// like a shell reports it
+const SIGNAL_CODE_BASE: i32 = 128;
+// Energy assigned to previously found crashes when focusing on them
+const CRASH_FOCUS_ENERGY: usize = 16;
+// Makes AddressSanitizer reports end the target with an exit code from `CRASH_EXIT_CODES`
+// instead of an abort, which is not detected as a crash
+const DEFAULT_ASAN_OPTIONS: &str = "abort_on_error=0:exitcode=6";
+// Energy assigned to test cases that covered new edges
+const COVERAGE_ENERGY: usize = 8;
+// Maximum number of target executions a worker spends on minimizing a single crash
+const MINIMIZE_MAX_EXECS: usize = 1024;
+// Time the metrics endpoint waits on a client, so an idle one cannot stall it
+const METRICS_TIMEOUT: Duration = Duration::from_secs(1);
+// File name prefixes of the artifacts libFuzzer writes next to its corpus
+const LIBFUZZER_ARTIFACT_PREFIXES: [&str; 5] =
+    ["crash-", "leak-", "timeout-", "oom-", "slow-unit-"];
+// Offset basis and prime of the 64-bit FNV-1a hash corpus entries are named by
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Distribution of the sizes of the random seeds described by `RandomSeedConfig`.
+///
+/// # Example
+///
+/// ```
+/// use executor::SeedSizeDist;
+///
+/// assert_eq!("fixed:64".parse::<SeedSizeDist>().unwrap(), SeedSizeDist::Fixed(64));
+/// assert_eq!("exp:512".parse::<SeedSizeDist>().unwrap(), SeedSizeDist::Exponential(512));
+/// assert!("uniform".parse::<SeedSizeDist>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSizeDist {
+    // Sizes drawn uniformly from [0, max)
+    Uniform(usize),
+    // Every seed is exactly this many bytes long
+    Fixed(usize),
+    // Sizes drawn from an exponential distribution with the given mean, favoring small seeds
+    Exponential(usize),
+}
+
+impl SeedSizeDist {
+    fn sample(self, prng: &mut Rng<Generator>) -> usize {
+        match self {
+            Self::Uniform(0) => 0,
+            Self::Uniform(max) => prng.rand_range(0, max),
+            Self::Fixed(n) => n,
+            Self::Exponential(mean) => {
+                (-(mean as f64) * (1.0 - prng.rand_float::<f64>()).ln()) as usize
+            }
+        }
+    }
+}
+
+impl FromStr for SeedSizeDist {
+    type Err = Error;
+
+    /// Parses a distribution of the form `uniform:<max>`, `fixed:<n>` or `exponential:<mean>`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::new(&format!("Invalid seed size distribution: {s}"));
+        let (kind, val) = s.split_once(':').ok_or_else(invalid)?;
+        let val = val.trim().parse::<usize>().map_err(|_| invalid())?;
+        match kind.trim().to_lowercase().as_str() {
+            "uniform" => Ok(Self::Uniform(val)),
+            "fixed" => Ok(Self::Fixed(val)),
+            "exponential" | "exp" => Ok(Self::Exponential(val)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Synthetic seeds of random bytes each worker adds to the corpus loaded from disk. A `count`
+/// of 0 disables them, in which case the corpus directory has to provide at least one seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomSeedConfig {
+    // Number of random seeds to add
+    pub count: usize,
+    // Distribution of the seed sizes in bytes
+    pub size_dist: SeedSizeDist,
+}
+
+impl Default for RandomSeedConfig {
+    fn default() -> Self {
+        Self {
+            count: 128,
+            size_dist: SeedSizeDist::Uniform(98304),
+        }
+    }
+}
+
+/// Layout of a corpus directory, see `import_corpus_dir`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorpusFormat {
+    // Flat directory where every file is a seed, as written by hantu
+    #[default]
+    Hantu,
+    // AFL/AFL++ output or queue directory with `id:000000,...` named seeds
+    Afl,
+    // libFuzzer corpus directory with hash named seeds
+    LibFuzzer,
+}
+
+impl CorpusFormat {
+    pub const NAMES: [&'static str; 3] = ["hantu", "afl", "libfuzzer"];
+}
+
+impl FromStr for CorpusFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "hantu" => Ok(Self::Hantu),
+            "afl" => Ok(Self::Afl),
+            "libfuzzer" => Ok(Self::LibFuzzer),
+            _ => Err(Error::new(&format!("Unknown corpus format: {s}"))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct FuzzerConfig {
     target: String,
     target_args: Vec<String>,
+    env: Vec<(String, String)>,
+    mem_limit_mb: Option<u64>,
+    forkserver: bool,
     corpus_dir: String,
     crash_dir: String,
+    input_dir: Option<PathBuf>,
     dict: Option<String>,
     batch_sz: usize,
     threads: Vec<CoreId>,
@@ -31,6 +178,18 @@ pub struct FuzzerConfig {
     printable: bool,
     mutation_passes: usize,
     max_length: usize,
+    only_mutators: Vec<String>,
+    disabled_mutators: Vec<String>,
+    focus_crashes: bool,
+    corpus_format: CorpusFormat,
+    max_seed_size: Option<usize>,
+    random_seeds: RandomSeedConfig,
+    crash_exploration: Option<usize>,
+    persistent: bool,
+    harness_path: Option<String>,
+    coverage: bool,
+    pub metrics_port: Option<u16>,
+    pub stats_file: Option<PathBuf>,
     pub max_iter: Option<usize>,
     pub max_time: Option<usize>,
 }
@@ -46,6 +205,27 @@ impl FuzzerConfig {
         self
     }
 
+    /// Sets additional environment variables of the target, e.g. `ASAN_OPTIONS`. By default
+    /// `ASAN_OPTIONS` is set to make AddressSanitizer reports exit with a crash exit code.
+    pub fn set_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Limits the address space of the target to `mem_limit_mb` MiB. A target exceeding the
+    /// limit fails to allocate, which usually ends it with a crash. `None` disables the limit.
+    pub const fn set_mem_limit_mb(mut self, mem_limit_mb: Option<u64>) -> Self {
+        self.mem_limit_mb = mem_limit_mb;
+        self
+    }
+
+    /// Runs the target through the AFL forkserver it starts, which forks a child for every test
+    /// case instead of executing the target again. The input has to be passed via `@@`.
+    pub const fn set_forkserver(mut self, forkserver: bool) -> Self {
+        self.forkserver = forkserver;
+        self
+    }
+
     fn ensure_dir(dir: &str) -> Result<String> {
         let p = Path::new(dir);
         if p.is_file() || p.is_symlink() {
@@ -80,6 +260,13 @@ impl FuzzerConfig {
         }
     }
 
+    /// Sets the directory the workers write the test case passed to the target to, one file per
+    /// worker. `None` uses the current directory.
+    pub fn set_input_dir(mut self, input_dir: Option<PathBuf>) -> Self {
+        self.input_dir = input_dir;
+        self
+    }
+
     pub const fn set_max_iter(mut self, max_iter: Option<usize>) -> Self {
         if max_iter.is_some() {
             self.max_iter = max_iter;
@@ -153,19 +340,237 @@ impl FuzzerConfig {
         self.ni_mutator = ni_mutator;
         self
     }
+
+    pub const fn set_focus_crashes(mut self, focus_crashes: bool) -> Self {
+        self.focus_crashes = focus_crashes;
+        self
+    }
+
+    pub const fn set_corpus_format(mut self, corpus_format: CorpusFormat) -> Self {
+        self.corpus_format = corpus_format;
+        self
+    }
+
+    /// Sets the maximum size in bytes of a seed imported from the corpus directory. Larger files
+    /// are skipped, so a stray large file cannot exhaust the memory. `None` disables the limit.
+    pub const fn set_max_seed_size(mut self, max_seed_size: Option<usize>) -> Self {
+        self.max_seed_size = max_seed_size;
+        self
+    }
+
+    pub const fn set_random_seeds(mut self, random_seeds: RandomSeedConfig) -> Self {
+        self.random_seeds = random_seeds;
+        self
+    }
+
+    /// Sets the number of iterations spent on mutating each newly found crash to discover
+    /// related crashes with a different exit code. `None` disables the exploration.
+    pub const fn set_crash_exploration(mut self, iterations: Option<usize>) -> Self {
+        self.crash_exploration = iterations;
+        self
+    }
+
+    /// Enables coverage feedback. The target gets the id of a shared memory edge bitmap in
+    /// `__AFL_SHM_ID` and test cases covering new edges are added to the corpus.
+    pub const fn set_coverage(mut self, coverage: bool) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Enables the in-process persistent mode, in which the harness set via `set_harness_path`
+    /// is called directly instead of spawning the target for every test case.
+    pub const fn set_persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    /// Sets the shared library used in persistent mode. It has to export the libFuzzer entry
+    /// point `int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size)`.
+    pub fn set_harness_path<T: AsRef<Path>>(mut self, harness_path: Option<T>) -> Self {
+        if let Some(harness_path) = harness_path {
+            assert!(harness_path.as_ref().is_file(), "Harness does not exist");
+            self.harness_path = Some(harness_path.as_ref().to_str().unwrap().to_string());
+        }
+        self
+    }
+
+    pub const fn set_metrics_port(mut self, metrics_port: Option<u16>) -> Self {
+        self.metrics_port = metrics_port;
+        self
+    }
+
+    /// Sets a file the stats are appended to as one JSON object per line on every update, see
+    /// `StatsSnapshot`. `None` disables the stats file.
+    pub fn set_stats_file(mut self, stats_file: Option<PathBuf>) -> Self {
+        self.stats_file = stats_file;
+        self
+    }
+
+    fn validate_mutator_names(names: &[String]) {
+        for name in names {
+            if let Err(e) = name.parse::<StandardMutators>() {
+                panic!("Error setting mutators: {e}");
+            }
+        }
+    }
+
+    pub fn set_only_mutators(mut self, only_mutators: Vec<String>) -> Self {
+        Self::validate_mutator_names(&only_mutators);
+        self.only_mutators = only_mutators;
+        self
+    }
+
+    pub fn set_disabled_mutators(mut self, disabled_mutators: Vec<String>) -> Self {
+        Self::validate_mutator_names(&disabled_mutators);
+        self.disabled_mutators = disabled_mutators;
+        self
+    }
+}
+
+/// The stats of a fuzzing run at a single point in time.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StatsSnapshot {
+    // Seconds since the stats were created
+    pub elapsed: f64,
+    pub iterations: usize,
+    pub execs_per_sec: f64,
+    pub crashes: usize,
+    pub unique_crashes: usize,
+    pub max_seen_size: usize,
+}
+
+impl StatsSnapshot {
+    /// Appends the snapshot as a single line of JSON to `path`, creating the file if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use executor::FuzzerStats;
+    ///
+    /// let path = std::env::temp_dir().join(format!("hantu_stats_{}.jsonl", std::process::id()));
+    /// let stats = FuzzerStats::new();
+    /// stats.inc_iterations_by(42);
+    /// stats.snapshot().append_to(&path).unwrap();
+    /// stats.snapshot().append_to(&path).unwrap();
+    /// let lines = std::fs::read_to_string(&path).unwrap();
+    /// assert_eq!(lines.lines().count(), 2);
+    /// assert!(lines.contains("\"iterations\":42"));
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn append_to<T: AsRef<Path>>(&self, path: T) -> Result<()> {
+        let path = path.as_ref();
+        let mut line = serde_json::to_string(self)
+            .map_err(|e| Error::new(&format!("Serializing stats: {e}")))?;
+        line.push('\n');
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .map_err(|e| Error::new(&format!("Writing stats to {}: {e}", path.display())))
+    }
+}
+
+/// Corpus entries the workers share with each other, e.g. test cases covering new edges. Entries
+/// are only appended, so every worker keeps a cursor of the entries it already pulled.
+///
+/// # Example
+///
+/// ```
+/// use executor::SharedCorpus;
+///
+/// let shared = SharedCorpus::default();
+/// let (mut cursor0, mut cursor1) = (0, 0);
+/// shared.share(0, vec![b"new edge".to_vec()]);
+/// assert!(shared.pull(0, &mut cursor0).is_empty());
+/// assert_eq!(shared.pull(1, &mut cursor1), vec![b"new edge".to_vec()]);
+/// assert!(shared.pull(1, &mut cursor1).is_empty());
+/// assert_eq!(shared.received(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedCorpus {
+    // Shared entries with the id of the worker that found them
+    entries: Mutex<Vec<(usize, Vec<u8>)>>,
+    // Number of entries pulled by workers other than the one that found them
+    received: AtomicUsize,
+}
+
+impl SharedCorpus {
+    /// Shares the entries found by worker `thr_id` with the other workers.
+    pub fn share(&self, thr_id: usize, entries: Vec<Vec<u8>>) {
+        if entries.is_empty() {
+            return;
+        }
+        let mut shared = self.entries.lock().unwrap();
+        shared.extend(entries.into_iter().map(|tc| (thr_id, tc)));
+    }
+
+    /// Returns the entries shared by workers other than `thr_id` since `cursor` and advances
+    /// `cursor` past all shared entries.
+    pub fn pull(&self, thr_id: usize, cursor: &mut usize) -> Vec<Vec<u8>> {
+        let shared = self.entries.lock().unwrap();
+        let new = shared[*cursor..]
+            .iter()
+            .filter(|(id, _)| *id != thr_id)
+            .map(|(_, tc)| tc.clone())
+            .collect::<Vec<_>>();
+        *cursor = shared.len();
+        self.received
+            .fetch_add(new.len(), std::sync::atomic::Ordering::SeqCst);
+        new
+    }
+
+    /// Returns the number of shared entries.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns whether no entries were shared yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of entries workers pulled from their peers.
+    pub fn received(&self) -> usize {
+        self.received.load(std::sync::atomic::Ordering::SeqCst)
+    }
 }
 
-#[derive(Default)]
 pub struct FuzzerStats {
+    start_time: Instant,
     iterations: AtomicUsize,
     crashes: AtomicUsize,
+    // Hashes of the crashing inputs saved so far, each is only written to disk once
+    crash_hashes: Mutex<BTreeSet<u64>>,
+    max_seen_size: AtomicUsize,
+    mutator_executions: Mutex<BTreeMap<StandardMutators, usize>>,
+    // Set to ask the workers to stop after their current batch
+    stop: AtomicBool,
+    // Entries the workers exchange after every batch
+    shared_corpus: SharedCorpus,
+}
+
+impl Default for FuzzerStats {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FuzzerStats {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
+            start_time: Instant::now(),
             iterations: AtomicUsize::new(0),
             crashes: AtomicUsize::new(0),
+            crash_hashes: Mutex::new(BTreeSet::new()),
+            max_seen_size: AtomicUsize::new(0),
+            mutator_executions: Mutex::new(BTreeMap::new()),
+            stop: AtomicBool::new(false),
+            shared_corpus: SharedCorpus::default(),
         }
     }
 
@@ -187,6 +592,53 @@ impl FuzzerStats {
             .fetch_add(n, std::sync::atomic::Ordering::SeqCst);
     }
 
+    /// Returns the corpus entries the workers share with each other.
+    pub const fn shared_corpus(&self) -> &SharedCorpus {
+        &self.shared_corpus
+    }
+
+    /// Asks the workers to stop after their current batch.
+    pub fn request_stop(&self) {
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.stop.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Claims up to `n` iterations for the next batch of a worker, without exceeding `max_iter`
+    /// iterations in total across all workers.
+    ///
+    /// # Returns
+    ///
+    /// The number of claimed iterations, 0 once `max_iter` is reached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use executor::FuzzerStats;
+    ///
+    /// let stats = FuzzerStats::new();
+    /// assert_eq!(stats.claim_iterations(1000, Some(2500)), 1000);
+    /// assert_eq!(stats.claim_iterations(1000, Some(2500)), 1000);
+    /// assert_eq!(stats.claim_iterations(1000, Some(2500)), 500);
+    /// assert_eq!(stats.claim_iterations(1000, Some(2500)), 0);
+    /// assert_eq!(stats.claim_iterations(1000, None), 1000);
+    /// ```
+    pub fn claim_iterations(&self, n: usize, max_iter: Option<usize>) -> usize {
+        let Some(max_iter) = max_iter else {
+            self.inc_iterations_by(n);
+            return n;
+        };
+        self.iterations
+            .fetch_update(
+                std::sync::atomic::Ordering::SeqCst,
+                std::sync::atomic::Ordering::SeqCst,
+                |cur| (cur < max_iter).then(|| cur + n.min(max_iter - cur)),
+            )
+            .map_or(0, |cur| n.min(max_iter - cur))
+    }
+
     pub fn inc_crashes(&self) {
         self.crashes
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -195,46 +647,397 @@ impl FuzzerStats {
     pub fn get_crashes(&self) -> usize {
         self.crashes.load(std::sync::atomic::Ordering::SeqCst)
     }
+
+    /// Counts a crash caused by `data` and checks whether the input crashed before.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no crash was recorded for `data` yet, i.e., it should be saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use executor::FuzzerStats;
+    ///
+    /// let stats = FuzzerStats::new();
+    /// assert!(stats.record_crash(b"BOOM"));
+    /// assert!(!stats.record_crash(b"BOOM"));
+    /// assert!(stats.record_crash(b"BANG"));
+    /// assert_eq!(stats.get_crashes(), 3);
+    /// assert_eq!(stats.get_unique_crashes(), 2);
+    /// ```
+    pub fn record_crash(&self, data: &[u8]) -> bool {
+        self.inc_crashes();
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        self.crash_hashes.lock().unwrap().insert(hasher.finish())
+    }
+
+    /// Returns the number of distinct crashing inputs.
+    pub fn get_unique_crashes(&self) -> usize {
+        self.crash_hashes.lock().unwrap().len()
+    }
+
+    /// Raises the high-water mark of test case sizes seen by the workers to `size` if larger.
+    pub fn update_max_seen_size(&self, size: usize) {
+        self.max_seen_size
+            .fetch_max(size, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn get_max_seen_size(&self) -> usize {
+        self.max_seen_size.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Average number of target executions per second since the stats were created.
+    pub fn get_execs_per_sec(&self) -> f64 {
+        self.get_iterations() as f64 / self.start_time.elapsed().as_secs_f64()
+    }
+
+    /// Takes a snapshot of the current stats.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let iterations = self.get_iterations();
+        StatsSnapshot {
+            elapsed,
+            iterations,
+            execs_per_sec: iterations as f64 / elapsed,
+            crashes: self.get_crashes(),
+            unique_crashes: self.get_unique_crashes(),
+            max_seen_size: self.get_max_seen_size(),
+        }
+    }
+
+    /// Adds the per-mutator execution counts reported by a worker.
+    pub fn add_mutator_executions(&self, executions: BTreeMap<StandardMutators, usize>) {
+        let mut total = self.mutator_executions.lock().unwrap();
+        for (mutator, n) in executions {
+            *total.entry(mutator).or_default() += n;
+        }
+    }
+
+    /// Renders the stats in the Prometheus text exposition format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use executor::FuzzerStats;
+    /// use mutation_engine::StandardMutators;
+    ///
+    /// let stats = FuzzerStats::new();
+    /// stats.inc_iterations_by(42);
+    /// stats.add_mutator_executions([(StandardMutators::ChangeBit, 7)].into());
+    /// let metrics = stats.prometheus_metrics();
+    /// assert!(metrics.contains("hantu_iterations_total 42\n"));
+    /// assert!(metrics.contains("hantu_mutator_executions_total{mutator=\"ChangeBit\"} 7\n"));
+    /// ```
+    pub fn prometheus_metrics(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: &dyn std::fmt::Display| {
+            let _ = write!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+            );
+        };
+        metric(
+            "hantu_iterations_total",
+            "counter",
+            "Total number of target executions.",
+            &self.get_iterations(),
+        );
+        metric(
+            "hantu_crashes_total",
+            "counter",
+            "Total number of crashing executions.",
+            &self.get_crashes(),
+        );
+        metric(
+            "hantu_unique_crashes_total",
+            "counter",
+            "Number of distinct crashing inputs.",
+            &self.get_unique_crashes(),
+        );
+        metric(
+            "hantu_execs_per_second",
+            "gauge",
+            "Average number of target executions per second.",
+            &self.get_execs_per_sec(),
+        );
+        metric(
+            "hantu_max_seen_size_bytes",
+            "gauge",
+            "Size of the largest test case seen so far.",
+            &self.get_max_seen_size(),
+        );
+
+        let executions = self.mutator_executions.lock().unwrap();
+        if !executions.is_empty() {
+            out.push_str(
+                "# HELP hantu_mutator_executions_total Number of times each mutator was applied.\n\
+                 # TYPE hantu_mutator_executions_total counter\n",
+            );
+            for (mutator, n) in executions.iter() {
+                let _ = writeln!(
+                    out,
+                    "hantu_mutator_executions_total{{mutator=\"{mutator:?}\"}} {n}"
+                );
+            }
+        }
+        out
+    }
 }
 
-fn load_corpus_from_disk<T: AsRef<Path> + std::convert::AsRef<std::ffi::OsStr>>(
-    p: T,
-) -> Arc<Vec<Vec<u8>>> {
-    let mut corpus = BTreeSet::new();
-    if Path::new(&p).is_dir() {
-        let _ = std::fs::read_dir(&p).map(|dir| {
-            dir.map(|entry| {
-                entry.map(|e| {
-                    let path = e.path();
-                    if path.is_file() {
-                        let _ = std::fs::read(path)
-                            .map_err(Error::ReadingTestcase)
-                            .map(|tc| corpus.insert(tc));
-                    }
-                })
-            })
-        });
-    } else if Path::new(&p).is_file() {
-        let _ = std::fs::read(p)
-            .map_err(Error::ReadingTestcase)
-            .map(|tc| corpus.insert(tc));
+/// Serves `fstats` in the Prometheus text format via HTTP on `127.0.0.1:port` from a background
+/// thread. Every request is answered with the current metrics regardless of its path.
+///
+/// # Errors
+///
+/// Returns an error if binding to the port fails.
+pub fn serve_metrics(fstats: &Arc<FuzzerStats>, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    let fstats = fstats.clone();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.set_read_timeout(Some(METRICS_TIMEOUT));
+            let _ = stream.set_write_timeout(Some(METRICS_TIMEOUT));
+            // The request itself is irrelevant, only drain what the client sent so far
+            let mut req = [0u8; 1024];
+            let _ = stream.read(&mut req);
+            let body = fstats.prometheus_metrics();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    Ok(())
+}
+
+// Stats whose workers are stopped by the handler installed via `install_stop_handler`
+static STOP_STATS: OnceLock<Arc<FuzzerStats>> = OnceLock::new();
+
+/// Installs a handler for SIGINT and SIGTERM that asks the workers sharing `fstats` to stop, so
+/// they can save their corpus additions before exiting. A second signal terminates the process
+/// right away.
+///
+/// # Errors
+///
+/// Returns an error if a stop handler was already installed.
+pub fn install_stop_handler(fstats: &Arc<FuzzerStats>) -> Result<()> {
+    STOP_STATS
+        .set(fstats.clone())
+        .map_err(|_| Error::new("Stop handler already installed"))?;
+    for sig in [libc::SIGINT, libc::SIGTERM] {
+        // SAFETY: `on_stop` only performs an atomic store
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(sig, &action, std::ptr::null_mut());
+        }
+    }
+    Ok(())
+}
+
+extern "C" fn on_stop(_sig: libc::c_int) {
+    if let Some(fstats) = STOP_STATS.get() {
+        fstats.request_stop();
+    }
+}
+
+fn is_crash_file(p: &Path) -> bool {
+    p.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(CRASH_FILE_PREFIX))
+}
+
+/// Imports the seeds of a corpus directory written by hantu or another fuzzer.
+///
+/// * `CorpusFormat::Hantu` reads every file directly within `path`.
+/// * `CorpusFormat::Afl` recursively reads all `id:...` (or `id_...`) named files, so `path` may
+///   be an AFL output directory, a single instance directory or a `queue/` directory. Note that
+///   the `crashes/` and `hangs/` directories use the same naming and are imported as well.
+/// * `CorpusFormat::LibFuzzer` recursively reads all files except libFuzzer's `crash-`, `leak-`,
+///   `timeout-`, `oom-` and `slow-unit-` artifacts.
+///
+/// Hidden files and directories, such as AFL's `.state/` or `.cur_input`, are skipped for the
+/// recursive formats and symlinks are not followed. Only the raw bytes of each seed are imported,
+/// metadata encoded in file names, like AFL's `orig:` or `op:` fields, is dropped. Empty and
+/// duplicate seeds are discarded, as are files that cannot be read or are larger than
+/// `max_seed_size` bytes.
+///
+/// # Arguments
+///
+/// * `path` - The corpus directory to import.
+/// * `format` - The `CorpusFormat` of the directory.
+/// * `max_seed_size` - The maximum size of a seed in bytes, `None` for no limit.
+///
+/// # Returns
+///
+/// A `Result<Arc<Vec<Vec<u8>>>>` holding the imported seeds.
+///
+/// # Errors
+///
+/// * If `path` is not a directory, an `Err(NotADir)` is returned.
+/// * If a directory cannot be read, an `Err(IoError)` is returned.
+/// * If no seed is imported, an `Err(EmptyCorpus)` is returned.
+///
+/// # Example
+///
+/// ```
+/// use executor::{import_corpus_dir, CorpusFormat};
+///
+/// let dir = std::env::temp_dir().join(format!("hantu_afl_import_{}", std::process::id()));
+/// std::fs::create_dir_all(dir.join("default/queue/.state")).unwrap();
+/// std::fs::write(dir.join("default/queue/id:000000,time:0,orig:seed"), b"seed").unwrap();
+/// std::fs::write(dir.join("default/queue/.state/id:000000"), b"state").unwrap();
+/// std::fs::write(dir.join("default/fuzzer_stats"), b"stats").unwrap();
+///
+/// let corpus = import_corpus_dir(&dir, CorpusFormat::Afl, None).unwrap();
+/// assert_eq!(*corpus, vec![b"seed".to_vec()]);
+/// std::fs::remove_dir_all(dir).unwrap();
+/// ```
+pub fn import_corpus_dir<T: AsRef<Path>>(
+    path: T,
+    format: CorpusFormat,
+    max_seed_size: Option<usize>,
+) -> Result<Arc<Vec<Vec<u8>>>> {
+    let seeds = import_seeds(path.as_ref(), format, max_seed_size)?;
+    Ok(Arc::new(seeds.into_keys().collect()))
+}
+
+// Imports the seeds of a corpus directory like `import_corpus_dir`, mapping every seed to whether
+// it was read from a crash file
+fn import_seeds(
+    path: &Path,
+    format: CorpusFormat,
+    max_seed_size: Option<usize>,
+) -> Result<BTreeMap<Vec<u8>, bool>> {
+    if !path.is_dir() {
+        return Err(Error::NotADir(path.display().to_string()));
+    }
+    let files = seed_files(path, format)?;
+    let mut corpus = BTreeMap::new();
+    let mut skipped = 0;
+    for file in &files {
+        // Check the size before reading, so an oversized file is never loaded
+        let fits = max_seed_size
+            .is_none_or(|max| fs::metadata(file).is_ok_and(|meta| meta.len() <= max as u64));
+        if !fits {
+            skipped += 1;
+            continue;
+        }
+        match fs::read(file) {
+            Ok(tc) if !tc.is_empty() => {
+                *corpus.entry(tc).or_insert(false) |= is_crash_file(file);
+            }
+            _ => skipped += 1,
+        }
+    }
+    println!(
+        "[HANTU] Loaded {} seeds from {} files in {}, skipped {skipped} files",
+        corpus.len(),
+        files.len(),
+        path.display()
+    );
+    if corpus.is_empty() {
+        return Err(Error::EmptyCorpus(path.display().to_string()));
+    }
+    Ok(corpus)
+}
+
+// Lists the seed files of a corpus directory in the given format, see `import_corpus_dir`
+fn seed_files(path: &Path, format: CorpusFormat) -> Result<Vec<PathBuf>> {
+    let is_seed: fn(&str) -> bool = match format {
+        CorpusFormat::Hantu => {
+            return Ok(fs::read_dir(path)?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect())
+        }
+        CorpusFormat::Afl => |name| name.starts_with("id:") || name.starts_with("id_"),
+        CorpusFormat::LibFuzzer => |name| {
+            !LIBFUZZER_ARTIFACT_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        },
     };
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)?.flatten() {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() && is_seed(&name) {
+                files.push(entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
 
-    corpus.retain(|x| !x.is_empty());
-    Arc::new(corpus.into_iter().collect())
+// Imported seeds along with the indices of the seeds read from crash files
+type Seeds = (Arc<Vec<Vec<u8>>>, Vec<usize>);
+
+/// Imports the corpus directory of the config along with the indices of the seeds read from crash
+/// files. Without any seeds on disk the corpus starts out empty if random seeds are enabled, as
+/// they fill it later on.
+fn load_seeds(fconfig: &FuzzerConfig) -> Result<Seeds> {
+    match import_seeds(
+        Path::new(&fconfig.corpus_dir),
+        fconfig.corpus_format,
+        fconfig.max_seed_size,
+    ) {
+        Ok(seeds) => {
+            let crashes = seeds
+                .values()
+                .enumerate()
+                .filter_map(|(i, &is_crash)| is_crash.then_some(i))
+                .collect();
+            Ok((Arc::new(seeds.into_keys().collect()), crashes))
+        }
+        Err(Error::EmptyCorpus(_)) if fconfig.random_seeds.count > 0 => {
+            Ok((Arc::new(Vec::new()), Vec::new()))
+        }
+        Err(e) => Err(e),
+    }
 }
 
-fn get_mutation_engine(corp: &Arc<Vec<Vec<u8>>>, fuzz_config: &FuzzerConfig) -> MutationEngine {
+fn get_mutation_engine(
+    corp: &Arc<Vec<Vec<u8>>>,
+    crashes: &[usize],
+    fuzz_config: &FuzzerConfig,
+    thr_id: usize,
+) -> Result<MutationEngine> {
     let mut me = MutationEngine::new()
         .set_corpus(corp.clone())
         .set_generator(&fuzz_config.generator)
         .set_generator_seed(fuzz_config.seed)
+        .set_worker_stream(thr_id)
         .set_mutation_passes(fuzz_config.mutation_passes)
         .set_max_test_case_size(fuzz_config.max_length)
         .set_printable(fuzz_config.printable);
     if let Some(ref dict) = fuzz_config.dict {
         me = me.set_token_dict(dict);
     }
+    if !fuzz_config.only_mutators.is_empty() {
+        me = me.set_mutators_by_name(&fuzz_config.only_mutators)?;
+    }
+    if !fuzz_config.disabled_mutators.is_empty() {
+        me = me.disable_mutators_by_name(&fuzz_config.disabled_mutators)?;
+    }
     let mut custom_mutators = Vec::new();
     if fuzz_config.ni_mutator {
         custom_mutators.push(CustomMutators::Ni);
@@ -250,36 +1053,174 @@ fn get_mutation_engine(corp: &Arc<Vec<Vec<u8>>>, fuzz_config: &FuzzerConfig) ->
         me = me.enable_custom_mutators(custom_mutators);
     }
 
-    for _ in 0..128 {
-        let tc_sz = me.prng.rand_range(0, 98304);
+    let random_seeds = fuzz_config.random_seeds;
+    for _ in 0..random_seeds.count {
+        let tc_sz = random_seeds.size_dist.sample(&mut me.prng);
         let tc = me.prng.rand_byte_vec(tc_sz);
         me.add_to_corpus(&tc);
     }
-    me
+    if me.corpus.is_empty() {
+        return Err(Error::new(
+            "Corpus is empty, provide seeds in the corpus directory or enable random seeds",
+        ));
+    }
+
+    if fuzz_config.focus_crashes {
+        println!("[HANTU] Focusing on {} known crashes", crashes.len());
+        for &idx in crashes {
+            me.set_energy_for(idx, CRASH_FOCUS_ENERGY)?;
+        }
+    }
+    Ok(me)
 }
 
-pub fn spawn_workers(fconfig: &FuzzerConfig, fstats: &Arc<FuzzerStats>) -> Result<()> {
+/// Generates test cases with the mutation engine a worker would use, without executing the
+/// target, e.g. to inspect the output of the configured mutators or grammar. Every test case is
+/// handed to `on_test_case` as soon as it is generated, so none of them are kept in memory.
+///
+/// # Arguments
+///
+/// * `fconfig` - The fuzzer config the mutation engine is built from.
+/// * `count` - The number of test cases to generate.
+/// * `on_test_case` - Called with the index and data of every generated test case.
+///
+/// # Errors
+///
+/// Returns an error if the corpus cannot be imported, the mutation engine cannot be built or
+/// `on_test_case` fails.
+///
+/// # Example
+///
+/// ```
+/// use executor::{dry_run, FuzzerConfig, RandomSeedConfig};
+///
+/// let dir = std::env::temp_dir().join("hantu_dry_run_doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("seed"), b"seed").unwrap();
+/// let config = FuzzerConfig::default()
+///     .set_corpus_dir(dir.to_str().unwrap())
+///     .set_random_seeds(RandomSeedConfig {
+///         count: 0,
+///         ..Default::default()
+///     })
+///     .set_mutation_passes(1)
+///     .set_max_length(64);
+/// let mut generated = 0;
+/// dry_run(&config, 10, |_, _| {
+///     generated += 1;
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert_eq!(generated, 10);
+/// std::fs::remove_dir_all(dir).unwrap();
+/// ```
+pub fn dry_run(
+    fconfig: &FuzzerConfig,
+    count: usize,
+    mut on_test_case: impl FnMut(usize, &[u8]) -> Result<()>,
+) -> Result<()> {
+    let (corpus, crashes) = load_seeds(fconfig)?;
+    let mut me = get_mutation_engine(&corpus, &crashes, fconfig, 0)?.set_random_test_case();
+    for i in 0..count {
+        on_test_case(i, &me.mutate().data)?;
+    }
+    Ok(())
+}
+
+/// Spawns a worker thread for every configured core. The workers run until the configured
+/// `max_iter` iterations are done or `FuzzerStats::request_stop` is called, see `join_workers`.
+///
+/// # Returns
+///
+/// The join handles of the workers.
+pub fn spawn_workers(
+    fconfig: &FuzzerConfig,
+    fstats: &Arc<FuzzerStats>,
+) -> Result<Vec<JoinHandle<Result<()>>>> {
+    let mut handles = Vec::with_capacity(fconfig.threads.len());
     for (thr_id, &core_id) in fconfig.threads.iter().enumerate() {
         println!("[HANTU] Spawning a worker on core {core_id:?}");
         let mut fconfig = fconfig.clone();
         let fstats = fstats.clone();
-        let _handle = thread::spawn(move || {
-            set_core_affinity(&core_id).unwrap();
-            worker(&mut fconfig, &fstats, thr_id).expect("Worker deployment successfully");
-        });
+        handles.push(thread::spawn(move || {
+            set_core_affinity(&core_id)?;
+            worker(&mut fconfig, &fstats, thr_id)
+        }));
     }
-    Ok(())
+    Ok(handles)
+}
+
+/// Waits for the workers returned by `spawn_workers` to exit.
+///
+/// # Errors
+///
+/// Returns the first error a worker failed with, or `Error::JoiningThread` if a worker panicked.
+pub fn join_workers(handles: Vec<JoinHandle<Result<()>>>) -> Result<()> {
+    let mut res = Ok(());
+    for handle in handles {
+        let worker_res = handle.join().unwrap_or(Err(Error::JoiningThread));
+        if res.is_ok() {
+            res = worker_res;
+        }
+    }
+    res
+}
+
+/// Writes a corpus entry to `corpus_dir`, named by the 64-bit FNV-1a hash of its contents. Entries
+/// that are already saved are not written again, which also dedups entries across runs.
+///
+/// # Returns
+///
+/// The path of the entry.
+///
+/// # Errors
+///
+/// Returns an error if the entry cannot be written.
+///
+/// # Example
+///
+/// ```
+/// use executor::save_corpus_entry;
+///
+/// let dir = std::env::temp_dir();
+/// let path = save_corpus_entry(dir.to_str().unwrap(), b"hantu").unwrap();
+/// assert_eq!(path, dir.join("81eb6c69307e289f"));
+/// assert_eq!(save_corpus_entry(dir.to_str().unwrap(), b"hantu").unwrap(), path);
+/// assert_eq!(std::fs::read(&path).unwrap(), b"hantu");
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn save_corpus_entry(corpus_dir: &str, data: &[u8]) -> Result<PathBuf> {
+    let path = Path::new(corpus_dir).join(format!("{:016x}", fnv1a(data)));
+    if !path.exists() {
+        fs::write(&path, data).map_err(Error::WritingTestcase)?;
+    }
+    Ok(path)
 }
 
-fn fuzz_from_file<T: AsRef<Path>>(
-    put: &str,
-    put_args: &str,
-    put_inp: T,
-    tc: &mut TestCase,
+// Hashes `data` with the 64-bit FNV-1a hash, which unlike `DefaultHasher` is stable across
+// toolchains
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Prints the final stats of a stopped worker.
+fn finish_worker(saved: usize, thr_id: usize, iterations: usize) {
+    println!(
+        "[HANTU] Worker {thr_id} stopped after {iterations} iterations, saved {saved} new corpus entries"
+    );
+}
+
+fn fuzz_from_file(
+    mut put: Command,
+    put_args: &[String],
+    put_inp: &str,
+    data: &[u8],
 ) -> Result<Child> {
-    fs::write(put_inp.as_ref(), tc.data.as_slice()).map_err(Error::WritingTestcase)?;
-    let child = Command::new(put)
-        .args(vec![put_args])
+    fs::write(put_inp, data).map_err(Error::WritingTestcase)?;
+    let child = put
+        .args(put_args)
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -288,20 +1229,10 @@ fn fuzz_from_file<T: AsRef<Path>>(
     Ok(child)
 }
 
-fn fuzz_from_stdin<T: AsRef<Path>>(
-    put: &str,
-    put_args: &str,
-    _: T,
-    tc: &mut TestCase,
-) -> Result<Child> {
-    let inp = unsafe { std::str::from_utf8_unchecked(tc.data.as_slice()) };
-    let args = if put_args.is_empty() {
-        vec![inp]
-    } else {
-        vec![put_args, inp]
-    };
-    let child = Command::new(put)
-        .args(args)
+fn fuzz_from_stdin(mut put: Command, put_args: &[String], _: &str, data: &[u8]) -> Result<Child> {
+    let child = put
+        .args(put_args)
+        .arg(OsStr::from_bytes(data))
         .stdin(Stdio::piped())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -310,68 +1241,1024 @@ fn fuzz_from_stdin<T: AsRef<Path>>(
     Ok(child)
 }
 
+/// Creates the command running `put` with `env` added to its environment. `ASAN_OPTIONS`
+/// defaults to `DEFAULT_ASAN_OPTIONS` unless it is set for the fuzzer or in `env`. The address
+/// space and data segment of the target are limited to `mem_limit_mb` MiB if set.
+fn target_command(put: &str, env: &[(String, String)], mem_limit_mb: Option<u64>) -> Command {
+    let mut cmd = Command::new(put);
+    if std::env::var_os("ASAN_OPTIONS").is_none() {
+        cmd.env("ASAN_OPTIONS", DEFAULT_ASAN_OPTIONS);
+    }
+    cmd.envs(env.iter().map(|(k, v)| (k, v)));
+    if let Some(mem_limit_mb) = mem_limit_mb {
+        let limit = libc::rlimit {
+            rlim_cur: mem_limit_mb << 20,
+            rlim_max: mem_limit_mb << 20,
+        };
+        // SAFETY: `setrlimit` is async-signal-safe and `limit` is copied into the closure
+        unsafe {
+            cmd.pre_exec(move || {
+                for resource in [libc::RLIMIT_AS, libc::RLIMIT_DATA] {
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+    cmd
+}
+
+// Starts the target on a single input
+type FuzzFn = fn(Command, &[String], &str, &[u8]) -> Result<Child>;
+
+/// Replaces every occurrence of `@@` in `args`, also within an argument like `--in=@@`, with
+/// `inp_file` and selects how the target receives its input. The input is only passed directly
+/// if there is no `@@` at all.
+fn prepare_target(args: &mut [String], inp_file: &str) -> FuzzFn {
+    if !args.iter().any(|arg| arg.contains("@@")) {
+        return fuzz_from_stdin;
+    }
+    for arg in args.iter_mut() {
+        *arg = arg.replace("@@", inp_file);
+    }
+    fuzz_from_file
+}
+
+/// Waits for the target to exit.
+///
+/// # Returns
+///
+/// The crash code of the target if it crashed, see `crash_code`.
+fn wait_for_crash(mut child_proc: Child) -> Result<Option<i32>> {
+    match child_proc.wait().map_err(Error::WaitingForTarget) {
+        Ok(status) => return Ok(crash_code(status)),
+        Err(e) => {
+            println!("Error: {e:?}");
+            let _ = child_proc.kill();
+        }
+    }
+    Ok(None)
+}
+
+// Returns the exit code of the target, or `SIGNAL_CODE_BASE` + the number of the signal that
+// terminated it, if it is one of `CRASH_EXIT_CODES`
+fn crash_code(status: ExitStatus) -> Option<i32> {
+    match (status.code(), status.signal()) {
+        (Some(code), _) if CRASH_EXIT_CODES.contains(&code) => Some(code),
+        (_, Some(sig)) if CRASH_EXIT_CODES.contains(&sig) => Some(SIGNAL_CODE_BASE + sig),
+        _ => None,
+    }
+}
+
+/// Shrinks a crashing `input` by removing ever smaller byte ranges, keeping every removal after
+/// which `exec` still reports the exit code `code`. Stops once no single byte can be removed, or
+/// early with the reductions found so far once `may_exec` denies another execution.
+fn minimize_with(
+    input: &[u8],
+    code: i32,
+    mut may_exec: impl FnMut() -> bool,
+    mut exec: impl FnMut(&[u8]) -> Result<Option<i32>>,
+) -> Result<Vec<u8>> {
+    let mut data = input.to_vec();
+    let mut chunk = (data.len() / 2).max(1);
+    loop {
+        let mut reduced = false;
+        let mut start = 0;
+        while start < data.len() {
+            if !may_exec() {
+                return Ok(data);
+            }
+            let end = (start + chunk).min(data.len());
+            let candidate = [&data[..start], &data[end..]].concat();
+            if exec(&candidate)? == Some(code) {
+                data = candidate;
+                reduced = true;
+            } else {
+                start += chunk;
+            }
+        }
+        if chunk == 1 && !reduced {
+            return Ok(data);
+        }
+        chunk = (chunk / 2).max(1);
+    }
+}
+
+/// Minimizes an input that crashes the target, see `FuzzerConfig::set_target` for how `args`
+/// are handled. A removal is only kept if the target still exits with the same crash exit code.
+///
+/// # Arguments
+///
+/// * `put` - The path of the target.
+/// * `args` - The arguments of the target, `@@` is replaced with a file holding the input.
+/// * `input` - The crashing input.
+///
+/// # Returns
+///
+/// The minimized input.
+///
+/// # Errors
+///
+/// Returns an error if the target cannot be executed or `input` does not crash it.
+pub fn minimize_crash(put: &str, args: &[String], input: &[u8]) -> Result<Vec<u8>> {
+    // Distinguishes the input files of concurrent minimizations
+    static MINIMIZATIONS: AtomicUsize = AtomicUsize::new(0);
+    let n = MINIMIZATIONS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let inp_file = std::env::temp_dir()
+        .join(format!(".tmp_min_{}_{n}", std::process::id()))
+        .to_string_lossy()
+        .into_owned();
+    let mut args = args.to_vec();
+    let fuzz = prepare_target(&mut args, &inp_file);
+    let exec = |data: &[u8]| {
+        wait_for_crash(fuzz(
+            target_command(put, &[], None),
+            &args,
+            &inp_file,
+            data,
+        )?)
+    };
+
+    let res = match exec(input) {
+        Ok(Some(code)) => minimize_with(input, code, || true, exec),
+        Ok(None) => Err(Error::new("Input does not crash the target")),
+        Err(e) => Err(e),
+    };
+    let _ = fs::remove_file(&inp_file);
+    res
+}
+
+/// Runs the configured target once on `input` outside of the fuzzing loop, e.g. to reproduce a
+/// saved crash. The input is passed like during fuzzing, see `FuzzerConfig::set_target`.
+///
+/// # Arguments
+///
+/// * `fconfig` - The fuzzer config holding the target, its arguments and environment.
+/// * `input` - The input to pass to the target.
+///
+/// # Returns
+///
+/// The exit status of the target.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be written or the target cannot be executed.
+///
+/// # Example
+///
+/// ```
+/// use executor::{replay, FuzzerConfig};
+///
+/// let config = FuzzerConfig::default().set_target(vec!["/bin/false".to_string()]);
+/// assert_eq!(replay(&config, b"hantu").unwrap().code(), Some(1));
+/// ```
+pub fn replay(fconfig: &FuzzerConfig, input: &[u8]) -> Result<ExitStatus> {
+    let inp_file = std::env::temp_dir()
+        .join(format!(".tmp_replay_{}", std::process::id()))
+        .to_string_lossy()
+        .into_owned();
+    let mut args = fconfig.target_args.clone();
+    let fuzz = prepare_target(&mut args, &inp_file);
+    let put = target_command(&fconfig.target, &fconfig.env, fconfig.mem_limit_mb);
+    let res = fuzz(put, &args, &inp_file, input)
+        .and_then(|mut child| child.wait().map_err(Error::WaitingForTarget));
+    let _ = fs::remove_file(&inp_file);
+    res
+}
+
 pub fn worker(fconfig: &mut FuzzerConfig, fstats: &Arc<FuzzerStats>, thr_id: usize) -> Result<()> {
-    let corpus = load_corpus_from_disk(&fconfig.corpus_dir);
-    let mut me = get_mutation_engine(&corpus, fconfig);
-    let mut avg_tc_sz = 0;
-    me.corpus.iter().for_each(|x| avg_tc_sz += x.len());
-    avg_tc_sz /= me.corpus.len();
+    let (corpus, crashes) = load_seeds(fconfig)?;
+    let mut me = get_mutation_engine(&corpus, &crashes, fconfig, thr_id)?;
+    println!(
+        "[HANTU] Worker {thr_id} using {:?} with seed {:#x}",
+        me.current_generator(),
+        me.current_seed()
+    );
+    let avg_tc_sz = me.corpus.iter().map(Vec::len).sum::<usize>() / me.corpus.len().max(1);
     println!("[HANTU] Average test case size in corpus: {avg_tc_sz} bytes");
 
-    let inp_ff = format!(".tmp_inp_{thr_id}");
+    if fconfig.persistent {
+        return persistent_worker(&mut me, fconfig, fstats, thr_id);
+    }
+
+    let inp_ff = fconfig
+        .input_dir
+        .as_deref()
+        .unwrap_or_else(|| Path::new(""))
+        .join(format!(".tmp_inp_{thr_id}"))
+        .to_string_lossy()
+        .into_owned();
+
+    if fconfig.forkserver && !fconfig.target_args.iter().any(|arg| arg.contains("@@")) {
+        return Err(Error::new(
+            "The forkserver requires passing the input via @@",
+        ));
+    }
+    let fuzz = prepare_target(&mut fconfig.target_args, &inp_ff);
+
+    me = me.set_random_test_case();
 
-    let fuzz = if let Some(idx) = fconfig
-        .target_args
-        .iter()
-        .position(|x| x == &"@@".to_string())
-    {
-        fconfig.target_args.remove(idx);
-        fconfig.target_args.insert(idx, inp_ff.clone());
-        fuzz_from_file::<&String>
+    let mut coverage = if fconfig.coverage {
+        Some(CoverageMap::new()?)
     } else {
-        fuzz_from_stdin::<&String>
+        None
     };
+    let shm_id = coverage.as_ref().map(CoverageMap::id);
 
-    me = me.set_random_test_case();
-    let targs = fconfig.target_args.join(" ");
+    let command = || {
+        let mut put = target_command(&fconfig.target, &fconfig.env, fconfig.mem_limit_mb);
+        if let Some(shm_id) = shm_id {
+            put.env(SHM_ENV_VAR, shm_id.to_string());
+        }
+        put
+    };
+    let forkserver = if fconfig.forkserver {
+        let mut put = command();
+        put.args(&fconfig.target_args);
+        Some(Forkserver::start(put)?)
+    } else {
+        None
+    };
 
-    loop {
-        for _i in 0..fconfig.batch_sz {
+    // Runs the target on a test case and returns the exit code if it crashed
+    let exec = |data: &[u8]| {
+        if let Some(forkserver) = &forkserver {
+            fs::write(&inp_ff, data).map_err(Error::WritingTestcase)?;
+            return Ok(crash_code(forkserver.run()?));
+        }
+        wait_for_crash(fuzz(command(), &fconfig.target_args, &inp_ff, data)?)
+    };
+    // Hashes of the raw crashing inputs seen by this worker, repeats are not minimized again
+    let mut seen_crashes = BTreeSet::new();
+    // Target executions spent on minimizing crashes, claimed like the iterations of a batch
+    let minimize_execs = Cell::new(0);
+    // Minimizes a crashing input and saves it unless the same input crashed before. Returns
+    // whether the crash was new
+    let mut save_crash = |code: i32, data: &[u8]| -> Result<bool> {
+        println!("Exited with code: {code}");
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        if !seen_crashes.insert(hasher.finish()) {
+            fstats.inc_crashes();
+            return Ok(false);
+        }
+        // Minimize until the budget of the crash or `max_iter` is used up, or the worker stops
+        let budget_end = minimize_execs.get() + MINIMIZE_MAX_EXECS;
+        let may_exec = || {
+            let run = minimize_execs.get() < budget_end
+                && !fstats.is_stopping()
+                && fstats.claim_iterations(1, fconfig.max_iter) == 1;
+            minimize_execs.set(minimize_execs.get() + usize::from(run));
+            run
+        };
+        let data = minimize_with(data, code, may_exec, exec)?;
+        if !fstats.record_crash(&data) {
+            return Ok(false);
+        }
+        let crash_file = format!(
+            "{CRASH_FILE_PREFIX}{thr_id}_{code}_{}",
+            fstats.get_unique_crashes()
+        );
+        fs::write(Path::new(&fconfig.crash_dir).join(crash_file), data)
+            .map_err(Error::WritingTestcase)?;
+        Ok(true)
+    };
+
+    let mut iterations = 0;
+    // Number of entries this worker saved to the corpus directory
+    let mut saved = 0;
+    // Entries covering new edges found in the current batch and the position in the entries
+    // shared by the other workers
+    let mut found = Vec::new();
+    let mut shared_cursor = 0;
+    while !fstats.is_stopping() {
+        let batch_sz = fstats.claim_iterations(fconfig.batch_sz, fconfig.max_iter);
+        if batch_sz == 0 {
+            break;
+        }
+        for _i in 0..batch_sz {
             me.mutate();
+            fstats.update_max_seen_size(me.test_case.size);
 
-            let mut child_proc = fuzz(&fconfig.target, &targs, &inp_ff, &mut me.test_case)?;
-            match child_proc.wait().map_err(Error::WaitingForTarget) {
-                Ok(status) => {
-                    if status.success() {
-                        //println!("exited with status: {exit_code}");
-                        continue;
+            if let Some(coverage) = &mut coverage {
+                coverage.clear();
+            }
+            let crashed = exec(&me.test_case.data)?;
+            if coverage.as_mut().is_some_and(CoverageMap::has_new_coverage) {
+                save_corpus_entry(&fconfig.corpus_dir, &me.test_case.data)?;
+                saved += 1;
+                me.add_to_corpus(&me.test_case.data.clone());
+                me.set_energy_for(me.corpus.len() - 1, COVERAGE_ENERGY)?;
+                found.push(me.test_case.data.clone());
+            }
+            let Some(code) = crashed else {
+                continue;
+            };
+            if !save_crash(code, &me.test_case.data)? {
+                continue;
+            }
+            if let Some(budget) = fconfig.crash_exploration {
+                // Havoc around the new crash and keep crashes with exit codes not seen for it yet
+                let crash = me.test_case.data.clone();
+                let mut seen = BTreeSet::from([code]);
+                let budget = fstats.claim_iterations(budget, fconfig.max_iter);
+                for _ in 0..budget {
+                    if fstats.is_stopping() {
+                        break;
                     }
-                    match status.code() {
-                        Some(code) => {
-                            if [4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15].contains(&code) {
-                                println!("Exited with code: {code}");
-                                fstats.inc_crashes();
-                                let crash_file =
-                                    format!(".crash_{thr_id}_{code}_{}", fstats.get_crashes());
-
-                                fs::write(
-                                    Path::new(&fconfig.crash_dir).join(crash_file),
-                                    me.test_case.data.as_slice(),
-                                )
-                                .unwrap();
-                            }
-                        }
-                        None => {
-                            println!("Exited with signal");
+                    me.mutate_from(&crash);
+                    fstats.update_max_seen_size(me.test_case.size);
+                    if let Some(code) = exec(&me.test_case.data)? {
+                        if seen.insert(code) {
+                            save_crash(code, &me.test_case.data)?;
                         }
                     }
                 }
-                Err(e) => {
-                    println!("Error: {e:?}");
-                    let _ = child_proc.kill();
+                iterations += budget;
+                println!(
+                    "[HANTU] Crash exploration found {} related crashes",
+                    seen.len() - 1
+                );
+            }
+        }
+        iterations += batch_sz + minimize_execs.take();
+        fstats.add_mutator_executions(me.take_mutator_executions());
+
+        // Exchange the entries covering new edges with the other workers once per batch
+        let shared = fstats.shared_corpus();
+        shared.share(thr_id, std::mem::take(&mut found));
+        for tc in shared.pull(thr_id, &mut shared_cursor) {
+            me.add_to_corpus(&tc);
+            me.set_energy_for(me.corpus.len() - 1, COVERAGE_ENERGY)?;
+        }
+    }
+    finish_worker(saved, thr_id, iterations);
+    Ok(())
+}
+
+/// Fuzzes the harness of `fconfig` in-process. The first crash ends the whole process after the
+/// crashing input was written to the crash directory, see the `persistent` module.
+fn persistent_worker(
+    me: &mut MutationEngine,
+    fconfig: &FuzzerConfig,
+    fstats: &Arc<FuzzerStats>,
+    thr_id: usize,
+) -> Result<()> {
+    let harness_path = fconfig
+        .harness_path
+        .as_ref()
+        .ok_or_else(|| Error::new("Persistent mode requires a harness"))?;
+    let harness = Harness::load(harness_path)?;
+    println!("[HANTU] Worker {thr_id} fuzzing {harness_path} in-process");
+    let mut guard = CrashGuard::new(
+        &fconfig.crash_dir,
+        &format!("{CRASH_FILE_PREFIX}{thr_id}_"),
+        &(fstats.get_crashes() + 1).to_string(),
+    );
+
+    let mut iterations = 0;
+    while !fstats.is_stopping() {
+        let batch_sz = fstats.claim_iterations(fconfig.batch_sz, fconfig.max_iter);
+        if batch_sz == 0 {
+            break;
+        }
+        for _i in 0..batch_sz {
+            me.mutate();
+            fstats.update_max_seen_size(me.test_case.size);
+            guard.run(&harness, &me.test_case.data);
+        }
+        iterations += batch_sz;
+        fstats.add_mutator_executions(me.take_mutator_executions());
+    }
+    // The harness has no coverage feedback, so no entries are saved
+    finish_worker(0, thr_id, iterations);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::TcpStream, ops::Range, os::unix::fs::PermissionsExt};
+
+    // Set for the child process of `test_persistent_crash` to its scratch directory
+    const PERSISTENT_DIR_ENV: &str = "HANTU_PERSISTENT_TEST_DIR";
+
+    // Fake instrumented target that marks one edge per distinct input byte in the shared memory
+    // bitmap
+    const COVERAGE_TARGET: &str = r#"
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/shm.h>
+
+int main(int argc, char **argv) {
+    const char *id = getenv("__AFL_SHM_ID");
+    unsigned char *map = id ? shmat(atoi(id), NULL, 0) : NULL;
+    FILE *f = fopen(argv[1], "rb");
+    int c;
+    while ((c = fgetc(f)) != EOF) {
+        if (map) {
+            map[c]++;
+        }
+    }
+    return 0;
+}
+"#;
+
+    // Target implementing the AFL forkserver protocol. It logs every exec and every run of a
+    // forked child, and crashes whenever its input contains an `A`
+    const FORKSERVER_TARGET: &str = r#"
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static void log_line(const char *line) {
+    FILE *log = fopen(getenv("HANTU_FORKSERVER_LOG"), "a");
+    fputs(line, log);
+    fclose(log);
+}
+
+int main(int argc, char **argv) {
+    log_line("exec\n");
+    unsigned int msg = 0;
+    int forked = 0;
+    if (write(199, &msg, 4) == 4) {
+        while (read(198, &msg, 4) == 4) {
+            pid_t pid = fork();
+            if (pid == 0) {
+                close(198);
+                close(199);
+                forked = 1;
+                break;
+            }
+            int status;
+            write(199, &pid, 4);
+            waitpid(pid, &status, 0);
+            write(199, &status, 4);
+        }
+        if (!forked) {
+            return 0;
+        }
+    }
+
+    log_line("run\n");
+    FILE *f = fopen(argv[1], "rb");
+    int c;
+    while ((c = fgetc(f)) != EOF) {
+        if (c == 'A') {
+            abort();
+        }
+    }
+    return 0;
+}
+"#;
+
+    // Target that allocates and touches 256 MiB for every input
+    const MEM_LIMIT_TARGET: &str = r#"
+#include <stdlib.h>
+#include <string.h>
+
+int main(void) {
+    size_t size = 256 << 20;
+    char *buf = malloc(size);
+    if (!buf) {
+        abort();
+    }
+    memset(buf, 1, size);
+    free(buf);
+    return 0;
+}
+"#;
+
+    // Harness that crashes whenever its input contains `BOOM`
+    const CRASH_HARNESS: &str = r#"
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size) {
+    for (size_t i = 0; i + 4 <= size; i++) {
+        if (memcmp(data + i, "BOOM", 4) == 0) {
+            abort();
+        }
+    }
+    return 0;
+}
+"#;
+
+    // Harness that never crashes
+    const NOP_HARNESS: &str = r#"
+#include <stdint.h>
+#include <stddef.h>
+
+int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size) {
+    return 0;
+}
+"#;
+
+    // Scratch directory of a single test holding a corpus directory with a single seed, removed
+    // once the test is done
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str, seed: &[u8]) -> Self {
+            let dir = std::env::temp_dir().join(format!("hantu_{name}_{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("corpus")).unwrap();
+            fs::write(dir.join("corpus/seed"), seed).unwrap();
+            Self(dir)
+        }
+
+        fn join<T: AsRef<Path>>(&self, path: T) -> PathBuf {
+            self.0.join(path)
+        }
+
+        // Writes an executable shell script running `body` and exiting with 0 afterwards
+        fn script(&self, body: &str) -> PathBuf {
+            let target = self.join("target.sh");
+            fs::write(&target, format!("#!/bin/sh\n{body}\nexit 0\n")).unwrap();
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).unwrap();
+            target
+        }
+
+        // Compiles the C `source` of a target executable, or of a harness if `shared` is set.
+        // Returns `None` if the host has no C compiler, the calling test is skipped then
+        fn compile(&self, source: &str, shared: bool) -> Option<PathBuf> {
+            let out = self.join(if shared { "libharness.so" } else { "target" });
+            fs::write(self.join("source.c"), source).unwrap();
+            let mut cc = Command::new("cc");
+            if shared {
+                cc.args(["-shared", "-fPIC"]);
+            }
+            let status = cc.arg("-o").arg(&out).arg(self.join("source.c")).status();
+            match status {
+                Ok(status) => assert!(status.success(), "Compiling the test target failed"),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    eprintln!("Skipping the test, its target requires a C compiler");
+                    return None;
                 }
+                Err(e) => panic!("Running the C compiler failed: {e}"),
             }
+            Some(out)
+        }
+
+        // Returns a reproducible config running a single worker on `target`. The corpus, crash
+        // and input directories are in the scratch directory, so tests may run concurrently
+        fn config(&self, target: &Path) -> FuzzerConfig {
+            FuzzerConfig::default()
+                .set_target(vec![target.to_str().unwrap().to_string(), "@@".to_string()])
+                .set_corpus_dir(self.join("corpus").to_str().unwrap())
+                .set_crash_dir(self.join("crashes").to_str().unwrap())
+                .set_input_dir(Some(self.0.clone()))
+                .set_threads(1)
+                .set_seed(0x1337)
+                .set_mutation_passes(1)
+                .set_random_seeds(RandomSeedConfig {
+                    count: 0,
+                    size_dist: SeedSizeDist::Fixed(0),
+                })
+        }
+
+        // Returns the names of the files in the crash directory
+        fn crash_files(&self) -> Vec<String> {
+            fs::read_dir(self.join("crashes"))
+                .unwrap()
+                .flatten()
+                .map(|e| e.file_name().into_string().unwrap())
+                .collect()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // Runs the workers of `fconfig` until they are done
+    fn fuzz(fconfig: &FuzzerConfig) -> Arc<FuzzerStats> {
+        let fstats = FuzzerStats::new().to_arc();
+        join_workers(spawn_workers(fconfig, &fstats).unwrap()).unwrap();
+        fstats
+    }
+
+    #[test]
+    fn test_crash_code() {
+        // Raw wait statuses hold the exit code in the second byte and the signal in the first
+        let cases = [
+            (0, None),
+            (1 << 8, None),
+            (6 << 8, Some(6)),
+            (11 << 8, Some(11)),
+            (libc::SIGINT, None),
+            (libc::SIGABRT, Some(128 + libc::SIGABRT)),
+            (libc::SIGSEGV, Some(128 + libc::SIGSEGV)),
+        ];
+        for (raw, code) in cases {
+            assert_eq!(crash_code(ExitStatus::from_raw(raw)), code, "{raw:#x}");
+        }
+    }
+
+    #[test]
+    fn test_target_env_and_limits() {
+        // Exits with the code given in the environment, but only if `ASAN_OPTIONS` configures
+        // AddressSanitizer to exit with a crash exit code
+        fn env_target(scratch: &Scratch) -> Option<PathBuf> {
+            Some(scratch.script(
+                r#"case "$ASAN_OPTIONS" in
+    *exitcode=6*) exit "$HANTU_EXIT_CODE" ;;
+esac"#,
+            ))
         }
-        fstats.inc_iterations_by(fconfig.batch_sz);
+        fn mem_limit_target(scratch: &Scratch) -> Option<PathBuf> {
+            scratch.compile(MEM_LIMIT_TARGET, false)
+        }
+        // Name, target, environment, memory limit and the prefix of the crash files if the
+        // target crashes
+        type Case<'a> = (
+            &'a str,
+            fn(&Scratch) -> Option<PathBuf>,
+            &'a [(&'a str, &'a str)],
+            Option<u64>,
+            Option<&'a str>,
+        );
+        let cases: [Case; 5] = [
+            // The default ASAN_OPTIONS are in place and the configured exit code takes effect
+            (
+                "env_crash",
+                env_target,
+                &[("HANTU_EXIT_CODE", "7")],
+                None,
+                Some(".crash_0_7_"),
+            ),
+            (
+                "env_no_crash",
+                env_target,
+                &[("HANTU_EXIT_CODE", "0")],
+                None,
+                None,
+            ),
+            // The default ASAN_OPTIONS can be overridden
+            (
+                "env_asan_options",
+                env_target,
+                &[("HANTU_EXIT_CODE", "7"), ("ASAN_OPTIONS", "exitcode=1")],
+                None,
+                None,
+            ),
+            ("mem_limit_none", mem_limit_target, &[], None, None),
+            // The target is killed by the abort after its allocation failed
+            (
+                "mem_limit",
+                mem_limit_target,
+                &[],
+                Some(64),
+                Some(".crash_0_134_"),
+            ),
+        ];
+        for (name, target, env, mem_limit_mb, crash_prefix) in cases {
+            let scratch = Scratch::new(name, b"hantu");
+            let Some(target) = target(&scratch) else {
+                continue;
+            };
+            let fconfig = scratch
+                .config(&target)
+                .set_env(
+                    env.iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                )
+                .set_mem_limit_mb(mem_limit_mb)
+                .set_batch_sz(10)
+                .set_max_iter(Some(20))
+                .set_only_mutators(vec!["ChangeByte".to_string()]);
+            let fstats = fuzz(&fconfig);
+            let crash_files = scratch.crash_files();
+            if let Some(prefix) = crash_prefix {
+                assert!(fstats.get_crashes() > 0, "{name}");
+                assert!(!crash_files.is_empty(), "{name}");
+                assert!(
+                    crash_files.iter().all(|f| f.starts_with(prefix)),
+                    "{name}: {crash_files:?}"
+                );
+            } else {
+                assert_eq!(fstats.get_crashes(), 0, "{name}");
+                assert!(crash_files.is_empty(), "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_repeated_crashes() {
+        // Crash exploration, batch size, `max_iter` and the range of the reported crashes for a
+        // target that crashes on every input, so the same inputs crash over and over again
+        let cases: [(Option<usize>, usize, usize, Range<usize>); 2] = [
+            // Only the first crash of each of the at most 8 distinct bit flips of the seed is
+            // minimized, which takes a single run for a one byte input
+            (None, 100, 1000, 992..1000),
+            // Only the first crash is explored, the others repeat the code seen for it
+            (Some(100), 50, 300, 1..300),
+        ];
+        for (exploration, batch_sz, max_iter, crashes) in cases {
+            let scratch = Scratch::new("repeated_crashes", b"A");
+            let runs = scratch.join("runs");
+            let target = scratch.script(&format!("echo >> {}\nexit 6", runs.display()));
+            let fconfig = scratch
+                .config(&target)
+                .set_batch_sz(batch_sz)
+                .set_max_iter(Some(max_iter))
+                .set_only_mutators(vec!["ChangeBit".to_string()])
+                .set_crash_exploration(exploration);
+            let fstats = fuzz(&fconfig);
+
+            // Every input is minimized to the same empty input. The minimization and exploration
+            // runs count towards `max_iter`
+            assert_eq!(fstats.get_unique_crashes(), 1);
+            assert_eq!(scratch.crash_files().len(), 1);
+            assert_eq!(fs::read_to_string(runs).unwrap().lines().count(), max_iter);
+            assert_eq!(fstats.get_iterations(), max_iter);
+            assert!(crashes.contains(&fstats.get_crashes()), "{exploration:?}");
+        }
+    }
+
+    #[test]
+    fn test_minimize_crash() {
+        // Scripts crashing whenever the input they received contains an `A`, and their arguments
+        let cases: [(&str, &[&str]); 4] = [
+            (r#"grep -q A "$1" && exit 6"#, &["@@"]),
+            (
+                r#"[ "$1" = "-v" ] && grep -q A "${2#--in=}" && exit 6"#,
+                &["-v", "--in=@@"],
+            ),
+            (
+                r#"[ "$1" = "$2" ] && grep -q A "$1" && exit 6"#,
+                &["@@", "@@"],
+            ),
+            // Without a placeholder the input is passed as the last argument
+            (
+                r#"[ "$1" = "-v" ] && case "$2" in *A*) exit 6 ;; esac"#,
+                &["-v"],
+            ),
+        ];
+        for (script, args) in cases {
+            let scratch = Scratch::new("minimize", b"");
+            let target = scratch.script(script);
+            let target = target.to_str().unwrap();
+            let args: Vec<_> = args.iter().map(ToString::to_string).collect();
+            assert_eq!(
+                minimize_crash(target, &args, b"hello A world").unwrap(),
+                b"A",
+                "{args:?}"
+            );
+            // Inputs that do not crash cannot be minimized
+            assert!(minimize_crash(target, &args, b"hello").is_err());
+        }
+    }
+
+    #[test]
+    fn test_workers_respect_max_iter() {
+        let scratch = Scratch::new("max_iter", b"hantu");
+        let fconfig = scratch
+            .config(Path::new("/bin/true"))
+            .set_batch_sz(64)
+            .set_max_iter(Some(5000))
+            .set_max_length(64);
+        // 5000 is not a multiple of the batch size, the last batch is cut short
+        assert_eq!(fuzz(&fconfig).get_iterations(), 5000);
+    }
+
+    #[test]
+    fn test_stop_workers() {
+        let scratch = Scratch::new("shutdown", b"hantu");
+        let Some(harness) = scratch.compile(NOP_HARNESS, true) else {
+            return;
+        };
+        let fconfig = scratch
+            .config(&harness)
+            .set_batch_sz(10)
+            .set_max_length(64)
+            .set_persistent(true)
+            .set_harness_path(Some(&harness));
+        let fstats = FuzzerStats::new().to_arc();
+        let workers = spawn_workers(&fconfig, &fstats).unwrap();
+
+        let start = Instant::now();
+        while fstats.get_iterations() < 100 {
+            assert!(
+                start.elapsed() < Duration::from_secs(30),
+                "Workers made no progress"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        fstats.request_stop();
+        join_workers(workers).unwrap();
+
+        // Stopped workers do not run any further batches
+        let iterations = fstats.get_iterations();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(fstats.get_iterations(), iterations);
+    }
+
+    #[test]
+    fn test_persistent_crash() {
+        // A crash of the harness terminates the fuzzer, so the worker runs in a child process
+        // that re-executes this test
+        if let Some(dir) = std::env::var_os(PERSISTENT_DIR_ENV) {
+            // Not removed, the parent checks the crash directory once the child is gone
+            let dir = std::mem::ManuallyDrop::new(Scratch(PathBuf::from(dir)));
+            let harness = dir.join("libharness.so");
+            let mut fconfig = dir
+                .config(&harness)
+                .set_batch_sz(100)
+                .set_max_length(64)
+                .set_persistent(true)
+                .set_harness_path(Some(&harness));
+            let res = worker(&mut fconfig, &FuzzerStats::new().to_arc(), 0);
+            panic!("Worker returned without crashing: {res:?}");
+        }
+
+        let scratch = Scratch::new("persistent", b"BOOMBOOMBOOMBOOM");
+        if scratch.compile(CRASH_HARNESS, true).is_none() {
+            return;
+        }
+        let child = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::test_persistent_crash", "--nocapture"])
+            .env(PERSISTENT_DIR_ENV, &scratch.0)
+            .status()
+            .unwrap();
+        assert_eq!(child.code(), Some(128 + 6), "Expected the harness to abort");
+
+        assert_eq!(scratch.crash_files(), [".crash_0_134_1"]);
+        let crash = fs::read(scratch.join("crashes/.crash_0_134_1")).unwrap();
+        assert!(crash.windows(4).any(|w| w == b"BOOM"));
+    }
+
+    #[test]
+    fn test_forkserver() {
+        // Changing a single byte keeps at least one `A`, so every input crashes
+        let scratch = Scratch::new("forkserver", b"AA");
+        let Some(target) = scratch.compile(FORKSERVER_TARGET, false) else {
+            return;
+        };
+        let log = scratch.join("log");
+        let fconfig = scratch
+            .config(&target)
+            .set_env(vec![(
+                "HANTU_FORKSERVER_LOG".to_string(),
+                log.to_str().unwrap().to_string(),
+            )])
+            .set_forkserver(true)
+            .set_batch_sz(10)
+            .set_max_iter(Some(50))
+            .set_only_mutators(vec!["ChangeByte".to_string()]);
+        let fstats = fuzz(&fconfig);
+
+        // The runs minimizing the crashes count as iterations as well
+        assert_eq!(fstats.get_iterations(), 50);
+        assert!(fstats.get_crashes() > 0);
+        // The target was executed once, every input ran in a forked child
+        let log = fs::read_to_string(log).unwrap();
+        assert_eq!(log.lines().filter(|l| *l == "exec").count(), 1);
+        assert!(log.lines().filter(|l| *l == "run").count() >= 50);
+        // Crashes found once `max_iter` is used up are saved without being fully minimized
+        assert!(scratch
+            .crash_files()
+            .iter()
+            .any(|f| fs::read(scratch.join("crashes").join(f)).unwrap() == b"A"));
+    }
+
+    #[test]
+    fn test_coverage_feedback() {
+        let scratch = Scratch::new("coverage", b"hantu");
+        let Some(target) = scratch.compile(COVERAGE_TARGET, false) else {
+            return;
+        };
+        // Runs a single worker and returns the number of test cases it added to the corpus
+        let corpus_additions = |coverage: bool| {
+            let fconfig = scratch
+                .config(&target)
+                .set_batch_sz(100)
+                .set_max_iter(Some(500))
+                .set_max_length(64)
+                .set_only_mutators(vec!["ChangeByte".to_string()])
+                .set_coverage(coverage);
+            fuzz(&fconfig);
+            fs::read_dir(scratch.join("corpus")).unwrap().count() - 1
+        };
+        assert_eq!(corpus_additions(false), 0);
+        assert!(corpus_additions(true) > 0);
+
+        // New entries are named by the hash of their contents
+        for entry in fs::read_dir(scratch.join("corpus")).unwrap().flatten() {
+            let data = fs::read(entry.path()).unwrap();
+            if data != b"hantu" {
+                assert_eq!(entry.file_name(), format!("{:016x}", fnv1a(&data)).as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn test_corpus_sharing() {
+        // Two workers with coverage feedback exchange the test cases covering new edges
+        let scratch = Scratch::new("corpus_sharing", b"hantu");
+        let Some(target) = scratch.compile(COVERAGE_TARGET, false) else {
+            return;
+        };
+        let fconfig = scratch
+            .config(&target)
+            .set_batch_sz(50)
+            .set_max_iter(Some(1000))
+            .set_max_length(64)
+            .set_only_mutators(vec!["ChangeByte".to_string()])
+            .set_coverage(true);
+        let fstats = FuzzerStats::new().to_arc();
+        // Run the workers directly, as `spawn_workers` pins each of them to its own core
+        thread::scope(|scope| {
+            for thr_id in 0..2 {
+                let mut fconfig = fconfig.clone();
+                let fstats = &fstats;
+                scope.spawn(move || worker(&mut fconfig, fstats, thr_id).unwrap());
+            }
+        });
+
+        let shared = fstats.shared_corpus();
+        assert!(!shared.is_empty());
+        assert!(shared.received() > 0);
+    }
+
+    #[test]
+    fn test_focus_crashes() {
+        let scratch = Scratch::new("focus_crashes", b"AAAAAAAA");
+        fs::write(scratch.join("corpus/.crash_6_0"), b"ZZZZZZZZ").unwrap();
+        fs::write(scratch.join("corpus/.crash_6_1"), vec![b'Z'; 64]).unwrap();
+        let fconfig = scratch
+            .config(Path::new("/bin/true"))
+            .set_only_mutators(vec!["ChangeBit".to_string()])
+            .set_max_seed_size(Some(32));
+        // Counts the test cases out of 1000 that were mutated from the crash. Bit flips keep the
+        // size, so none of them may be based on the oversized crash
+        let crash_based = |fconfig: &FuzzerConfig| {
+            let mut based = 0;
+            dry_run(fconfig, 1000, |_, data| {
+                assert_eq!(data.len(), 8, "Test case based on the oversized crash");
+                based += usize::from(data.iter().filter(|&&b| b == b'Z').count() >= 7);
+                Ok(())
+            })
+            .unwrap();
+            based
+        };
+        // The crash is picked about 16 times as often as the seed when focusing on crashes
+        let unfocused = crash_based(&fconfig);
+        let focused = crash_based(&fconfig.clone().set_focus_crashes(true));
+        assert!(unfocused < 600, "{unfocused}");
+        assert!(focused > 900, "{focused}");
+    }
+
+    #[test]
+    fn test_import_corpus_dir() {
+        // Empty seeds are discarded
+        let scratch = Scratch::new("empty_corpus", b"");
+        for format in [CorpusFormat::Hantu, CorpusFormat::LibFuzzer] {
+            let res = import_corpus_dir(scratch.join("corpus"), format, None);
+            assert!(matches!(res, Err(Error::EmptyCorpus(_))), "{format:?}");
+        }
+
+        // Oversized seeds are skipped
+        let scratch = Scratch::new("oversized_seed", b"small");
+        fs::write(scratch.join("corpus/large"), vec![0x41; 4096]).unwrap();
+        let corpus = import_corpus_dir(scratch.join("corpus"), CorpusFormat::Hantu, Some(1024));
+        assert_eq!(*corpus.unwrap(), vec![b"small".to_vec()]);
+        let corpus = import_corpus_dir(scratch.join("corpus"), CorpusFormat::Hantu, None);
+        assert_eq!(corpus.unwrap().len(), 2);
+        fs::remove_file(scratch.join("corpus/seed")).unwrap();
+        let res = import_corpus_dir(scratch.join("corpus"), CorpusFormat::Hantu, Some(1024));
+        assert!(matches!(res, Err(Error::EmptyCorpus(_))));
+    }
+
+    #[test]
+    fn test_metrics_idle_client() {
+        // Serves the metrics while a client that never sends a request is connected
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let fstats = FuzzerStats::new().to_arc();
+        fstats.inc_iterations_by(42);
+        serve_metrics(&fstats, port).unwrap();
+
+        let _idle = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        client.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut res = String::new();
+        client.read_to_string(&mut res).unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK"));
+        assert!(res.contains("hantu_iterations_total 42"));
     }
 }
@@ -0,0 +1,183 @@
+// In-process execution of libFuzzer style harnesses. Instead of spawning the target for every
+// test case, a shared library exporting
+//
+//     int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size);
+//
+// is loaded once and called in a loop, which avoids the cost of a process per execution. The
+// harness must not keep state between calls that influences later ones and must return 0.
+// `LLVMFuzzerInitialize` is not called.
+//
+// A crashing input cannot be recovered from within the process. Like libFuzzer, the crash
+// handler writes the input that is currently executed to the crash directory and terminates the
+// whole fuzzer with an exit code of 128 + the signal number. Timeouts are not detected.
+use errors::{Error, Result};
+use libloading::Library;
+use std::{cell::Cell, ffi::c_int, path::Path, ptr, sync::Once};
+
+// Name of the entry point every harness has to export
+const TEST_ONE_INPUT: &[u8] = b"LLVMFuzzerTestOneInput\0";
+// Signals that are treated as a crash of the harness
+const CRASH_SIGNALS: [c_int; 5] = [
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGABRT,
+];
+// Size of the buffer the crash handler assembles the crash file path in
+const CRASH_PATH_MAX: usize = 4096;
+
+type TestOneInput = unsafe extern "C" fn(*const u8, usize) -> c_int;
+
+/// Describes where the crash handler saves the input that is currently executed. The crash file
+/// is named `{path_prefix}{128 + signal}{path_suffix}`, the path is assembled by the handler
+/// itself as it must not allocate.
+struct CrashContext {
+    path_prefix: Vec<u8>,
+    path_suffix: Vec<u8>,
+    data: *const u8,
+    len: usize,
+}
+
+thread_local! {
+    // The context of the harness call currently running on this thread, if any
+    static CRASH_CONTEXT: Cell<*const CrashContext> = const { Cell::new(ptr::null()) };
+}
+
+static INSTALL_CRASH_HANDLER: Once = Once::new();
+
+/// A loaded harness exposing `LLVMFuzzerTestOneInput`.
+pub struct Harness {
+    test_one_input: TestOneInput,
+    // Keeps the library mapped as long as `test_one_input` may be called
+    _lib: Library,
+}
+
+impl Harness {
+    /// Loads the shared library at `path` and resolves its `LLVMFuzzerTestOneInput`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the library cannot be loaded or does not export the entry point.
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self> {
+        let path = path.as_ref();
+        // SAFETY: Loading a harness runs its initializers, which is inherent to fuzzing it
+        let lib = unsafe { Library::new(path) }
+            .map_err(|e| Error::new(&format!("Loading harness {}: {e}", path.display())))?;
+        // SAFETY: The symbol is declared with the signature libFuzzer defines for it
+        let test_one_input = unsafe { lib.get::<TestOneInput>(TEST_ONE_INPUT) }
+            .map(|sym| *sym)
+            .map_err(|e| Error::new(&format!("Resolving LLVMFuzzerTestOneInput: {e}")))?;
+        Ok(Self {
+            test_one_input,
+            _lib: lib,
+        })
+    }
+
+    /// Calls the harness with `data`.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by the harness.
+    pub fn run(&self, data: &[u8]) -> i32 {
+        // SAFETY: `data` is valid for `data.len()` bytes for the duration of the call
+        unsafe { (self.test_one_input)(data.as_ptr(), data.len()) }
+    }
+}
+
+/// Runs harnesses with a crash handler in place that saves crashing inputs to a directory.
+pub struct CrashGuard {
+    context: CrashContext,
+}
+
+impl CrashGuard {
+    /// Installs the process wide crash handler if needed. Crashing inputs are saved as
+    /// `{crash_dir}/{prefix}{128 + signal}_{suffix}`, matching the exit code of the process.
+    pub fn new<T: AsRef<Path>>(crash_dir: T, prefix: &str, suffix: &str) -> Self {
+        INSTALL_CRASH_HANDLER.call_once(install_crash_handler);
+        let path_prefix = crash_dir.as_ref().join(prefix);
+        let mut path_suffix = format!("_{suffix}").into_bytes();
+        path_suffix.push(0);
+        Self {
+            context: CrashContext {
+                path_prefix: path_prefix.into_os_string().into_encoded_bytes(),
+                path_suffix,
+                data: ptr::null(),
+                len: 0,
+            },
+        }
+    }
+
+    /// Calls `harness` with `data`. If the harness crashes, `data` is written to the crash
+    /// directory and the process exits.
+    pub fn run(&mut self, harness: &Harness, data: &[u8]) -> i32 {
+        self.context.data = data.as_ptr();
+        self.context.len = data.len();
+        CRASH_CONTEXT.set(&self.context);
+        let ret = harness.run(data);
+        CRASH_CONTEXT.set(ptr::null());
+        ret
+    }
+}
+
+fn install_crash_handler() {
+    for sig in CRASH_SIGNALS {
+        // SAFETY: `on_crash` only calls async-signal-safe functions
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_crash as extern "C" fn(c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(sig, &action, ptr::null_mut());
+        }
+    }
+}
+
+extern "C" fn on_crash(sig: c_int) {
+    let ctx = CRASH_CONTEXT.get();
+    if ctx.is_null() {
+        // Not a crash of the harness, returning re-raises the signal with the default action
+        // restored by `SA_RESETHAND`
+        return;
+    }
+    // SAFETY: The context outlives the harness call it was set for
+    let ctx = unsafe { &*ctx };
+
+    let mut path = [0u8; CRASH_PATH_MAX];
+    let mut len = 0;
+    let mut push = |bytes: &[u8]| {
+        let n = bytes.len().min(CRASH_PATH_MAX - 1 - len);
+        path[len..len + n].copy_from_slice(&bytes[..n]);
+        len += n;
+    };
+    push(&ctx.path_prefix);
+    let mut digits = [0u8; 10];
+    let mut n = (128 + sig).unsigned_abs();
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    push(&digits[start..]);
+    push(&ctx.path_suffix);
+
+    // SAFETY: `path` is NUL terminated and `data` is valid for `len` bytes
+    unsafe {
+        let fd = libc::open(
+            path.as_ptr().cast(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+            0o644,
+        );
+        if fd >= 0 {
+            libc::write(fd, ctx.data.cast(), ctx.len);
+            libc::close(fd);
+        }
+        let msg = b"[HANTU] Harness crashed, saved the input and exiting\n";
+        libc::write(libc::STDERR_FILENO, msg.as_ptr().cast(), msg.len());
+        libc::_exit(128 + sig);
+    }
+}
@@ -137,3 +137,42 @@ pub const MAGIC_64: [u64; 61] = [
     0x0100_0000_0000_0080,
     0xfeff_ffff_ffff_ffff,
 ];
+// Special floats numeric parsers and arithmetic commonly mishandle, used by the
+// `AddFromFloatMagic` mutator
+pub const MAGIC_F32: [f32; 14] = [
+    f32::NAN,
+    f32::from_bits(0x7f80_0001),
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    0.0,
+    -0.0,
+    f32::from_bits(0x0000_0001),
+    f32::from_bits(0x007f_ffff),
+    f32::MIN_POSITIVE,
+    f32::EPSILON,
+    f32::MAX,
+    f32::MIN,
+    1.0,
+    -1.0,
+];
+pub const MAGIC_F64: [f64; 14] = [
+    f64::NAN,
+    f64::from_bits(0x7ff0_0000_0000_0001),
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    0.0,
+    -0.0,
+    f64::from_bits(0x0000_0000_0000_0001),
+    f64::from_bits(0x000f_ffff_ffff_ffff),
+    f64::MIN_POSITIVE,
+    f64::EPSILON,
+    f64::MAX,
+    f64::MIN,
+    1.0,
+    -1.0,
+];
+// Tokens commonly triggering bugs in printf-style formatting, path handling, shell and SQL
+// injection, used by the `InjectDangerous` mutator
+pub const DANGEROUS_TOKENS: [&[u8]; 7] = [
+    b"%n", b"%s%s%s", b"../", b"..\\", b"\x00", b"${IFS}", b"'; DROP",
+];
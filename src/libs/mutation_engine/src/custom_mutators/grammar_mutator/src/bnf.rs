@@ -0,0 +1,152 @@
+// Parser for grammars written in BNF, e.g.:
+//
+//   <start> ::= <a> <b> | "literal"
+//   <a>     ::= "x" | ""          ; comments run until the end of the line
+//
+// Rules may span several lines and a rule defined twice gains the alternatives of both
+// definitions. For a subset of ABNF, `=` and `=/` are accepted for `::=`, `/` for `|` and rule
+// names may be written without angle brackets. Terminals are quoted with `"` or `'`, the quote
+// can be escaped and other escapes are decoded like in JSON grammars, e.g. `"\x89PNG"`.
+use super::{SerializedJsonGrammar, SerializedRule};
+use errors::{Error, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lexeme {
+    // A rule name, normalized to `<name>`
+    Name(String),
+    Terminal(String),
+    Define,
+    Alternative,
+}
+
+// Splits the grammar into lexemes, dropping whitespace and comments
+fn lex(text: &str) -> Result<Vec<Lexeme>> {
+    let mut lexemes = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' | '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '|' | '/' => lexemes.push(Lexeme::Alternative),
+            ':' => {
+                if chars.next() != Some(':') || chars.next() != Some('=') {
+                    return Err(Error::new("Expected '::=' in BNF grammar"));
+                }
+                lexemes.push(Lexeme::Define);
+            }
+            '=' => {
+                chars.next_if_eq(&'/');
+                lexemes.push(Lexeme::Define);
+            }
+            '<' => {
+                let mut name = String::from("<");
+                loop {
+                    match chars.next() {
+                        Some('>') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(Error::new(&format!("Unterminated rule name: {name}"))),
+                    }
+                }
+                name.push('>');
+                lexemes.push(Lexeme::Name(name));
+            }
+            '"' | '\'' => {
+                let mut terminal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') => match chars.next() {
+                            Some(q) if q == c => terminal.push(q),
+                            Some(e) => terminal.extend(['\\', e]),
+                            None => break,
+                        },
+                        Some(t) => terminal.push(t),
+                        None => {
+                            return Err(Error::new(&format!(
+                                "Unterminated terminal: {c}{terminal}"
+                            )))
+                        }
+                    }
+                }
+                lexemes.push(Lexeme::Terminal(terminal));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '-' => {
+                let mut name = format!("<{c}");
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '-')
+                {
+                    name.push(c);
+                }
+                name.push('>');
+                lexemes.push(Lexeme::Name(name));
+            }
+            c => {
+                return Err(Error::new(&format!(
+                    "Unexpected character in BNF grammar: {c:?}"
+                )))
+            }
+        }
+    }
+    Ok(lexemes)
+}
+
+/// Parses a grammar in BNF into the same representation as a JSON grammar.
+pub(crate) fn parse(text: &str) -> Result<SerializedJsonGrammar> {
+    let lexemes = lex(text)?;
+    let mut rules: BTreeMap<String, Vec<Vec<String>>> = BTreeMap::new();
+    let mut current: Option<&mut Vec<Vec<String>>> = None;
+    let mut referenced = BTreeSet::new();
+
+    let mut i = 0;
+    while i < lexemes.len() {
+        match (&lexemes[i], lexemes.get(i + 1)) {
+            // A name followed by a definition starts a new rule
+            (Lexeme::Name(name), Some(Lexeme::Define)) => {
+                let alternatives = rules.entry(name.clone()).or_default();
+                alternatives.push(Vec::new());
+                current = Some(alternatives);
+                i += 1;
+            }
+            (Lexeme::Define, _) => return Err(Error::new("Definition without a rule name")),
+            (lexeme, _) => {
+                let Some(alternatives) = current.as_deref_mut() else {
+                    return Err(Error::new(
+                        "BNF grammar has to start with a rule definition",
+                    ));
+                };
+                match lexeme {
+                    Lexeme::Alternative => alternatives.push(Vec::new()),
+                    Lexeme::Name(s) | Lexeme::Terminal(s) => {
+                        if let Lexeme::Name(name) = lexeme {
+                            referenced.insert(name);
+                        }
+                        if let Some(last) = alternatives.last_mut() {
+                            last.push(s.clone());
+                        }
+                    }
+                    Lexeme::Define => unreachable!(),
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // Terminals are told apart from rule names by not being a rule, so every name has to be one
+    for name in referenced {
+        if !rules.contains_key(name) {
+            return Err(Error::new(&format!(
+                "Undefined rule in BNF grammar: {name}"
+            )));
+        }
+    }
+    if !rules.contains_key("<start>") {
+        return Err(Error::new("BNF grammar has no <start> rule"));
+    }
+    Ok(SerializedJsonGrammar(
+        rules
+            .into_iter()
+            .map(|(name, alternatives)| (name, SerializedRule::Expansions(alternatives)))
+            .collect(),
+    ))
+}
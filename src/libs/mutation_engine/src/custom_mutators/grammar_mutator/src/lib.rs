@@ -2,11 +2,18 @@ use errors::{Error, Result};
 use prng::{Generator, Rng};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    ffi::OsString,
+    fs,
+    hash::{Hash, Hasher},
     path::Path,
     path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    sync::OnceLock,
 };
 
+mod bnf;
+
 // Adapted from:
 //  - <https://github.com/vrthra/F1>
 //  - <https://github.com/gamozolabs/fzero_fuzzer>
@@ -23,6 +30,7 @@ pub enum GrammarTemplate {
     BinaryFormat(BinaryFormat),
     Book(Book),
     Custom(PathBuf),
+    Bnf(PathBuf),
 }
 
 impl From<String> for GrammarTemplate {
@@ -90,6 +98,7 @@ impl From<String> for GrammarTemplate {
             "ttf" => Self::Font(Font::Ttf),
             "woff" => Self::Font(Font::Woff),
             "epub" => Self::Book(Book::Epub),
+            rem if rem.ends_with(".bnf") || rem.ends_with(".abnf") => Self::Bnf(PathBuf::from(rem)),
             rem => Self::Custom(PathBuf::from(rem)),
         }
     }
@@ -245,7 +254,7 @@ impl GrammarTemplate {
             Self::Book(book) => match book {
                 Book::Epub => base_path.join("grammars/epub.json"),
             },
-            Self::Custom(path) => {
+            Self::Custom(path) | Self::Bnf(path) => {
                 assert!(path.is_file(), "Grammar file not found: {path:?}");
                 path.clone()
             }
@@ -351,17 +360,17 @@ pub enum BinaryFormat {
     Ebpf,
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenIdentifier(pub usize);
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Token {
     // A list of tokens that should be expanded in order.
     OrderedExpansion(Vec<TokenIdentifier>),
 
     // A non-terminal token that should be expanded to a
-    // random token from the given set.
-    NonTerminal(Vec<TokenIdentifier>),
+    // random token from the given set, picked proportional to its weight.
+    NonTerminal(Vec<(TokenIdentifier, u32)>),
 
     // A terminal token that should be expanded to expanded
     // to the given bytes.
@@ -371,8 +380,19 @@ pub enum Token {
     Nop,
 }
 
+// Suffix of the key holding the weights of a rule's expansions, e.g. `"<rule>__weights": [3, 1]`
+const WEIGHTS_SUFFIX: &str = "__weights";
+
+// A value of the JSON grammar, either the expansions of a rule or the weights of those expansions
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct SerializedJsonGrammar(BTreeMap<String, Vec<Vec<String>>>);
+#[serde(untagged)]
+enum SerializedRule {
+    Expansions(Vec<Vec<String>>),
+    Weights(Vec<u32>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedJsonGrammar(BTreeMap<String, SerializedRule>);
 
 impl SerializedJsonGrammar {
     fn new<T: AsRef<Path> + ?Sized>(g: &T) -> Result<Self> {
@@ -387,12 +407,26 @@ impl SerializedJsonGrammar {
 impl Default for SerializedJsonGrammar {
     fn default() -> Self {
         let mut grammar = BTreeMap::new();
-        grammar.insert("<start>".to_string(), vec![vec![]]);
+        grammar.insert(
+            "<start>".to_string(),
+            SerializedRule::Expansions(vec![vec![]]),
+        );
         Self(grammar)
     }
 }
 
-#[derive(Debug, Default)]
+// Suffix of the compiled sidecar file `Grammar::new` caches a grammar in
+const COMPILED_SUFFIX: &str = ".compiled";
+// Header of compiled grammars, followed by the hash of the grammar source. Bump the version
+// whenever the layout of `Grammar` or the way grammars are compiled changes, so sidecars written
+// by older versions are rebuilt
+const COMPILED_HEADER: &[u8] = b"hantu grammar v2\n";
+// Size of the source hash following `COMPILED_HEADER`
+const SOURCE_HASH_LEN: usize = 8;
+// Recursion limit of the generators as specified in the original F1 paper
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Grammar {
     // The start token.
     pub start: Option<TokenIdentifier>,
@@ -402,6 +436,30 @@ pub struct Grammar {
 
     // A map from token names to token identifiers.
     token_map: BTreeMap<String, TokenIdentifier>,
+
+    // The minimum number of bytes each token expands to, computed on first use.
+    #[serde(skip)]
+    min_lens: OnceLock<Vec<usize>>,
+
+    // The maximum recursion depth of the generators.
+    #[serde(skip, default = "default_max_depth")]
+    max_depth: usize,
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        Self {
+            start: None,
+            tokens: Vec::new(),
+            token_map: BTreeMap::new(),
+            min_lens: OnceLock::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+const fn default_max_depth() -> usize {
+    DEFAULT_MAX_DEPTH
 }
 
 impl PartialEq for Grammar {
@@ -424,42 +482,216 @@ impl Grammar {
     ///
     /// Finally, the start token is set to the token identifier of the non-terminal token
     /// with the name "<start\>". If no such token exists, the start token is set to None.
+    ///
+    /// The expansions of a rule are picked uniformly, unless a sibling key
+    /// `"<rule>__weights"` lists one weight per expansion, e.g. `[3, 1]`.
+    ///
+    /// Templates of the `Bnf` variant are loaded with `from_bnf` instead.
+    ///
+    /// Grammars of the `Custom` and `Bnf` variants are cached in a compiled sidecar file next to
+    /// the grammar file, e.g. `my.json.compiled`, which is loaded instead as long as it was
+    /// compiled from the current contents of the grammar file by a compatible version. Failing to
+    /// write the sidecar is not an error. The bundled grammars are always parsed, so the source tree they
+    /// are shipped in is never written to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grammar cannot be loaded or has malformed weights.
     pub fn new(t: &GrammarTemplate) -> Result<Self> {
+        let path = t.get_path();
+        let compiled =
+            matches!(t, GrammarTemplate::Custom(_) | GrammarTemplate::Bnf(_)).then(|| {
+                let mut compiled = OsString::from(&path);
+                compiled.push(COMPILED_SUFFIX);
+                PathBuf::from(compiled)
+            });
+        let source_hash = compiled
+            .as_ref()
+            .and_then(|_| fs::read(&path).ok())
+            .map(|source| hash_source(&source));
+        if let (Some(compiled), Some(source_hash)) = (&compiled, source_hash) {
+            match Self::read_compiled(compiled) {
+                Ok((hash, grammar)) if hash == source_hash => return Ok(grammar),
+                _ => {}
+            }
+        }
+
+        let grammar = if let GrammarTemplate::Bnf(path) = t {
+            Self::from_bnf(path)?
+        } else {
+            let sjg = Self::default().load_from_json(t)?;
+            Self::from_serialized(&sjg)?
+        };
+        if let (Some(compiled), Some(source_hash)) = (compiled, source_hash) {
+            let _ = grammar.write_compiled(&compiled, source_hash);
+        }
+        Ok(grammar)
+    }
+
+    /// Sets the maximum recursion depth of the generators, 128 by default. Deeper limits allow
+    /// more nested outputs, shallower ones keep the generation fast.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The maximum recursion depth.
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Writes the grammar in its optimized form to a file, to be loaded with `load_compiled`.
+    /// The file is written to a temporary file first, so concurrent readers never see a
+    /// partially written grammar.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write the compiled grammar to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grammar cannot be serialized or written.
+    pub fn compile_to_file<T: AsRef<Path> + ?Sized>(&self, path: &T) -> Result<()> {
+        // The file is not tied to a grammar source, so `Grammar::new` never picks it up as is
+        self.write_compiled(path.as_ref(), 0)
+    }
+
+    // Writes the compiled grammar along with the hash of the source it was compiled from
+    fn write_compiled(&self, path: &Path, source_hash: u64) -> Result<()> {
+        static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut data = COMPILED_HEADER.to_vec();
+        data.extend_from_slice(&source_hash.to_le_bytes());
+        rmp_serde::encode::write(&mut data, self)
+            .map_err(|e| Error::new(&format!("Could not serialize grammar: {}", e)))?;
+        let mut tmp = OsString::from(path);
+        tmp.push(format!(
+            ".{}_{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, data)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                Error::new(&format!("Failed to write compiled grammar: {}", e))
+            })
+    }
+
+    /// Loads a grammar written by `compile_to_file`, skipping parsing and optimization.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the compiled grammar.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not contain a grammar compiled by this
+    /// version.
+    pub fn load_compiled<T: AsRef<Path> + ?Sized>(path: &T) -> Result<Self> {
+        Self::read_compiled(path.as_ref()).map(|(_, grammar)| grammar)
+    }
+
+    // Reads a compiled grammar along with the hash of the source it was compiled from
+    fn read_compiled(path: &Path) -> Result<(u64, Self)> {
+        let data = fs::read(path)
+            .map_err(|e| Error::new(&format!("Failed to read grammar from disk: {}", e)))?;
+        let data = data
+            .strip_prefix(COMPILED_HEADER)
+            .filter(|data| data.len() >= SOURCE_HASH_LEN)
+            .ok_or_else(|| Error::new("Compiled grammar was written by an incompatible version"))?;
+        let (source_hash, data) = data.split_at(SOURCE_HASH_LEN);
+        let source_hash = u64::from_le_bytes(source_hash.try_into().unwrap());
+        rmp_serde::from_slice(data)
+            .map(|grammar| (source_hash, grammar))
+            .map_err(|e| Error::new(&format!("Could not deserialize grammar: {}", e)))
+    }
+
+    /// Creates a new Grammar instance from a grammar written in BNF, e.g.
+    /// `<start> ::= <a> <b> | "literal"`.
+    ///
+    /// Terminals are quoted, alternatives are separated by `|` and whitespace separated symbols
+    /// are concatenated. The grammar has to define a `<start>` rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the BNF file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid BNF grammar.
+    pub fn from_bnf<T: AsRef<Path> + ?Sized>(path: &T) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::new(&format!("Failed to read grammar from disk: {}", e)))?;
+        Self::from_serialized(&bnf::parse(&text)?)
+    }
+
+    // Constructs the grammar as described for `new`
+    fn from_serialized(sjg: &SerializedJsonGrammar) -> Result<Self> {
         let mut g = Self::default();
-        let sjg = g.load_from_json(t)?;
+        let rules = sjg.0.iter().filter_map(|(name, rule)| match rule {
+            SerializedRule::Expansions(values) => Some((name, values)),
+            SerializedRule::Weights(_) => None,
+        });
 
         // Pre-populate the token list all non-terminal tokens.
-        sjg.0.iter().for_each(|(non_term, _)| {
+        rules.clone().for_each(|(non_term, _)| {
             assert!(g.token_map.get(non_term).is_none());
             let token_id = g.allocate_token(Token::NonTerminal(Vec::new()));
             g.token_map.insert(non_term.clone(), token_id);
         });
+        for (name, rule) in &sjg.0 {
+            let rule_name = name.strip_suffix(WEIGHTS_SUFFIX);
+            match rule {
+                SerializedRule::Weights(_)
+                    if !rule_name.is_some_and(|n| g.token_map.contains_key(n)) =>
+                {
+                    return Err(Error::new(&format!("Weights without a rule: {name}")));
+                }
+                SerializedRule::Expansions(_) if rule_name.is_some() => {
+                    return Err(Error::new(&format!("Expected weights for: {name}")));
+                }
+                _ => {}
+            }
+        }
 
         // Construct the grammar.
-        sjg.0.iter().for_each(|(non_term, values)| {
+        for (non_term, values) in rules {
             let token_id = g.token_map[non_term];
+            let weights = match sjg.0.get(&format!("{non_term}{WEIGHTS_SUFFIX}")) {
+                Some(SerializedRule::Weights(weights)) => {
+                    if weights.len() != values.len() {
+                        return Err(Error::new(&format!(
+                            "Expected {} weights for {non_term}, got {}",
+                            values.len(),
+                            weights.len()
+                        )));
+                    }
+                    weights.clone()
+                }
+                _ => vec![1; values.len()],
+            };
             let mut ordered_exp = Vec::new();
 
-            for val in values {
+            for (val, weight) in values.iter().zip(weights) {
                 let expansion_tokens = val
                     .iter()
                     .map(|token| {
                         if let Some(&non_term) = g.token_map.get(token) {
-                            g.allocate_token(Token::NonTerminal(vec![non_term]))
+                            g.allocate_token(Token::NonTerminal(vec![(non_term, 1)]))
                         } else {
-                            g.allocate_token(Token::Terminal(token.as_bytes().to_vec()))
+                            g.allocate_token(Token::Terminal(decode_escapes(token)))
                         }
                     })
                     .collect::<Vec<_>>();
 
                 let token_id = g.allocate_token(Token::OrderedExpansion(expansion_tokens));
-                ordered_exp.push(token_id);
+                ordered_exp.push((token_id, weight));
             }
 
             if let Token::NonTerminal(nt) = &mut g.tokens[token_id.0] {
                 *nt = ordered_exp;
             }
-        });
+        }
 
         // Resolve start node
         g.start = Some(g.token_map["<start>"]);
@@ -502,7 +734,7 @@ impl Grammar {
                 match self.tokens[idx].clone() {
                     Token::NonTerminal(options) => {
                         if options.len() == 1 {
-                            self.tokens[idx] = self.tokens[options[0].0].clone();
+                            self.tokens[idx] = self.tokens[options[0].0 .0].clone();
                             changed = true;
                         }
                     }
@@ -540,6 +772,16 @@ impl Grammar {
         &self.tokens[id.0]
     }
 
+    /// Returns the token identifiers of all rules except the start rule, e.g. to generate
+    /// fragments of an output instead of a whole one.
+    pub fn fragment_rules(&self) -> Vec<TokenIdentifier> {
+        self.token_map
+            .values()
+            .copied()
+            .filter(|&id| Some(id) != self.start)
+            .collect()
+    }
+
     /// Recursively generates a sequence of bytes from the grammar starting from the given token identifier.
     ///
     /// # Arguments
@@ -551,7 +793,8 @@ impl Grammar {
     ///
     /// # Notes
     ///
-    /// The function limits the recursion depth to 128 to prevent infinite loops or stack overflows.
+    /// The function limits the recursion depth to `max_depth`, 128 by default, to prevent infinite
+    /// loops or stack overflows.
     pub fn generate(
         &self,
         depth: usize,
@@ -560,8 +803,7 @@ impl Grammar {
         out: &mut Vec<u8>,
     ) {
         // Limit recursion depth to prevent infinite loops or stack overflows
-        // as specified in the original F1 paper
-        if depth > 128 {
+        if depth > self.max_depth {
             return;
         }
         match self.get_token(id) {
@@ -569,8 +811,8 @@ impl Grammar {
                 out.extend_from_slice(terminal);
             }
             Token::NonTerminal(options) => {
-                let option = prng.pick(options);
-                self.generate(depth + 1, *option, prng, out);
+                let option = Self::pick_option(options, prng);
+                self.generate(depth + 1, option, prng, out);
             }
             Token::OrderedExpansion(expansions) => {
                 for expansion in expansions {
@@ -580,6 +822,193 @@ impl Grammar {
             Token::Nop => {}
         }
     }
+
+    // Picks one of the options of a `NonTerminal` proportional to its weight. Options with equal
+    // weights are picked uniformly.
+    fn pick_option(
+        options: &[(TokenIdentifier, u32)],
+        prng: &mut Rng<Generator>,
+    ) -> TokenIdentifier {
+        if options.windows(2).all(|w| w[0].1 == w[1].1) {
+            return prng.pick(options).0;
+        }
+        let weights: Vec<u32> = options.iter().map(|&(_, weight)| weight).collect();
+        prng.pick_weighted(options, &weights).0
+    }
+
+    /// Computes the minimum number of bytes each token expands to, similar to the "steps to
+    /// terminate" of fzero. Tokens that never terminate get a length of `usize::MAX`.
+    fn compute_min_lens(&self) -> Vec<usize> {
+        let mut min_lens = vec![usize::MAX; self.tokens.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (idx, token) in self.tokens.iter().enumerate() {
+                let len = match token {
+                    Token::Terminal(terminal) => terminal.len(),
+                    Token::Nop => 0,
+                    Token::NonTerminal(options) => options
+                        .iter()
+                        .map(|(option, _)| min_lens[option.0])
+                        .min()
+                        .unwrap_or(0),
+                    Token::OrderedExpansion(expansions) => expansions
+                        .iter()
+                        .fold(0, |acc: usize, exp| acc.saturating_add(min_lens[exp.0])),
+                };
+                if len < min_lens[idx] {
+                    min_lens[idx] = len;
+                    changed = true;
+                }
+            }
+        }
+        min_lens
+    }
+
+    /// Generates a sequence of bytes from the start token that appends at most `max_len` bytes
+    /// to `out`, as long as the grammar allows any output that short.
+    ///
+    /// Every `NonTerminal` picks randomly among the options whose shortest expansion still
+    /// fits into the remaining budget, after reserving the bytes the pending tokens need at
+    /// least. Once the output approaches `max_len` only the cheapest options remain, so the
+    /// output stays well-formed instead of being cut off.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The maximum number of bytes to append.
+    /// * `prng` - The mutable reference to the random number generator used for randomizing choices.
+    /// * `out` - The mutable reference to the output byte vector where the generated sequence will be stored.
+    ///
+    /// # Notes
+    ///
+    /// Like `generate`, the recursion depth is limited to `max_depth`, which only cuts off the output
+    /// for grammars that can recurse without producing any bytes.
+    pub fn generate_bounded(&self, max_len: usize, prng: &mut Rng<Generator>, out: &mut Vec<u8>) {
+        let Some(start) = self.start else {
+            return;
+        };
+        self.generate_bounded_from(start, max_len, prng, out);
+    }
+
+    /// Like `generate_bounded`, but generates a sequence of bytes from the token `id` instead of
+    /// the start token, e.g. a fragment of one of the `fragment_rules`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The token to generate from.
+    /// * `max_len` - The maximum number of bytes to append.
+    /// * `prng` - The mutable reference to the random number generator used for randomizing choices.
+    /// * `out` - The mutable reference to the output byte vector where the generated sequence will be stored.
+    pub fn generate_bounded_from(
+        &self,
+        id: TokenIdentifier,
+        max_len: usize,
+        prng: &mut Rng<Generator>,
+        out: &mut Vec<u8>,
+    ) {
+        let min_lens = self.min_lens.get_or_init(|| self.compute_min_lens());
+        let limit = out.len().saturating_add(max_len);
+        self.generate_within(0, id, limit, 0, min_lens, prng, out);
+    }
+
+    // Generates `id` while keeping at least `reserved` bytes of the budget up to `limit` for the
+    // tokens that follow it.
+    #[allow(clippy::too_many_arguments)]
+    fn generate_within(
+        &self,
+        depth: usize,
+        id: TokenIdentifier,
+        limit: usize,
+        reserved: usize,
+        min_lens: &[usize],
+        prng: &mut Rng<Generator>,
+        out: &mut Vec<u8>,
+    ) {
+        if depth > self.max_depth {
+            return;
+        }
+        match self.get_token(id) {
+            Token::Terminal(terminal) => {
+                out.extend_from_slice(terminal);
+            }
+            Token::NonTerminal(options) => {
+                let budget = limit.saturating_sub(out.len()).saturating_sub(reserved);
+                let fitting: Vec<_> = options
+                    .iter()
+                    .filter(|(option, _)| min_lens[option.0] <= budget)
+                    .copied()
+                    .collect();
+                let option = if fitting.is_empty() {
+                    options
+                        .iter()
+                        .map(|&(option, _)| option)
+                        .min_by_key(|option| min_lens[option.0])
+                } else {
+                    Some(Self::pick_option(&fitting, prng))
+                };
+                if let Some(option) = option {
+                    self.generate_within(depth + 1, option, limit, reserved, min_lens, prng, out);
+                }
+            }
+            Token::OrderedExpansion(expansions) => {
+                let mut pending = expansions
+                    .iter()
+                    .fold(0, |acc: usize, exp| acc.saturating_add(min_lens[exp.0]));
+                for expansion in expansions {
+                    pending = pending.saturating_sub(min_lens[expansion.0]);
+                    let reserved = reserved.saturating_add(pending);
+                    self.generate_within(
+                        depth + 1,
+                        *expansion,
+                        limit,
+                        reserved,
+                        min_lens,
+                        prng,
+                        out,
+                    );
+                }
+            }
+            Token::Nop => {}
+        }
+    }
+}
+
+// Hashes the source of a grammar to tell whether a compiled sidecar is outdated. A hash that
+// changes across toolchains only causes the sidecar to be rebuilt
+fn hash_source(source: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Decodes the escapes `\xNN`, `\n`, `\r`, `\t`, `\0`, `\"` and `\\` in a terminal into raw bytes, so
+// grammars can emit bytes that are not valid UTF-8. Any other backslash is kept as is.
+fn decode_escapes(terminal: &str) -> Vec<u8> {
+    let bytes = terminal.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let (byte, len) = match (bytes[i], bytes.get(i + 1)) {
+            (b'\\', Some(b'x')) => match bytes
+                .get(i + 2..i + 4)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => (byte, 4),
+                None => (b'\\', 1),
+            },
+            (b'\\', Some(b'n')) => (b'\n', 2),
+            (b'\\', Some(b'r')) => (b'\r', 2),
+            (b'\\', Some(b't')) => (b'\t', 2),
+            (b'\\', Some(b'0')) => (0, 2),
+            (b'\\', Some(b'"')) => (b'"', 2),
+            (b'\\', Some(b'\\')) => (b'\\', 2),
+            (byte, _) => (byte, 1),
+        };
+        out.push(byte);
+        i += len;
+    }
+    out
 }
 
 #[cfg(test)]
@@ -624,7 +1053,7 @@ mod tests {
     fn create_simple_dummy_grammar() -> Grammar {
         // Define the tokens for the dummy grammar
         let tokens = vec![
-            Token::NonTerminal(vec![TokenIdentifier(1)]),
+            Token::NonTerminal(vec![(TokenIdentifier(1), 1)]),
             Token::OrderedExpansion(vec![TokenIdentifier(2), TokenIdentifier(3)]),
             Token::Terminal(b"A".to_vec()),
             Token::Terminal(b"B".to_vec()),
@@ -643,6 +1072,7 @@ mod tests {
             start: Some(TokenIdentifier(0)),
             tokens,
             token_map,
+            ..Default::default()
         }
     }
 
@@ -681,6 +1111,7 @@ mod tests {
             start: Some(TokenIdentifier(0)),
             tokens: optimized_tokens,
             token_map: optimized_token_map,
+            ..Default::default()
         };
 
         assert_eq!(grammar, expected_optimized_grammar);
@@ -695,11 +1126,11 @@ mod tests {
     /// Returns the generated `Grammar` object.
     fn create_complex_dummy_grammar() -> Grammar {
         let tokens = vec![
-            Token::NonTerminal(vec![TokenIdentifier(1)]),
+            Token::NonTerminal(vec![(TokenIdentifier(1), 1)]),
             Token::OrderedExpansion(vec![TokenIdentifier(2), TokenIdentifier(3)]),
             Token::Terminal(b"A".to_vec()),
             Token::Terminal(b"B".to_vec()),
-            Token::NonTerminal(vec![TokenIdentifier(5)]),
+            Token::NonTerminal(vec![(TokenIdentifier(5), 1)]),
             Token::OrderedExpansion(vec![TokenIdentifier(6), TokenIdentifier(7)]),
             Token::Terminal(b"C".to_vec()),
             Token::Terminal(b"D".to_vec()),
@@ -720,6 +1151,7 @@ mod tests {
             start: Some(TokenIdentifier(0)),
             tokens,
             token_map,
+            ..Default::default()
         }
     }
 
@@ -757,6 +1189,7 @@ mod tests {
             start: Some(TokenIdentifier(0)),
             tokens,
             token_map,
+            ..Default::default()
         }
     }
 
@@ -795,4 +1228,286 @@ mod tests {
         //fs::write("test.yml", &res).unwrap();
         assert!(res.len() >= 500);
     }
+
+    #[test]
+    fn test_min_lens() {
+        let mut grammar = create_complex_dummy_grammar();
+        grammar.optimize();
+        assert_eq!(
+            grammar.compute_min_lens(),
+            vec![2, 2, 1, 1, 2, 2, 1, 1, 0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn generate_bounded_json() {
+        // A recursive JSON grammar, in which objects and arrays nest arbitrarily deep
+        let rules = [
+            ("<start>", vec![vec!["<value>"]]),
+            (
+                "<value>",
+                vec![
+                    vec!["<object>"],
+                    vec!["<array>"],
+                    vec!["<string>"],
+                    vec!["<number>"],
+                    vec!["true"],
+                    vec!["null"],
+                ],
+            ),
+            (
+                "<object>",
+                vec![vec!["{", "}"], vec!["{", "<members>", "}"]],
+            ),
+            (
+                "<members>",
+                vec![vec!["<member>"], vec!["<member>", ",", "<members>"]],
+            ),
+            ("<member>", vec![vec!["<string>", ":", "<value>"]]),
+            ("<array>", vec![vec!["[", "]"], vec!["[", "<values>", "]"]]),
+            (
+                "<values>",
+                vec![vec!["<value>"], vec!["<value>", ",", "<values>"]],
+            ),
+            ("<string>", vec![vec!["\"", "<chars>", "\""]]),
+            ("<chars>", vec![vec![], vec!["<char>", "<chars>"]]),
+            ("<char>", vec![vec!["a"], vec!["b"], vec![" "]]),
+            ("<number>", vec![vec!["<digit>"], vec!["-", "<digit>"]]),
+            ("<digit>", vec![vec!["0"], vec!["1"], vec!["7"]]),
+        ];
+        let sjg = SerializedJsonGrammar(
+            rules
+                .into_iter()
+                .map(|(name, alts)| {
+                    let alts = alts
+                        .into_iter()
+                        .map(|alt| alt.into_iter().map(String::from).collect())
+                        .collect();
+                    (name.to_string(), SerializedRule::Expansions(alts))
+                })
+                .collect(),
+        );
+        let grammar = Grammar::from_serialized(&sjg).unwrap();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+
+        for max_len in [16, 64, 256, 1024] {
+            for _ in 0..100 {
+                let mut res = Vec::new();
+                grammar.generate_bounded(max_len, &mut prng, &mut res);
+                assert!(res.len() <= max_len);
+                assert!(
+                    serde_json::from_slice::<serde_json::Value>(&res).is_ok(),
+                    "Invalid JSON: {}",
+                    String::from_utf8_lossy(&res)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generate_weighted() {
+        let sjg: SerializedJsonGrammar = serde_json::from_str(
+            r#"{
+                "<start>": [["<item>", "<item>"]],
+                "<item>": [["a"], ["b"]],
+                "<item>__weights": [10, 1]
+            }"#,
+        )
+        .unwrap();
+        let grammar = Grammar::from_serialized(&sjg).unwrap();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+
+        let mut res = Vec::new();
+        for _ in 0..1000 {
+            grammar.generate(0, grammar.start.unwrap(), &mut prng, &mut res);
+        }
+        let heavy = res.iter().filter(|&&b| b == b'a').count();
+        assert!(heavy > res.len() * 8 / 10, "{heavy} of {}", res.len());
+        assert!(heavy < res.len());
+    }
+
+    #[test]
+    fn weights_must_match_expansions() {
+        let sjg: SerializedJsonGrammar =
+            serde_json::from_str(r#"{"<start>": [["a"], ["b"]], "<start>__weights": [1]}"#)
+                .unwrap();
+        assert!(Grammar::from_serialized(&sjg).is_err());
+    }
+
+    #[test]
+    fn from_bnf_matches_json() {
+        let bnf = r#"
+            ; A list of numbers
+            <start>  ::= "[" <list> "]" | "[]"
+            <list>   ::= <number>
+                       | <number> ", " <list>
+            <number> ::= <digit> | "-" <digit>
+            <digit>  ::= "0" | "1" | '7'
+        "#;
+        let path = std::env::temp_dir().join(format!("hantu_grammar_{}.bnf", std::process::id()));
+        std::fs::write(&path, bnf).unwrap();
+        let from_bnf = Grammar::from_bnf(&path).unwrap();
+        let from_template =
+            Grammar::new(&GrammarTemplate::from(path.to_string_lossy().to_string())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let _ = std::fs::remove_file(format!("{}{COMPILED_SUFFIX}", path.display()));
+
+        let sjg: SerializedJsonGrammar = serde_json::from_str(
+            r#"{
+                "<start>": [["[", "<list>", "]"], ["[]"]],
+                "<list>": [["<number>"], ["<number>", ", ", "<list>"]],
+                "<number>": [["<digit>"], ["-", "<digit>"]],
+                "<digit>": [["0"], ["1"], ["7"]]
+            }"#,
+        )
+        .unwrap();
+        let from_json = Grammar::from_serialized(&sjg).unwrap();
+        assert_eq!(from_bnf, from_json);
+        assert_eq!(from_template, from_json);
+
+        let mut prng_bnf = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        let mut prng_json = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        for _ in 0..100 {
+            let (mut res_bnf, mut res_json) = (Vec::new(), Vec::new());
+            from_bnf.generate_bounded(64, &mut prng_bnf, &mut res_bnf);
+            from_json.generate_bounded(64, &mut prng_json, &mut res_json);
+            assert_eq!(res_bnf, res_json);
+            assert!(res_bnf.starts_with(b"[") && res_bnf.ends_with(b"]"));
+        }
+    }
+
+    #[test]
+    fn parse_bnf() {
+        let sjg = bnf::parse("start = \"a\\n\" rule\nrule = 'b' / \"\"\nrule =/ \"c\"").unwrap();
+        let grammar = Grammar::from_serialized(&sjg).unwrap();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+        let mut res = Vec::new();
+        for _ in 0..100 {
+            res.clear();
+            grammar.generate(0, grammar.start.unwrap(), &mut prng, &mut res);
+            assert!([&b"a\n"[..], b"a\nb", b"a\nc"].contains(&res.as_slice()));
+        }
+
+        assert!(bnf::parse("<start> ::= <missing>").is_err());
+        assert!(bnf::parse("<a> ::= \"x\"").is_err());
+        assert!(bnf::parse("<start> ::= \"unterminated").is_err());
+    }
+
+    #[test]
+    fn terminal_escapes() {
+        let sjg: SerializedJsonGrammar =
+            serde_json::from_str(r#"{"<start>": [["\\x89PNG\\x0d\\x0a"]]}"#).unwrap();
+        let grammar = Grammar::from_serialized(&sjg).unwrap();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        let mut res = Vec::new();
+        grammar.generate(0, grammar.start.unwrap(), &mut prng, &mut res);
+        assert_eq!(res, [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A]);
+
+        assert_eq!(decode_escapes(r"a\n\t\0\\b"), b"a\n\t\0\\b");
+        assert_eq!(decode_escapes(r"\xZZ\q\x4"), br"\xZZ\q\x4");
+        assert_eq!(decode_escapes("plain text"), b"plain text");
+    }
+
+    #[test]
+    fn compiled_round_trip() {
+        let fresh = Grammar::new(&GrammarTemplate::DataFormat(DataFormat::Json)).unwrap();
+        let path = std::env::temp_dir().join(format!("hantu_json_{}.compiled", std::process::id()));
+        fresh.compile_to_file(&path).unwrap();
+        let loaded = Grammar::load_compiled(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(fresh, loaded);
+
+        let mut prng_fresh = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        let mut prng_loaded = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        for _ in 0..100 {
+            let (mut res_fresh, mut res_loaded) = (Vec::new(), Vec::new());
+            fresh.generate_bounded(256, &mut prng_fresh, &mut res_fresh);
+            loaded.generate_bounded(256, &mut prng_loaded, &mut res_loaded);
+            assert_eq!(res_fresh, res_loaded);
+        }
+    }
+
+    #[test]
+    fn new_uses_compiled_sidecar() {
+        let dir = std::env::temp_dir().join(format!("hantu_sidecar_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("grammar.json");
+        let compiled = dir.join(format!("grammar.json{COMPILED_SUFFIX}"));
+        std::fs::write(&path, r#"{"<start>": [["a"], ["b"]]}"#).unwrap();
+
+        let t = GrammarTemplate::Custom(path.clone());
+        let parsed = Grammar::new(&t).unwrap();
+        assert_eq!(Grammar::load_compiled(&compiled).unwrap(), parsed);
+
+        // An outdated sidecar is ignored and replaced, even if the grammar file keeps its size
+        // and modification time
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        std::fs::write(&path, r#"{"<start>": [["c"], ["d"]]}"#).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let reparsed = Grammar::new(&t).unwrap();
+        assert_ne!(reparsed, parsed);
+        assert_eq!(Grammar::new(&t).unwrap(), reparsed);
+        assert_eq!(Grammar::load_compiled(&compiled).unwrap(), reparsed);
+
+        // A sidecar written by an incompatible version is replaced even though the grammar file
+        // did not change
+        std::fs::write(&compiled, rmp_serde::to_vec(&parsed).unwrap()).unwrap();
+        assert!(Grammar::load_compiled(&compiled).is_err());
+        assert_eq!(Grammar::new(&t).unwrap(), reparsed);
+        assert_eq!(Grammar::load_compiled(&compiled).unwrap(), reparsed);
+
+        // A partial layout is rejected instead of being filled with defaults
+        let mut partial = COMPILED_HEADER.to_vec();
+        partial.extend_from_slice(&[0; SOURCE_HASH_LEN]);
+        rmp_serde::encode::write(&mut partial, &(reparsed.start,)).unwrap();
+        std::fs::write(&compiled, partial).unwrap();
+        assert!(Grammar::load_compiled(&compiled).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bundled_grammars_have_no_sidecar() {
+        let t = GrammarTemplate::DataFormat(DataFormat::Json);
+        let mut compiled = OsString::from(t.get_path());
+        compiled.push(COMPILED_SUFFIX);
+        let _ = std::fs::remove_file(&compiled);
+        Grammar::new(&t).unwrap();
+        assert!(!Path::new(&compiled).exists());
+    }
+
+    #[test]
+    fn max_depth() {
+        let sjg: SerializedJsonGrammar = serde_json::from_str(
+            r#"{
+                "<start>": [["(", "<start>", ")"], ["x"]],
+                "<start>__weights": [9, 1]
+            }"#,
+        )
+        .unwrap();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        let mut generate_with = |max_depth| {
+            let grammar = Grammar::from_serialized(&sjg)
+                .unwrap()
+                .with_max_depth(max_depth);
+            (0..100)
+                .map(|_| {
+                    let mut res = Vec::new();
+                    grammar.generate(0, grammar.start.unwrap(), &mut prng, &mut res);
+                    res.len()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let shallow = generate_with(4);
+        let deep = generate_with(64);
+        assert!(shallow.iter().all(|&len| len <= 4), "{shallow:?}");
+        assert!(deep.iter().any(|&len| len > 4));
+        assert!(deep.iter().sum::<usize>() > shallow.iter().sum::<usize>());
+    }
 }
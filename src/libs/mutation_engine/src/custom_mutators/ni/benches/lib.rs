@@ -4,7 +4,7 @@ extern crate ni;
 extern crate prng;
 extern crate test;
 
-use ni::{ni_area, ni_area_parallel, ni_area_parallel_hybrid};
+use ni::{ni_area, ni_area_parallel, ni_area_parallel_hybrid, NiConfig};
 use prng::xorshift::Xorshift64;
 use prng::{Generator, Rng};
 use std::sync::Arc;
@@ -34,7 +34,8 @@ fn bench_original_ni_area_size(b: &mut Bencher, size: usize) {
     let data = &corpus[prng.rand() % corpus.len()];
     let mut out = Vec::new();
 
-    b.iter(|| ni_area(data, ITERATIONS, &mut out, &mut prng, &corpus));
+    let config = NiConfig::default();
+    b.iter(|| ni_area(data, ITERATIONS, &mut out, &mut prng, &corpus, &config));
 }
 
 fn bench_parallel_ni_area_size(b: &mut Bencher, size: usize) {
@@ -44,7 +45,8 @@ fn bench_parallel_ni_area_size(b: &mut Bencher, size: usize) {
     let data = &corpus[prng.rand() % corpus.len()];
     let mut out = Vec::new();
 
-    b.iter(|| ni_area_parallel(data, ITERATIONS, &mut out, &mut prng, &corpus));
+    let config = NiConfig::default();
+    b.iter(|| ni_area_parallel(data, ITERATIONS, &mut out, &mut prng, &corpus, &config));
 }
 
 fn bench_parallel_hybrid_ni_area_size(b: &mut Bencher, size: usize) {
@@ -54,7 +56,8 @@ fn bench_parallel_hybrid_ni_area_size(b: &mut Bencher, size: usize) {
     let data = &corpus[prng.rand() % corpus.len()];
     let mut out = Vec::new();
 
-    b.iter(|| ni_area_parallel_hybrid(data, ITERATIONS, &mut out, &mut prng, &corpus));
+    let config = NiConfig::default();
+    b.iter(|| ni_area_parallel_hybrid(data, ITERATIONS, &mut out, &mut prng, &corpus, &config));
 }
 
 #[bench]
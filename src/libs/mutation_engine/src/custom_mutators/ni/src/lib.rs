@@ -10,27 +10,50 @@ const AIMAX: usize = 512;
 const AIMROUNDS: usize = 256;
 const AIMLEN: usize = 1024;
 
+/// Tunables of the aimed mutations, trading speed for more thorough searches of good jump
+/// and land positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NiConfig {
+    // Score at which the comparison of two suffixes stops
+    pub aimax: usize,
+    // Upper bound of the attempts to find a better jump and land position, has to be positive
+    pub aimrounds: usize,
+    // Maximum number of bytes skipped to find a matching land position
+    pub aimlen: usize,
+}
+
+impl Default for NiConfig {
+    fn default() -> Self {
+        Self {
+            aimax: AIMAX,
+            aimrounds: AIMROUNDS,
+            aimlen: AIMLEN,
+        }
+    }
+}
+
 /// Calculate the score of the difference between two byte slices `a` and `b`.
 ///
 /// The score is calculated by iterating through the elements of the slices, comparing them, and
 /// updating the score based on the differences found. The calculation stops when either the maximum
-/// allowed score (`AIMAX`) is reached or when a matching element is found in both slices.
+/// allowed score (`aimax`) is reached or when a matching element is found in both slices.
 ///
 /// # Arguments
 ///
 /// * `a`: A byte slice to compare.
 /// * `b`: Another byte slice to compare.
+/// * `aimax`: The maximum score.
 ///
 /// # Returns
 ///
 /// * The score of the difference between the two input slices.
-fn sufscore(a: &[u8], b: &[u8]) -> usize {
+fn sufscore(a: &[u8], b: &[u8], aimax: usize) -> usize {
     let mut n = 0;
     let mut last = u8::MAX;
     a.iter()
         .zip(b.iter())
         .take_while(|(a, b)| {
-            if n >= AIMAX || *a == *b {
+            if n >= aimax || *a == *b {
                 false
             } else {
                 if **a != last {
@@ -57,7 +80,15 @@ fn sufscore(a: &[u8], b: &[u8]) -> usize {
 /// * `jump`: A mutable reference to a usize that will be set to the optimal jump index.
 /// * `land`: A mutable reference to a usize that will be set to the optimal land index.
 /// * `prng`: A mutable reference to a random number generator.
-fn aim(from: &[u8], to: &[u8], jump: &mut usize, land: &mut usize, prng: &mut Rng<Generator>) {
+/// * `config`: The tunables of the search.
+fn aim(
+    from: &[u8],
+    to: &[u8],
+    jump: &mut usize,
+    land: &mut usize,
+    prng: &mut Rng<Generator>,
+    config: &NiConfig,
+) {
     // Set jump and land to 0 if `from` is empty, otherwise set them to random values within the range of `from` length.
     let flen = from.len();
     let tlen = to.len();
@@ -81,16 +112,16 @@ fn aim(from: &[u8], to: &[u8], jump: &mut usize, land: &mut usize, prng: &mut Rn
 
     // Store the best score and update jump and land with better scores found
     let mut best_score = 0;
-    let rounds = prng.rand_range(0, AIMROUNDS);
+    let rounds = prng.rand_range(0, config.aimrounds);
     for _ in 0..rounds {
-        let mut maxs = AIMLEN;
+        let mut maxs = config.aimlen;
         let j = prng.rand_range(0, flen);
         let mut l = prng.rand_range(0, tlen);
         while maxs > 0 && l < tlen && from[j] != to[l] {
             l += 1;
             maxs -= 1;
         }
-        let score = sufscore(&from[j..], &to[l..]);
+        let score = sufscore(&from[j..], &to[l..], config.aimax);
         if score > best_score {
             best_score = score;
             *jump = j;
@@ -197,7 +228,7 @@ fn twiddle(mut val: i64, prng: &mut Rng<Generator>) -> i64 {
                 val ^= 1 << prng.rand_range(0, (std::mem::size_of::<i64>() * 8) - 1) as i64;
             }
             2 => {
-                val += prng.rand_range(0, 5) as i64 - 2;
+                val = val.wrapping_add(prng.rand_range(0, 5) as i64 - 2);
             }
             _ => continue,
         }
@@ -228,7 +259,9 @@ const fn delim_of(delim: u8) -> Option<u8> {
         b'}' => Some(b'{'),
         b']' => Some(b'['),
         b'\n' => Some(b'\n'),
-        //b'"' => Some(b'"'),
+        b'"' => Some(b'"'),
+        b'\'' => Some(b'\''),
+        b'`' => Some(b'`'),
         //b' ' => Some(b' '),
         //b',' => Some(b','),
         _ => None,
@@ -237,7 +270,8 @@ const fn delim_of(delim: u8) -> Option<u8> {
 
 /// Searches for the first delimiter in a given data slice and returns its index and a reference to the delimiter character.
 ///
-/// Delimiters are considered to be one of the following characters: `[`, `<`, `(`, or `\n`.
+/// Delimiters are considered to be one of the following characters: `[`, `<`, `(`, `\n`, `"`, `'`
+/// or `` ` ``.
 ///
 /// # Arguments
 ///
@@ -250,11 +284,14 @@ const fn delim_of(delim: u8) -> Option<u8> {
 fn drange_start(data: &[u8]) -> Option<(usize, &u8)> {
     data.iter()
         .enumerate()
-        .find(|&(_, c)| matches!(*c as char, '[' | '<' | '(' | '\n'))
+        .find(|&(_, c)| matches!(*c as char, '[' | '<' | '(' | '\n' | '"' | '\'' | '`'))
         .map(|(i, c)| (i, c))
 }
 
 /// Finds the position of the closing delimiter in a slice of data, considering nested delimiters.
+/// Delimiters that open and close a range with the same byte, like quotes, cannot nest, so they
+/// toggle between being inside and outside a range instead. Inside such a range, a byte escaped
+/// with a backslash does not close it.
 ///
 /// # Arguments
 ///
@@ -272,11 +309,17 @@ fn drange_end(
     delim_close: u8,
     prng: &mut Rng<Generator>,
 ) -> Option<usize> {
+    let self_matching = delim_open == delim_close;
     let mut depth = 0;
+    let mut escaped = false;
 
     for (i, c) in data.iter().enumerate() {
-        if *c == delim_close {
-            depth -= 1;
+        if escaped {
+            escaped = false;
+        } else if self_matching && depth == 1 && *c == b'\\' {
+            escaped = true;
+        } else if *c == delim_close {
+            depth = if self_matching { 1 - depth } else { depth - 1 };
             if depth == 0 {
                 if prng.bool_chance(3) {
                     return Some(i + 1);
@@ -349,21 +392,83 @@ fn other_drange(data: &[u8], delim_start: u8, prng: &mut Rng<Generator>) -> Opti
     None
 }
 
+/// The mutation strategies of ni, which `mutate_area` picks from at random.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NiMutation {
+    InsertRandomByte,
+    DeleteRandomByte,
+    Jump,
+    RepeatChars,
+    InsertRandomData,
+    AimedJump,
+    AimedFusion,
+    InsertSemirandomBytes,
+    OverwriteSemirandomBytes,
+    TextualNumber,
+    DelimSwap,
+}
+
+impl NiMutation {
+    /// All strategies along with the weight `mutate_area` picks them with.
+    pub const WEIGHTED: [(Self, usize); 11] = [
+        (Self::InsertRandomByte, 1),
+        (Self::DeleteRandomByte, 1),
+        (Self::Jump, 2),
+        (Self::RepeatChars, 2),
+        (Self::InsertRandomData, 1),
+        (Self::AimedJump, 6),
+        (Self::AimedFusion, 9),
+        (Self::InsertSemirandomBytes, 2),
+        (Self::OverwriteSemirandomBytes, 1),
+        (Self::TextualNumber, 4),
+        (Self::DelimSwap, 6),
+    ];
+
+    /// Applies the strategy to `data` and writes the result to the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: A slice of `u8` data to mutate.
+    /// * `out`: A mutable reference to the output writer.
+    /// * `prng`: A mutable reference to a custom random number generator.
+    /// * `corpus`: A shared reference to the corpus, used as reference data by some strategies.
+    /// * `config`: The tunables of the aimed mutations.
+    ///
+    /// # Returns
+    ///
+    /// `None` without writing anything if the strategy cannot be applied to `data`.
+    pub fn apply<W: Write>(
+        self,
+        data: &[u8],
+        out: &mut W,
+        prng: &mut Rng<Generator>,
+        corpus: &Arc<Vec<Vec<u8>>>,
+        config: &NiConfig,
+    ) -> Option<()> {
+        let strategy = match self {
+            Self::InsertRandomByte => ni_insert_random_byte,
+            Self::DeleteRandomByte => ni_delete_random_byte,
+            Self::Jump => ni_jump,
+            Self::RepeatChars => ni_repeat_chars,
+            Self::InsertRandomData => ni_insert_random_data,
+            Self::AimedJump => ni_aimed_jump,
+            Self::AimedFusion => ni_aimed_fusion,
+            Self::InsertSemirandomBytes => ni_insert_semirandom_bytes,
+            Self::OverwriteSemirandomBytes => ni_overwrite_semirandom_bytes,
+            Self::TextualNumber => ni_textual_number,
+            Self::DelimSwap => ni_delim_swap,
+        };
+        strategy(data, out, prng, corpus, config)
+    }
+}
+
 /// Mutates a given data slice according to various mutation strategies and writes the result to the output.
 ///
 /// This function accepts a reference to a data slice, a mutable reference to an output writer implementing the Write trait,
 /// a mutable reference to a Rng instance with a Generator, and a shared reference to a corpus.
 ///
-/// The function applies various mutation strategies to the input data, including but not limited to:
-/// - Inserting or deleting random bytes
-/// - Jumping or overlapping sequences
-/// - Repeating characters
-/// - Inserting random data
-/// - Aimed jump to self
-/// - Aimed random block fusion
-/// - Inserting or overwriting semi-random bytes
-/// - Textual number mutation
-/// - Delimiter swapping
+/// The function picks one of the `NiMutation` strategies according to their weights, until one
+/// of them can be applied to the input data.
 ///
 /// # Arguments
 ///
@@ -371,208 +476,307 @@ fn other_drange(data: &[u8], delim_start: u8, prng: &mut Rng<Generator>) -> Opti
 /// * out - A mutable reference to the output writer implementing the Write trait, where the mutated result will be written
 /// * prng - A mutable reference to a Rng instance with a Generator, used to generate random values for the mutation strategies
 /// * corpus - A shared reference to a corpus, used in some mutation strategies for reference data
+/// * config - The tunables of the aimed mutations
 fn mutate_area<W: Write>(
     data: &[u8],
     out: &mut W,
     prng: &mut Rng<Generator>,
     corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
 ) {
-    let end = data.len();
+    let total: usize = NiMutation::WEIGHTED.iter().map(|&(_, weight)| weight).sum();
     loop {
-        let r = prng.rand_range(0, 35);
-        match r {
-            0 => {
-                // Insert random byte
-                let pos = prng.rand_range(0, end);
-                let _ = out.write(&data[..pos]);
-                let _ = out.write(&prng.rand_byte_vec(1));
-                let _ = out.write(&data[pos..]);
-                return;
-            }
-            1 => {
-                // Delete a random byte
-                let pos = prng.rand_range(0, end);
-                if pos + 1 >= end {
-                    continue;
-                }
-                let _ = out.write(&data[..pos]);
-                let _ = out.write(&data[pos + 1..]);
-                return;
-            }
-            2..=3 => {
-                // Jump / Overlapping sequences
-                if end <= 1 {
-                    continue;
+        let mut r = prng.rand_range(0, total);
+        for (mutation, weight) in NiMutation::WEIGHTED {
+            if r < weight {
+                if mutation.apply(data, out, prng, corpus, config).is_some() {
+                    return;
                 }
-                // Generate two random numbers a,b, with a<b
-                let (a, b) = prng.rand_two(end);
-                let _ = out.write(&data[..a]);
-                let _ = out.write(&data[b..]);
-                return;
+                break;
             }
-            4..=5 => {
-                // Repeat characters
-                if end < 2 {
-                    continue;
-                }
-                let mut n = 8;
-                while prng.bool() && n < 20000 {
-                    n <<= 1;
-                }
-                n = prng.rand_range(1, n + 3);
-                let (a, b) = prng.rand_two(end);
-                let mut len = b - a;
+            r -= weight;
+        }
+    }
+}
 
-                let _ = out.write(&data[..a]);
+/// Inserts a random byte at a random position.
+pub fn ni_insert_random_byte<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    let pos = prng.rand_range(0, data.len());
+    let _ = out.write(&data[..pos]);
+    let _ = out.write(&prng.rand_byte_vec(1));
+    let _ = out.write(&data[pos..]);
+    Some(())
+}
 
-                if len * n > 0x800_0000 {
-                    len = prng.rand_range(0, 1026);
-                }
+/// Deletes a random byte, except for the last one.
+pub fn ni_delete_random_byte<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    let pos = prng.rand_range(0, data.len());
+    if pos + 1 >= data.len() {
+        return None;
+    }
+    let _ = out.write(&data[..pos]);
+    let _ = out.write(&data[pos + 1..]);
+    Some(())
+}
 
-                // Insert some substring `n` times
-                for _ in 0..n {
-                    let _ = out.write(&data[a..a + len]);
-                }
+/// Jumps from a random position to a later one, dropping the bytes in between.
+pub fn ni_jump<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    if data.len() <= 1 {
+        return None;
+    }
+    // Generate two random numbers a,b, with a<b
+    let (a, b) = prng.rand_two(data.len());
+    let _ = out.write(&data[..a]);
+    let _ = out.write(&data[b..]);
+    Some(())
+}
 
-                let _ = out.write(&data[a..]);
-                return;
-            }
-            6 => {
-                // Insert random data
-                let pos = prng.rand_range(0, end);
-                let n = prng.rand_range(0, 1024);
-                let random_data = prng.rand_byte_vec(n);
-                let _ = out.write(&data[..pos]);
-                let _ = out.write(&random_data);
-                let _ = out.write(&data[pos..]);
-                return;
-            }
-            7..=12 => {
-                // Aimed jump to self
-                if end < 5 {
-                    continue;
-                }
+/// Repeats a random substring a random number of times.
+pub fn ni_repeat_chars<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    if data.len() < 2 {
+        return None;
+    }
+    let mut n = 8;
+    while prng.bool() && n < 20000 {
+        n <<= 1;
+    }
+    n = prng.rand_range(1, n + 3);
+    let (a, b) = prng.rand_two(data.len());
+    let mut len = b - a;
 
-                let mut j = 0;
-                let mut l = 0;
-                aim(data, data, &mut j, &mut l, prng);
+    let _ = out.write(&data[..a]);
 
-                let _ = out.write(&data[..j]);
-                let _ = out.write(&data[l..]);
-                return;
-            }
-            13..=21 => {
-                // Aimed random block fusion
-                if end < 8 {
-                    continue;
-                }
+    if len * n > 0x800_0000 {
+        len = prng.rand_range(0, 1026);
+    }
 
-                let rchk = random_block(data, prng, corpus);
-                let mut j = 0;
-                let mut l = 1;
-                aim(
-                    &data[..end >> 1],
-                    &rchk[..rchk.len() >> 1],
-                    &mut j,
-                    &mut l,
-                    prng,
-                );
-                let _ = out.write(&data[..j]);
+    // Insert some substring `n` times
+    for _ in 0..n {
+        let _ = out.write(&data[a..a + len]);
+    }
 
-                let buff = &rchk[rchk.len() >> 1..];
-                aim(buff, &data[j..], &mut j, &mut l, prng);
-                let _ = out.write(&buff[..j]);
-                let _ = out.write(&data[l..]);
-                return;
-            }
-            22..=23 => {
-                // Insert semirandom bytes
-                if end < 2 {
-                    continue;
-                }
+    let _ = out.write(&data[a..]);
+    Some(())
+}
 
-                let n = prng.rand_range(2, 4096) % (4096 / 5);
-                let pos = prng.rand_range(0, end);
-                let mut r = prng.rand_range(2, data.len());
-                let _ = out.write(&data[..pos]);
-                for _ in 0..n {
-                    let _ = out.write(&data[r - 1..r]);
-                    r = prng.rand_range(2, data.len());
-                }
-                let _ = out.write(&data[pos..]);
-                return;
-            }
-            24 => {
-                // Overwrite semirandom bytes
-                if end < 2 {
-                    continue;
-                }
+/// Inserts up to 1024 random bytes at a random position.
+pub fn ni_insert_random_data<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    let pos = prng.rand_range(0, data.len());
+    let n = prng.rand_range(0, 1024);
+    let mut random_data = [0u8; 1024];
+    prng.rand_bytes_into(&mut random_data[..n]);
+    let _ = out.write(&data[..pos]);
+    let _ = out.write(&random_data[..n]);
+    let _ = out.write(&data[pos..]);
+    Some(())
+}
 
-                let a = prng.rand_range(0, end - 2);
-                let mut b = a + 2;
-                if prng.bool() {
-                    b += prng.rand_range(0, 32);
-                } else {
-                    b += prng.rand_range(0, std::cmp::min(4096 - 2, end - a - 2));
-                }
-                b = std::cmp::min(b, end);
+/// Jumps from one position of the data to another one with a similar suffix.
+pub fn ni_aimed_jump<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
+) -> Option<()> {
+    if data.len() < 5 {
+        return None;
+    }
 
-                let _ = out.write(&data[..a]);
-                for _ in a..b {
-                    let r = prng.rand_range(0, end);
-                    let _ = out.write(&data[r..=r]);
-                }
+    let mut j = 0;
+    let mut l = 0;
+    aim(data, data, &mut j, &mut l, prng, config);
 
-                if end > b {
-                    let _ = out.write(&data[b..]);
-                }
-                return;
-            }
-            25..=28 => {
-                // Textual number mutation
-                if end < 2 {
-                    continue;
-                }
-                // Attempt to find a number at a random location in the data buffer
-                for _ in 0..prng.rand_range(0, AIMROUNDS) {
-                    if let Some((ns, ne)) = seek_num(data, prng) {
-                        let _ = out.write(&data[..ns]);
-                        let num = std::str::from_utf8(&data[ns..ne])
-                            .unwrap()
-                            .parse::<usize>()
-                            .unwrap() as i64;
-                        let twid = twiddle(num, prng);
-                        let raw_bytes: [u8; 8] = twid.to_ne_bytes();
-                        let _ = out.write(&raw_bytes);
-                        let _ = out.write(&data[ne..]);
-                        break;
-                    }
-                }
-                return;
-            }
-            29..=34 => {
-                // delim swap
-                match drange(data, prng) {
-                    None => continue,
-                    Some((delim1_s, delim1_e)) => match other_drange(data, data[delim1_s], prng) {
-                        None => continue,
-                        Some((delim2_s, delim2_e)) => {
-                            let _ = out.write(&data[..delim1_s]);
-                            let _ = out.write(&data[delim2_s..delim2_e]);
-                            if delim2_s > delim1_e {
-                                let _ = out.write(&data[delim1_e..delim2_s]);
-                            }
-                            let _ = out.write(&data[delim1_s..delim1_e]);
-                            let _ = out.write(&data[delim2_e..]);
-                        }
-                    },
-                }
+    let _ = out.write(&data[..j]);
+    let _ = out.write(&data[l..]);
+    Some(())
+}
 
-                return;
-            }
-            _ => unimplemented!(),
+/// Fuses a random block of the corpus into the data at aimed positions.
+pub fn ni_aimed_fusion<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
+) -> Option<()> {
+    let end = data.len();
+    if end < 8 {
+        return None;
+    }
+
+    let rchk = random_block(data, prng, corpus);
+    let mut j = 0;
+    let mut l = 1;
+    aim(
+        &data[..end >> 1],
+        &rchk[..rchk.len() >> 1],
+        &mut j,
+        &mut l,
+        prng,
+        config,
+    );
+    let _ = out.write(&data[..j]);
+
+    let buff = &rchk[rchk.len() >> 1..];
+    aim(buff, &data[j..], &mut j, &mut l, prng, config);
+    let _ = out.write(&buff[..j]);
+    let _ = out.write(&data[l..]);
+    Some(())
+}
+
+/// Inserts bytes picked from the data at a random position.
+pub fn ni_insert_semirandom_bytes<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    if data.len() < 2 {
+        return None;
+    }
+
+    let n = prng.rand_range(2, 4096) % (4096 / 5);
+    let pos = prng.rand_range(0, data.len());
+    let mut r = prng.rand_range(2, data.len());
+    let _ = out.write(&data[..pos]);
+    for _ in 0..n {
+        let _ = out.write(&data[r - 1..r]);
+        r = prng.rand_range(2, data.len());
+    }
+    let _ = out.write(&data[pos..]);
+    Some(())
+}
+
+/// Overwrites a random range with bytes picked from the data.
+pub fn ni_overwrite_semirandom_bytes<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    let end = data.len();
+    if end < 2 {
+        return None;
+    }
+
+    let a = prng.rand_range(0, end - 2);
+    let mut b = a + 2;
+    if prng.bool() {
+        b += prng.rand_range(0, 32);
+    } else {
+        b += prng.rand_range(0, std::cmp::min(4096 - 2, end - a - 2));
+    }
+    b = std::cmp::min(b, end);
+
+    let _ = out.write(&data[..a]);
+    for _ in a..b {
+        let r = prng.rand_range(0, end);
+        let _ = out.write(&data[r..=r]);
+    }
+
+    if end > b {
+        let _ = out.write(&data[b..]);
+    }
+    Some(())
+}
+
+/// Replaces a textual number found in the data with the raw bytes of a twiddled version of it.
+pub fn ni_textual_number<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
+) -> Option<()> {
+    if data.len() < 2 {
+        return None;
+    }
+    // Attempt to find a number at a random location in the data buffer
+    for _ in 0..prng.rand_range(0, config.aimrounds) {
+        if let Some((ns, ne)) = seek_num(data, prng) {
+            let _ = out.write(&data[..ns]);
+            // Numbers too large to parse are replaced with a random one
+            let num = std::str::from_utf8(&data[ns..ne])
+                .ok()
+                .and_then(|num| num.parse::<usize>().ok())
+                .map_or_else(|| prng.rand() as i64, |num| num as i64);
+            let twid = twiddle(num, prng);
+            let raw_bytes: [u8; 8] = twid.to_ne_bytes();
+            let _ = out.write(&raw_bytes);
+            let _ = out.write(&data[ne..]);
+            return Some(());
         }
     }
+    None
+}
+
+/// Swaps two ranges of data enclosed by the same kind of delimiters.
+pub fn ni_delim_swap<W: Write>(
+    data: &[u8],
+    out: &mut W,
+    prng: &mut Rng<Generator>,
+    _corpus: &Arc<Vec<Vec<u8>>>,
+    _config: &NiConfig,
+) -> Option<()> {
+    delim_swap(data, out, prng)
+}
+
+/// Swaps two ranges of data enclosed by the same kind of delimiters and writes the result to the
+/// output.
+///
+/// # Arguments
+///
+/// * `data`: A slice of `u8` data to search for the delimited ranges.
+/// * `out`: A mutable reference to the output writer.
+/// * `prng`: A mutable reference to a custom random number generator.
+///
+/// # Returns
+///
+/// `None` without writing anything if no two delimited ranges were found.
+fn delim_swap<W: Write>(data: &[u8], out: &mut W, prng: &mut Rng<Generator>) -> Option<()> {
+    let (delim1_s, delim1_e) = drange(data, prng)?;
+    let (delim2_s, delim2_e) = other_drange(data, data[delim1_s], prng)?;
+    let _ = out.write(&data[..delim1_s]);
+    let _ = out.write(&data[delim2_s..delim2_e]);
+    if delim2_s > delim1_e {
+        let _ = out.write(&data[delim1_e..delim2_s]);
+    }
+    let _ = out.write(&data[delim1_s..delim1_e]);
+    let _ = out.write(&data[delim2_e..]);
+    Some(())
 }
 
 /// Performs a mutation process on a given data slice.
@@ -588,6 +792,7 @@ fn mutate_area<W: Write>(
 /// * `out`: A mutable reference to a `Vec<u8>` to write the output to.
 /// * `prng`: A mutable reference to a custom random number generator.
 /// * `corpus`: A shared reference to an `Arc<Vec<Vec<u8>>>` containing the corpus data.
+/// * `config`: The tunables of the aimed mutations.
 ///
 /// # Returns
 ///
@@ -598,11 +803,12 @@ pub fn ni_area_parallel<W: Write + Send + Sync>(
     out: &mut W,
     prng: &mut Rng<Generator>,
     corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
 ) {
     let len = data.len();
 
     if n == 1 || len < 256 {
-        mutate_area(data, out, prng, corpus);
+        mutate_area(data, out, prng, corpus, config);
     } else {
         // Determine the number of threads based on the available hardware
         let num_threads = rayon::current_num_threads();
@@ -622,6 +828,7 @@ pub fn ni_area_parallel<W: Write + Send + Sync>(
                     &mut local_out,
                     &mut local_prng,
                     corpus,
+                    config,
                 );
                 local_out
             })
@@ -645,6 +852,7 @@ pub fn ni_area_parallel<W: Write + Send + Sync>(
 /// * `out`: A mutable reference to a `Vec<u8>` to write the output to.
 /// * `prng`: A mutable reference to a custom random number generator.
 /// * `corpus`: A shared reference to an `Arc<Vec<Vec<u8>>>` containing the corpus data.
+/// * `config`: The tunables of the aimed mutations.
 ///
 /// # Returns
 ///
@@ -655,11 +863,12 @@ pub fn ni_area_parallel_hybrid<W: Write + Send + Sync>(
     out: &mut W,
     prng: &mut Rng<Generator>,
     corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
 ) {
     let len = data.len();
 
     if n == 1 || len < 256 {
-        mutate_area(data, out, prng, corpus);
+        mutate_area(data, out, prng, corpus, config);
     } else {
         // Determine the number of threads based on the available hardware
         let num_threads = rayon::current_num_threads();
@@ -679,6 +888,7 @@ pub fn ni_area_parallel_hybrid<W: Write + Send + Sync>(
                     &mut local_out,
                     &mut local_prng,
                     corpus,
+                    config,
                 );
                 local_out
             })
@@ -698,12 +908,13 @@ pub fn ni_area<W: Write>(
     out: &mut W,
     prng: &mut Rng<Generator>,
     corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
 ) {
     let mut stack = vec![(data, n)];
     while let Some((data, n)) = stack.pop() {
         let len = data.len();
         if n == 1 || len < 256 {
-            mutate_area(data, out, prng, corpus);
+            mutate_area(data, out, prng, corpus, config);
         } else {
             let mut split = prng.rand_range(0, len);
             while split == 1 {
@@ -725,6 +936,7 @@ pub fn ni_area<W: Write>(
 /// * `data_sz`: The size of the data.
 /// * `prng`: A mutable reference to a custom random number generator.
 /// * `corpus`: A shared reference to an `Arc<Vec<Vec<u8>>>` containing the corpus data.
+/// * `config`: The tunables of the aimed mutations.
 ///
 /// # Returns
 ///
@@ -736,7 +948,7 @@ pub fn ni_area<W: Write>(
 /// use prng::xorshift::Xorshift64;
 /// use prng::{Generator, Rng};
 /// use std::sync::Arc;
-/// use ni::ni_mutate;
+/// use ni::{ni_mutate, NiConfig};
 /// let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec!["<!DOCTYPE html>
 /// <html>
 ///   <body><h1>My 1337 Heading</h1>
@@ -747,7 +959,7 @@ pub fn ni_area<W: Write>(
 /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
 /// let mut data = corpus[0].clone();
 /// let data_sz = data.len();
-/// let res = ni_mutate(&mut data, data_sz, &mut prng, &corpus).unwrap();
+/// let res = ni_mutate(&mut data, data_sz, &mut prng, &corpus, &NiConfig::default()).unwrap();
 /// assert!(res.len() > 0);
 /// assert_ne!(res, corpus[0]);
 /// ```
@@ -756,6 +968,7 @@ pub fn ni_mutate(
     data_sz: usize,
     prng: &mut Rng<Generator>,
     corpus: &Arc<Vec<Vec<u8>>>,
+    config: &NiConfig,
 ) -> Result<Vec<u8>> {
     let mut res = Vec::new();
     let n = if prng.rand() & 3 == 1 {
@@ -764,9 +977,112 @@ pub fn ni_mutate(
         2 + prng.rand_range(0, data_sz >> (12 + 8))
     };
     if data_sz < 4096 {
-        ni_area(data, n, &mut res, prng, corpus);
+        ni_area(data, n, &mut res, prng, corpus, config);
     } else {
-        ni_area_parallel_hybrid(data, n, &mut res, prng, corpus);
+        ni_area_parallel_hybrid(data, n, &mut res, prng, corpus, config);
     }
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prng::xorshift::Xorshift64;
+
+    // Returns the quoted strings of `data`
+    fn quoted(data: &[u8]) -> Vec<&[u8]> {
+        data.split(|&c| c == b'"').skip(1).step_by(2).collect()
+    }
+
+    #[test]
+    fn drange_end_self_matching() {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        for _ in 0..100 {
+            let end = drange_end(br#""a\"b" x "c""#, b'"', b'"', &mut prng).unwrap();
+            assert!(end == 6 || end == 12, "{end}");
+        }
+        assert_eq!(drange_end(br#""unterminated"#, b'"', b'"', &mut prng), None);
+    }
+
+    #[test]
+    fn delim_swap_quotes() {
+        let data = br#"{"key": "value", "other": "thing", "n": 1}"#;
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        let mut sorted_data = data.to_vec();
+        sorted_data.sort_unstable();
+        let mut sorted_quoted = quoted(data);
+        sorted_quoted.sort_unstable();
+
+        let mut swapped = 0;
+        for _ in 0..100 {
+            let mut out = Vec::new();
+            if delim_swap(data, &mut out, &mut prng).is_none() {
+                continue;
+            }
+            let mut sorted_out = out.clone();
+            sorted_out.sort_unstable();
+            let mut out_quoted = quoted(&out);
+            let reordered = out_quoted != quoted(data);
+            out_quoted.sort_unstable();
+            if sorted_out == sorted_data && out_quoted == sorted_quoted && reordered {
+                swapped += 1;
+            }
+        }
+        assert!(swapped > 0);
+    }
+
+    #[test]
+    fn strategies() {
+        let data = b"<a>1337 [x, y] \"q\" (z)</a>\n<b>42</b>\n".to_vec();
+        let corpus = Arc::new(vec![data.clone()]);
+        let config = NiConfig::default();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+
+        for (mutation, _) in NiMutation::WEIGHTED {
+            let mut applied = 0;
+            for _ in 0..100 {
+                let mut out = Vec::new();
+                if mutation
+                    .apply(&data, &mut out, &mut prng, &corpus, &config)
+                    .is_none()
+                {
+                    assert!(out.is_empty(), "{mutation:?} wrote without applying");
+                    continue;
+                }
+                applied += 1;
+                match mutation {
+                    NiMutation::InsertRandomByte => assert_eq!(out.len(), data.len() + 1),
+                    NiMutation::DeleteRandomByte => assert_eq!(out.len(), data.len() - 1),
+                    NiMutation::Jump => assert!(out.len() < data.len()),
+                    NiMutation::RepeatChars
+                    | NiMutation::InsertRandomData
+                    | NiMutation::InsertSemirandomBytes => {
+                        assert!(out.len() >= data.len());
+                    }
+                    NiMutation::TextualNumber => assert!(out.len() > data.len()),
+                    NiMutation::OverwriteSemirandomBytes => assert_eq!(out.len(), data.len()),
+                    NiMutation::AimedJump | NiMutation::AimedFusion | NiMutation::DelimSwap => {
+                        assert!(!out.is_empty());
+                    }
+                }
+            }
+            assert!(applied > 0, "{mutation:?} was never applied");
+        }
+    }
+
+    #[test]
+    fn strategies_on_tiny_inputs() {
+        let corpus = Arc::new(vec![b"corpus entry".to_vec()]);
+        let config = NiConfig::default();
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        for (mutation, _) in NiMutation::WEIGHTED {
+            let mut out = Vec::new();
+            if mutation
+                .apply(b"A", &mut out, &mut prng, &corpus, &config)
+                .is_none()
+            {
+                assert!(out.is_empty());
+            }
+        }
+    }
+}
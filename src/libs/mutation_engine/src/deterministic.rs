@@ -0,0 +1,117 @@
+// Deterministic mutation stages that walk a test case position by position, in the spirit of
+// AFL's deterministic phase. Unlike the randomized mutators they are reproducible and finite.
+
+// Upper bound of steps a single deterministic stage yields, so that large inputs cannot stall
+// the fuzzer in a deterministic stage
+pub const DETERMINISTIC_MAX_STEPS: usize = 1 << 16;
+
+/// Iterator over the outputs of a deterministic stage created by e.g.
+/// `MutationEngine::deterministic_bitflips_n`. Each item is a copy of the test case with a
+/// single deterministic mutation applied. The stage is exhausted once all positions were
+/// visited or `DETERMINISTIC_MAX_STEPS` items were yielded, whichever comes first. After
+/// that it keeps returning `None`.
+#[derive(Debug, Clone)]
+pub struct DeterministicIter {
+    // Snapshot of the test case the stage operates on
+    data: Vec<u8>,
+    // Number of adjacent bits flipped per step
+    bits: usize,
+    // Number of bits the flipped bits advance per step
+    stride: usize,
+    // Index of the next step
+    pos: usize,
+    // Total number of steps this stage yields
+    steps: usize,
+}
+
+impl DeterministicIter {
+    pub(crate) fn bitflips(data: Vec<u8>, bits: usize) -> Self {
+        let positions = (data.len() * 8).saturating_sub(bits - 1);
+        let steps = if data.is_empty() {
+            0
+        } else {
+            positions.min(DETERMINISTIC_MAX_STEPS)
+        };
+        Self {
+            data,
+            bits,
+            stride: 1,
+            pos: 0,
+            steps,
+        }
+    }
+
+    pub(crate) fn byteflips(data: Vec<u8>) -> Self {
+        let steps = data.len().min(DETERMINISTIC_MAX_STEPS);
+        Self {
+            data,
+            bits: 8,
+            stride: 8,
+            pos: 0,
+            steps,
+        }
+    }
+
+    fn flip(&mut self) {
+        let start = self.pos * self.stride;
+        for bit in start..start + self.bits {
+            self.data[bit / 8] ^= 0x80 >> (bit % 8);
+        }
+    }
+}
+
+impl Iterator for DeterministicIter {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.steps {
+            return None;
+        }
+        self.flip();
+        let out = self.data.clone();
+        self.flip();
+        self.pos += 1;
+        Some(out)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.steps - self.pos;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for DeterministicIter {}
+
+impl std::iter::FusedIterator for DeterministicIter {}
+
+/// State of the `DeterministicBitSweep` mutator: every single bit flip of a test case followed by
+/// every single byte flip, yielded one per call.
+#[derive(Debug, Clone)]
+pub(crate) struct BitSweep {
+    // The pristine test case the sweep walks
+    data: Vec<u8>,
+    stages: std::iter::Chain<DeterministicIter, DeterministicIter>,
+}
+
+impl BitSweep {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        Self {
+            data: data.to_vec(),
+            stages: DeterministicIter::bitflips(data.to_vec(), 1)
+                .chain(DeterministicIter::byteflips(data.to_vec())),
+        }
+    }
+
+    /// Whether the sweep walks `data`.
+    pub(crate) fn sweeps(&self, data: &[u8]) -> bool {
+        self.data == data
+    }
+}
+
+impl Iterator for BitSweep {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stages.next()
+    }
+}
@@ -0,0 +1,139 @@
+// Detection and length-preserving (de|en)coding of ASCII encoded regions, such as hex strings or
+// base64 tokens, embedded in a test case.
+
+// Minimum number of encoded characters for a run to be considered an encoded region. Shorter
+// runs are mostly ordinary words or numbers.
+const MIN_REGION_LEN: usize = 8;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+/// The encodings `StandardMutators::EncodedRegion` scans a test case for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionEncoding {
+    // Runs of `[0-9a-fA-F]` with an even length
+    Hex,
+    // Runs of the standard base64 alphabet `[A-Za-z0-9+/]`, excluding any `=` padding
+    Base64,
+}
+
+impl RegionEncoding {
+    fn is_alphabet(self, b: u8) -> bool {
+        match self {
+            Self::Hex => b.is_ascii_hexdigit(),
+            Self::Base64 => b.is_ascii_alphanumeric() || b == b'+' || b == b'/',
+        }
+    }
+
+    /// Number of characters of a run of `len` characters that can be decoded and re-encoded
+    /// to exactly the same length.
+    const fn usable_len(self, len: usize) -> usize {
+        match self {
+            Self::Hex => len & !1,
+            // A single trailing base64 character carries less than a byte
+            Self::Base64 if len % 4 == 1 => len - 1,
+            Self::Base64 => len,
+        }
+    }
+
+    /// Finds all regions of `data` that look like they are encoded with `self`.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<(usize, usize)>` holding the start and end index of each region.
+    pub fn find_regions(self, data: &[u8]) -> Vec<(usize, usize)> {
+        let mut regions = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            if !self.is_alphabet(data[i]) {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < data.len() && self.is_alphabet(data[i]) {
+                i += 1;
+            }
+            let len = self.usable_len(i - start);
+            if len >= MIN_REGION_LEN {
+                regions.push((start, start + len));
+            }
+        }
+        regions
+    }
+
+    /// Decodes a region previously returned by `find_regions`.
+    pub fn decode(self, region: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Hex => region
+                .chunks_exact(2)
+                .map(|pair| (hex_value(pair[0]) << 4) | hex_value(pair[1]))
+                .collect(),
+            Self::Base64 => {
+                let mut out = Vec::with_capacity(region.len() * 3 / 4);
+                let mut acc = 0u32;
+                let mut bits = 0;
+                for c in region {
+                    acc = (acc << 6) | u32::from(base64_value(*c));
+                    bits += 6;
+                    if bits >= 8 {
+                        bits -= 8;
+                        out.push((acc >> bits) as u8);
+                        acc &= (1 << bits) - 1;
+                    }
+                }
+                out
+            }
+        }
+    }
+
+    /// Encodes `decoded` into `region`, which keeps its length. `decoded` has to originate from
+    /// `decode` on a region of the same length. Hex digits are written in uppercase if
+    /// `uppercase` is set. Unused trailing bits of a base64 region are set to 0.
+    pub fn encode(self, decoded: &[u8], region: &mut [u8], uppercase: bool) {
+        match self {
+            Self::Hex => {
+                let digits = if uppercase { HEX_UPPER } else { HEX_LOWER };
+                for (byte, pair) in decoded.iter().zip(region.chunks_exact_mut(2)) {
+                    pair[0] = digits[usize::from(byte >> 4)];
+                    pair[1] = digits[usize::from(byte & 0xf)];
+                }
+            }
+            Self::Base64 => {
+                let mut bytes = decoded.iter();
+                let mut acc = 0u32;
+                let mut bits = 0;
+                for c in region {
+                    if bits < 6 {
+                        acc = (acc << 8) | u32::from(*bytes.next().unwrap_or(&0));
+                        bits += 8;
+                    }
+                    bits -= 6;
+                    *c = BASE64_ALPHABET[((acc >> bits) & 0x3f) as usize];
+                    acc &= (1 << bits) - 1;
+                }
+            }
+        }
+    }
+}
+
+const fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => 0,
+    }
+}
+
+const fn base64_value(c: u8) -> u8 {
+    match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => 0,
+    }
+}
@@ -0,0 +1,64 @@
+// Lightweight field layouts for structure-aware mutation of fixed-layout formats, such as file or
+// packet headers, without the need for a full grammar.
+
+/// The kind of a field, which decides how `StandardMutators::MutateField` mutates it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    // Unsigned integer spanning the whole field, which has to be 1, 2, 4 or 8 bytes long
+    Int { is_little_endian: bool },
+    // Text that is overwritten with dictionary tokens
+    Str,
+    // Opaque bytes that are changed bytewise
+    Bytes,
+}
+
+/// A single field of a fixed layout set via `MutationEngine::set_field_layout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpec {
+    // Offset of the field from the start of the test case
+    pub offset: usize,
+    // Length of the field in bytes
+    pub len: usize,
+    // How the field is mutated
+    pub kind: FieldKind,
+}
+
+impl FieldSpec {
+    /// Creates a new `FieldSpec` covering `len` bytes starting at `offset`.
+    pub const fn new(offset: usize, len: usize, kind: FieldKind) -> Self {
+        Self { offset, len, kind }
+    }
+
+    /// Returns whether the field lies within a test case of `size` bytes.
+    pub(crate) fn fits(&self, size: usize) -> bool {
+        self.len != 0
+            && self
+                .offset
+                .checked_add(self.len)
+                .is_some_and(|end| end <= size)
+    }
+}
+
+/// Reads an unsigned integer spanning all of `bytes`.
+pub(crate) fn read_int(bytes: &[u8], is_little_endian: bool) -> u64 {
+    let fold = |acc: u64, b: &u8| (acc << 8) | u64::from(*b);
+    if is_little_endian {
+        bytes.iter().rev().fold(0, fold)
+    } else {
+        bytes.iter().fold(0, fold)
+    }
+}
+
+/// Writes `val` as an unsigned integer spanning all of `bytes`, dropping bits that do not fit.
+/// Bytes past the 8 that a `u64` occupies are zero-filled.
+pub(crate) fn write_int(bytes: &mut [u8], val: u64, is_little_endian: bool) {
+    let len = bytes.len();
+    for (i, b) in bytes.iter_mut().enumerate() {
+        let shift = if is_little_endian { i } else { len - i - 1 };
+        *b = if shift < 8 {
+            (val >> (8 * shift)) as u8
+        } else {
+            0
+        };
+    }
+}
@@ -0,0 +1,121 @@
+// Post-processors that repair integrity fields, such as checksums or length prefixes, after a
+// test case was mutated so that the target does not reject it right away.
+use crate::field::write_int;
+use std::ops::Range;
+
+// Reversed polynomial of the CRC-32 used by zlib, PNG and Ethernet
+const CRC32_POLY: u32 = 0xedb8_8320;
+// Size of a CRC-32 checksum in bytes
+const CRC32_LEN: usize = 4;
+
+/// A fix-up applied to every test case at the end of `MutationEngine::mutate`, set via
+/// `MutationEngine::set_post_processor`.
+pub trait PostProcessor {
+    /// Repairs the test case in place. The size of the test case is synced with its data
+    /// afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The mutated test case data.
+    fn fixup(&self, data: &mut Vec<u8>);
+}
+
+/// Applies all post-processors in order, e.g. a length fix-up followed by a checksum over it.
+impl PostProcessor for Vec<Box<dyn PostProcessor>> {
+    fn fixup(&self, data: &mut Vec<u8>) {
+        for post_processor in self {
+            post_processor.fixup(data);
+        }
+    }
+}
+
+/// Writes the CRC-32 of a byte range into a 4 byte field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crc32Fixup {
+    // Offset of the checksum field from the start of the test case
+    pub offset: usize,
+    // Bytes the checksum is computed over, clamped to the length of the test case
+    pub range: Range<usize>,
+    // Byte order the checksum is stored in
+    pub is_little_endian: bool,
+}
+
+impl Crc32Fixup {
+    /// Creates a new `Crc32Fixup` storing the big endian checksum of `range` at `offset`.
+    pub const fn new(offset: usize, range: Range<usize>) -> Self {
+        Self {
+            offset,
+            range,
+            is_little_endian: false,
+        }
+    }
+}
+
+impl PostProcessor for Crc32Fixup {
+    fn fixup(&self, data: &mut Vec<u8>) {
+        let end = self.range.end.min(data.len());
+        if self.offset + CRC32_LEN > data.len() || self.range.start > end {
+            return;
+        }
+        let crc = crc32(&data[self.range.start..end]);
+        write_int(
+            &mut data[self.offset..self.offset + CRC32_LEN],
+            u64::from(crc),
+            self.is_little_endian,
+        );
+    }
+}
+
+/// Writes the number of bytes following `body_start` into an unsigned integer field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthFieldFixup {
+    // Offset of the length field from the start of the test case
+    pub offset: usize,
+    // Width of the length field in bytes, bits that do not fit are dropped and bytes past the
+    // 8 of a `u64` are zero-filled
+    pub width: usize,
+    // Byte order the length is stored in
+    pub is_little_endian: bool,
+    // Offset the counted body starts at
+    pub body_start: usize,
+}
+
+impl LengthFieldFixup {
+    /// Creates a new `LengthFieldFixup` storing the length of the body starting at `body_start`.
+    pub const fn new(
+        offset: usize,
+        width: usize,
+        is_little_endian: bool,
+        body_start: usize,
+    ) -> Self {
+        Self {
+            offset,
+            width,
+            is_little_endian,
+            body_start,
+        }
+    }
+}
+
+impl PostProcessor for LengthFieldFixup {
+    fn fixup(&self, data: &mut Vec<u8>) {
+        if self.width == 0 || self.offset + self.width > data.len() {
+            return;
+        }
+        let len = data.len().saturating_sub(self.body_start) as u64;
+        write_int(
+            &mut data[self.offset..self.offset + self.width],
+            len,
+            self.is_little_endian,
+        );
+    }
+}
+
+/// Computes the CRC-32 (IEEE 802.3) of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, b| {
+        (0..8).fold(crc ^ u32::from(*b), |crc, _| {
+            (crc >> 1) ^ (CRC32_POLY & (crc & 1).wrapping_neg())
+        })
+    })
+}
@@ -1,14 +1,30 @@
 // Feature needs to stay here until issue #43244 is resolved: https://github.com/rust-lang/rust/issues/43244
 #![feature(extract_if)]
-mod grammer_caller;
+mod deterministic;
+mod encoded_region;
+mod field;
+mod fixup;
+mod torc;
+
+// Upper bound of mutation attempts per requested test case when generating distinct batches
+const DISTINCT_BATCH_RETRY_FACTOR: usize = 8;
+// Upper bound of extra edits applied by `mutate` to reach the minimum edit distance
+pub const MAX_EXTRA_EDITS: usize = 256;
+// Factor each corpus entry's energy is scaled by when picking a new test case, so that entries
+// with an energy of 0 keep a selection weight of 1
+const ENERGY_SCALE: usize = 16;
+// Delimiters the `SpliceOnBoundary` mutator cuts after, to keep text and JSON like inputs intact
+const SPLICE_BOUNDARIES: &[u8] = b"\n}>],";
 
 use errors::{Error, Result};
-use magic::{MAGIC_16, MAGIC_32, MAGIC_64, MAGIC_8};
+use magic::{DANGEROUS_TOKENS, MAGIC_16, MAGIC_32, MAGIC_64, MAGIC_8, MAGIC_F32, MAGIC_F64};
 use num_traits::{
     AsPrimitive, WrappingAdd, WrappingMul, WrappingNeg, WrappingShl, WrappingShr, WrappingSub,
 };
 
+use prng::chacha::ChaCha20;
 use prng::lehmer::Lehmer64;
+use prng::pcg::Pcg64;
 use prng::romuduojr::RomuDuoJr;
 use prng::romutrio::RomuTrio;
 use prng::shishua::ShiShua;
@@ -17,24 +33,47 @@ use prng::wyhash::Wyhash64;
 use prng::xorshift::Xorshift64;
 use prng::xorshiro128ss::XorShiro128ss;
 use prng::xorshiro256ss::XorShiro256ss;
-use prng::{Generator, Generators, Rng};
+use prng::{Generator, GeneratorTrait, Generators, Rng};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
 use std::{path::Path, ptr, sync::Arc, usize};
 use test_case::TestCase;
 
+use deterministic::BitSweep;
+pub use deterministic::{DeterministicIter, DETERMINISTIC_MAX_STEPS};
+pub use encoded_region::RegionEncoding;
+pub use field::{FieldKind, FieldSpec};
+pub use fixup::{crc32, Crc32Fixup, LengthFieldFixup, PostProcessor};
 use grammar_mutator::{Grammar, GrammarTemplate, TokenIdentifier};
-use grammer_caller::{GenerateFn, GrammarCaller};
 use ni::ni_mutate;
+pub use ni::{NiConfig, NiMutation};
 
 #[derive(Debug, Clone)]
 pub enum Mutators {
     Standard(StandardMutators),
     Custom(CustomMutators),
+    // Index into the mutators registered via `MutationEngine::register_custom`
+    User(usize),
+}
+
+impl Mutators {
+    /// Returns the `StandardMutators` variant identifying this mutator. Custom mutators map to
+    /// their counterparts `StandardMutators::Ni`, `StandardMutators::GrammarGenerator` and
+    /// `StandardMutators::GrammarSplice`, all user registered mutators to `StandardMutators::User`.
+    pub const fn kind(&self) -> StandardMutators {
+        match self {
+            Self::Standard(m) => *m,
+            Self::Custom(CustomMutators::Ni) => StandardMutators::Ni,
+            Self::Custom(CustomMutators::GrammarGenerator(_)) => StandardMutators::GrammarGenerator,
+            Self::Custom(CustomMutators::GrammarSplice(_)) => StandardMutators::GrammarSplice,
+            Self::User(_) => StandardMutators::User,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StandardMutators {
     ShuffleBytes,
     EraseBytes,
@@ -50,34 +89,193 @@ pub enum StandardMutators {
     ChangeBinaryInteger,
     CrossOver,
     Splice,
+    SpliceOnBoundary,
     Truncate,
     Append,
     AddFromMagic,
+    AddFromFloatMagic,
     AddWordFromDict,
     AddWordFromTORC,
+    EncodedRegion,
+    MutateField,
+    InjectDangerous,
+    ShuffleRecords,
+    Utf8CodepointMutate,
+    DeterministicBitSweep,
     Ni,
     GrammarGenerator,
+    GrammarSplice,
+    User,
+}
+
+impl StandardMutators {
+    /// All standard mutators that can be selected by name. `Ni`, `GrammarGenerator` and
+    /// `GrammarSplice` are enabled through `MutationEngine::enable_custom_mutators` and `User` through
+    /// `MutationEngine::register_custom` instead.
+    pub const ALL: [Self; 27] = [
+        Self::ShuffleBytes,
+        Self::EraseBytes,
+        Self::InsertBytes,
+        Self::SwapNeighbors,
+        Self::SwapEndianness,
+        Self::ChangeBit,
+        Self::ChangeByte,
+        Self::NegateByte,
+        Self::ArithmeticWidth,
+        Self::CopyPart,
+        Self::ChangeASCIIInteger,
+        Self::ChangeBinaryInteger,
+        Self::CrossOver,
+        Self::Splice,
+        Self::SpliceOnBoundary,
+        Self::Truncate,
+        Self::Append,
+        Self::AddFromMagic,
+        Self::AddFromFloatMagic,
+        Self::AddWordFromDict,
+        Self::AddWordFromTORC,
+        Self::EncodedRegion,
+        Self::MutateField,
+        Self::InjectDangerous,
+        Self::ShuffleRecords,
+        Self::Utf8CodepointMutate,
+        Self::DeterministicBitSweep,
+    ];
+}
+
+impl FromStr for StandardMutators {
+    type Err = Error;
+
+    /// Parses a mutator from its name. Matching is case-insensitive and ignores `_` and `-`,
+    /// so `ShuffleBytes`, `shuffle_bytes` and `shuffle-bytes` all yield `ShuffleBytes`.
+    fn from_str(s: &str) -> Result<Self> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| *c != '_' && *c != '-')
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let name = normalize(s.trim());
+        Self::ALL
+            .into_iter()
+            .find(|m| normalize(&format!("{m:?}")) == name)
+            .ok_or_else(|| Error::new(&format!("Unknown mutator: {s}")))
+    }
+}
+
+/// Controls how strictly the mutation engine keeps test cases printable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrintableMode {
+    // No restrictions on the produced bytes
+    #[default]
+    None,
+    // Bytes inserted by the mutators are mapped into the printable ASCII range
+    Bytewise,
+    // Only whole whitespace-delimited tokens are replaced with printable tokens of the same length
+    WordBoundary,
+}
+
+/// How the `CrossOver` mutator combines the current test case with a corpus entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrossOverStrategy {
+    // Alternates randomly sized chunks of both, in a random total length
+    #[default]
+    RandomChunks,
+    // Keeps the head of the test case and takes the tail of the corpus entry from a single offset
+    SinglePoint,
+    // Replaces the region between two offsets of the test case with the same region of the entry
+    TwoPoint,
+}
+
+/// Which part of the test case the `Truncate` mutator removes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncateMode {
+    // Removes bytes from the end
+    #[default]
+    Tail,
+    // Removes bytes from the start, keeping trailing structures such as footers or checksums
+    Head,
+    // Removes a slice in between, keeping the first and the last byte
+    Middle,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CustomMutators {
     Ni,
     GrammarGenerator(GrammarTemplate),
+    // Splices a fragment generated from the grammar into the test case instead of replacing it
+    GrammarSplice(GrammarTemplate),
+}
+
+/// A user provided mutator that can be plugged into the engine via
+/// `MutationEngine::register_custom`.
+pub trait Mutator {
+    /// Mutates the test case in place. The size of the test case is synced with its data
+    /// afterwards, so implementations only need to modify `tc.data`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tc` - The test case to mutate.
+    /// * `prng` - The random number generator of the engine.
+    /// * `corpus` - The in-memory corpus of the engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mutator cannot be applied to the test case.
+    fn mutate(
+        &self,
+        tc: &mut TestCase,
+        prng: &mut Rng<Generator>,
+        corpus: &Arc<Vec<Vec<u8>>>,
+    ) -> Result<()>;
+}
+
+/// Statistics on how a single mutator performed, as returned by `MutationEngine::mutator_report`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MutatorStat {
+    // Number of times the mutator was picked by `mutate`
+    pub times_selected: usize,
+    // Number of times the mutator returned an error, e.g. as the test case was too small
+    pub times_errored: usize,
+    // Sum of the size changes of the test case caused by the mutator
+    pub bytes_delta_sum: i64,
+}
+
+/// Everything needed to reproduce a single call to `MutationEngine::mutate`, as returned by
+/// `MutationEngine::mutate_with_log`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationRecord {
+    // Index of the corpus entry the test case was derived from
+    pub corpus_index: usize,
+    // Mutators applied to the test case, in order
+    pub mutators: Vec<StandardMutators>,
+    // State of the PRNG right before the mutators were applied
+    pub prng_state: Vec<u64>,
 }
 
 pub struct MutationEngine {
     // List of available mutators to use
     mutators: Vec<Mutators>,
-    // Function pointer to the grammar generator if set
-    grammar_generator: GrammarCaller,
+    // Grammar of the grammar mutators if set
+    grammar: Option<Grammar>,
     // Start token for the grammar generator
     grammar_start: TokenIdentifier,
+    // Rules of the grammar other than the start one, to generate fragments from
+    grammar_fragments: Vec<TokenIdentifier>,
+    // Maximum recursion depth of the grammar generator, the grammar's default if unset
+    grammar_max_depth: Option<usize>,
+    // Tunables of the ni mutator
+    ni_config: NiConfig,
+    // Progress of the `DeterministicBitSweep` mutator through its current test case
+    bit_sweep: Option<BitSweep>,
     // Maximum percentage of the test case to mutate
     // TODO: expose to CLI
     max_mutation_factor: usize,
     // Maximum size of the test case
     // TODO:: expose to CLI
     max_test_case_size: usize,
+    // Hard cap on the length of a test case, enforced after every mutation pass
+    max_len: Option<usize>,
     // The current test case size when generating from scratch
     // We want to start small to increase exec/s and only increase
     // the generation of new test cases when we cannot find new
@@ -85,18 +283,62 @@ pub struct MutationEngine {
     current_test_case_size: usize,
     // PRNG to use for mutations
     pub prng: Rng<Generator>,
+    // The kind of generator backing `prng`
+    generator: Generators,
+    // The effective seed `prng` was seeded with
+    seed: usize,
     // Enforce ASCII printable mutations
-    printable: bool,
-    // User provided token dictionary
-    user_token_dict: Vec<Vec<u8>>,
+    printable: PrintableMode,
+    // How the `CrossOver` mutator combines two test cases
+    cross_over_strategy: CrossOverStrategy,
+    // Which part of the test case the `Truncate` mutator removes
+    truncate_mode: TruncateMode,
+    // User provided token dictionary as pairs of AFL dictionary level and token, sorted by level
+    user_token_dict: Vec<(usize, Vec<u8>)>,
     // Mutation rounds per iteration
     mutation_passes: usize,
+    // Deduplicate the test cases returned by `mutate_batch`
+    distinct_batches: bool,
+    // Whether `mutate_batch` already warned about a batch falling short of distinct test cases
+    warned_short_batch: bool,
+    // Inclusive range of the number of bytes the `Append` mutator grows a test case by
+    append_size_range: (usize, usize),
+    // Minimum number of bytes a mutated test case has to differ from its parent
+    min_edit_distance: usize,
+    // Probability of picking one of the enabled custom mutators instead of a standard one.
+    // None if all mutators are picked uniformly
+    custom_mutator_frequency: Option<f64>,
+    // Relative selection weight per mutator. Mutators without an entry have a weight of 1
+    mutator_weights: BTreeMap<StandardMutators, u32>,
+    // Number of times each mutator was applied since the last `take_mutator_executions`
+    mutator_executions: BTreeMap<StandardMutators, usize>,
+    // Cumulative statistics per mutator over the lifetime of the engine
+    mutator_stats: BTreeMap<StandardMutators, MutatorStat>,
+    // Mutators applied by the most recent mutation, in order
+    applied_mutators: Vec<StandardMutators>,
+    // Encodings scanned for by the `EncodedRegion` mutator
+    region_encodings: Vec<RegionEncoding>,
+    // Fixed field layout of the test cases used by the `MutateField` mutator
+    field_layout: Vec<FieldSpec>,
+    // Delimiter separating the records reordered by the `ShuffleRecords` mutator
+    record_delimiter: Vec<u8>,
+    // User provided tokens injected by the `InjectDangerous` mutator in addition to the built-in ones
+    extra_dangerous_tokens: Vec<Vec<u8>>,
+    // User provided magic values inserted by the `AddFromMagic` mutator next to the built-in ones
+    extra_magic: Vec<u64>,
+    // Mutators registered via `register_custom`, referenced by `Mutators::User`
+    user_mutators: Vec<Box<dyn Mutator>>,
+    // Fix-up applied to every test case after it was mutated, e.g. to repair a checksum
+    post_processor: Option<Box<dyn PostProcessor>>,
     // TORC dict filled dynamically during runtime
     torc_token_dict: Vec<Vec<u8>>,
     // The current test case to mutate
     pub test_case: TestCase,
     // Complete in-memory corpus
     pub corpus: Arc<Vec<Vec<u8>>>,
+    // Energy per corpus entry, used to favor entries when picking a new test case.
+    // Empty if all entries are picked uniformly
+    corpus_energy: Vec<usize>,
 }
 
 impl Default for MutationEngine {
@@ -124,37 +366,94 @@ impl Default for MutationEngine {
 
         let mut me = Self {
             mutators,
-            grammar_generator: GrammarCaller::default(),
+            grammar: None,
             grammar_start: TokenIdentifier(0),
+            grammar_fragments: Vec::new(),
+            grammar_max_depth: None,
+            ni_config: NiConfig::default(),
+            bit_sweep: None,
             max_mutation_factor: 10,
             max_test_case_size: 4096,
+            max_len: None,
             current_test_case_size: 128,
             prng: Rng::new(Generator::Xorshift64(Xorshift64::new(0))),
-            printable: false,
+            generator: Generators::Xorshift64,
+            seed: 0,
+            printable: PrintableMode::None,
+            cross_over_strategy: CrossOverStrategy::RandomChunks,
+            truncate_mode: TruncateMode::Tail,
             user_token_dict: Vec::new(),
             mutation_passes: 1,
+            distinct_batches: false,
+            warned_short_batch: false,
+            append_size_range: (1, 128),
+            min_edit_distance: 0,
+            custom_mutator_frequency: None,
+            mutator_weights: BTreeMap::new(),
+            mutator_executions: BTreeMap::new(),
+            mutator_stats: BTreeMap::new(),
+            applied_mutators: Vec::new(),
+            region_encodings: vec![RegionEncoding::Hex, RegionEncoding::Base64],
+            field_layout: Vec::new(),
+            record_delimiter: b"\n".to_vec(),
+            extra_dangerous_tokens: Vec::new(),
+            extra_magic: Vec::new(),
+            user_mutators: Vec::new(),
+            post_processor: None,
             torc_token_dict: Vec::new(),
             test_case: TestCase::default(),
             corpus: Arc::new(Vec::new()),
+            corpus_energy: Vec::new(),
         };
+        me = me.set_generator_seed(0);
         let initial_tc = me.prng.rand_byte_vec(128);
         me.add_to_corpus(&initial_tc);
         me
     }
 }
 
+/// Expands a seed of 0 into a non-zero seed derived from the system time, so the effective seed
+/// can be recorded and reused to reproduce a run. Non-zero seeds are returned as is.
+fn expand_seed(seed: usize) -> usize {
+    if seed != 0 {
+        return seed;
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as usize);
+    (nanos ^ 0xdeadbeefcafebabe).max(1)
+}
+
 impl MutationEngine {
     /// Create a new `MutationEngine` with default settings.
     /// The default settings are:
     ///
-    /// * `mutators`: all available mutators
+    /// * `mutators`: the standard mutators of the original engine. Mutators added later, such as
+    ///   `SpliceOnBoundary`, `AddFromFloatMagic`, `EncodedRegion`, `ShuffleRecords`, `MutateField`,
+    ///   `InjectDangerous`, `Utf8CodepointMutate` and `DeterministicBitSweep`, are opt-in via
+    ///   `set_mutators_by_name` so that seeded runs keep producing the same test cases
     /// * `max_mutation_factor`: 10
     /// * `max_test_case_size`: 4096,
+    /// * `max_len`: None
     /// * `current_test_case_size`: 128,
     /// * `prng`: Xorshift64
-    /// * `printable`: false
+    /// * `printable`: `PrintableMode::None`
+    /// * `cross_over_strategy`: `CrossOverStrategy::RandomChunks`
+    /// * `truncate_mode`: `TruncateMode::Tail`
     /// * `user_token_dict`: empty
     /// * `mutation_passes`: 1
+    /// * `distinct_batches`: false
+    /// * `append_size_range`: (1, 128)
+    /// * `min_edit_distance`: 0
+    /// * `custom_mutator_frequency`: None
+    /// * `mutator_weights`: empty
+    /// * `region_encodings`: `Hex` and `Base64`
+    /// * `field_layout`: empty
+    /// * `record_delimiter`: `\n`
+    /// * `extra_dangerous_tokens`: empty
+    /// * `extra_magic`: empty
+    /// * `user_mutators`: empty
+    /// * `post_processor`: None
     /// * `torc_token_dict`: empty
     /// * `test_case`: empty
     /// * `corpus`: empty
@@ -175,6 +474,7 @@ impl MutationEngine {
 
     /// Seed the PRNG with a given seed.
     /// This is useful for reproducible results. The default seed is 0.
+    /// A seed of 0 is expanded into a random non-zero seed, which can be read back via `current_seed`.
     /// If you want to use a different seed, you should call this function before any mutations.
     /// If you change Generators, the seed will be reset to 0. You will need to call this function again.
     ///
@@ -193,11 +493,89 @@ impl MutationEngine {
     /// let mutator = MutationEngine::new().set_generator_seed(1234);
     /// ```
     pub fn set_generator_seed(mut self, seed: usize) -> Self {
-        self.prng.set_seed(seed);
+        self.seed = expand_seed(seed);
+        self.prng.set_seed(self.seed);
         self
     }
 
+    /// Moves the PRNG to an independent stream for the worker thread `thread_id`, so that
+    /// workers sharing a seed do not produce correlated mutations. Generators that support
+    /// jumping are jumped ahead `thread_id` times. All others are reseeded with the current seed
+    /// mixed with `thread_id`, which is then returned by `current_seed`. Thread 0 keeps the
+    /// stream as is. Call this after setting the generator and its seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread_id` - The index of the worker thread.
+    ///
+    /// # Returns
+    ///
+    /// `Self` with the PRNG moved to the worker's stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    /// let mut first = MutationEngine::new().set_generator_seed(1234).set_worker_stream(0);
+    /// let mut second = MutationEngine::new().set_generator_seed(1234).set_worker_stream(1);
+    /// assert_ne!(first.prng.rand(), second.prng.rand());
+    /// ```
+    pub fn set_worker_stream(mut self, thread_id: usize) -> Self {
+        if thread_id == 0 {
+            return self;
+        }
+        if self.prng.jump() {
+            for _ in 1..thread_id {
+                self.prng.jump();
+            }
+            self
+        } else {
+            let seed = self.seed ^ thread_id.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            self.set_generator_seed(seed)
+        }
+    }
+
+    /// Returns the effective seed the PRNG was seeded with. If the PRNG was seeded with 0, this
+    /// is the random seed that 0 was expanded into.
+    ///
+    /// # Returns
+    ///
+    /// The seed as a `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    /// let mutator = MutationEngine::new().set_generator_seed(1234);
+    /// assert_eq!(mutator.current_seed(), 1234);
+    /// let mutator = MutationEngine::new().set_generator_seed(0);
+    /// assert_ne!(mutator.current_seed(), 0);
+    /// ```
+    pub const fn current_seed(&self) -> usize {
+        self.seed
+    }
+
+    /// Returns the kind of generator that is currently used by the PRNG.
+    ///
+    /// # Returns
+    ///
+    /// The `Generators` variant backing the PRNG.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use prng::Generators;
+    /// use mutation_engine::MutationEngine;
+    /// let mutator = MutationEngine::new().set_generator(&Generators::Lehmer64);
+    /// assert_eq!(mutator.current_generator(), Generators::Lehmer64);
+    /// ```
+    pub fn current_generator(&self) -> Generators {
+        self.generator.clone()
+    }
+
     /// Sets the random number generator to a specified generator from the `Generators` enum.
+    /// `Generators::External` cannot be constructed by name and leaves the engine untouched, use
+    /// `set_external_generator` instead.
     ///
     /// # Arguments
     ///
@@ -244,7 +622,60 @@ impl MutationEngine {
                 .prng
                 .set_generator(Generator::Wyhash64(Wyhash64::new(0))),
             Generators::Shishua => self.prng.set_generator(Generator::ShiShua(ShiShua::new(0))),
+            Generators::Chacha20 => self
+                .prng
+                .set_generator(Generator::ChaCha20(ChaCha20::new(0))),
+            Generators::Pcg64 => self.prng.set_generator(Generator::Pcg64(Pcg64::new(0))),
+            Generators::External => return self,
         };
+        self.generator = prng.clone();
+        self.set_generator_seed(0)
+    }
+
+    /// Drives the PRNG with a user supplied generator, e.g. one replaying a pre-recorded
+    /// sequence. The generator is used as is and not reseeded, so `current_seed` returns 0
+    /// until `set_generator_seed` is called explicitly.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator to use, see `prng::ExternalGenerator`.
+    ///
+    /// # Returns
+    ///
+    /// Self with the external generator set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::{GeneratorTrait, Generators};
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// #[derive(Clone)]
+    /// struct Counter(usize);
+    ///
+    /// impl GeneratorTrait for Counter {
+    ///     fn rand(&mut self) -> usize {
+    ///         self.0 += 1;
+    ///         self.0
+    ///     }
+    ///
+    ///     fn set_seed(&mut self, seed: usize) {
+    ///         self.0 = seed;
+    ///     }
+    /// }
+    ///
+    /// let mutator = MutationEngine::new().set_external_generator(Counter(0));
+    /// assert_eq!(mutator.current_generator(), Generators::External);
+    /// ```
+    pub fn set_external_generator<G: GeneratorTrait + Clone + Send + Sync + 'static>(
+        mut self,
+        generator: G,
+    ) -> Self {
+        self.prng = self
+            .prng
+            .set_generator(Generator::External(Box::new(generator)));
+        self.generator = Generators::External;
+        self.seed = 0;
         self
     }
 
@@ -273,6 +704,7 @@ impl MutationEngine {
     /// ```
     pub fn set_corpus(mut self, corpus: Arc<Vec<Vec<u8>>>) -> Self {
         self.corpus = corpus;
+        self.corpus_energy.clear();
         self
     }
 
@@ -298,40 +730,186 @@ impl MutationEngine {
     pub fn add_to_corpus(&mut self, test_case: &[u8]) {
         let corpus = Arc::make_mut(&mut self.corpus);
         corpus.push(test_case.to_vec());
+        if !self.corpus_energy.is_empty() {
+            self.corpus_energy.push(1);
+        }
     }
 
-    /// Reads user tokens from a file and converts them to a `Vec<Vec<u8>>`.
+    /// Shrinks the in-memory corpus. Identical entries are merged into one, keeping the highest
+    /// energy, and entries that are strict prefixes of longer entries are dropped. If more than
+    /// `keep_max` entries remain afterwards, only the `keep_max` shortest ones are kept. Surviving
+    /// entries keep their relative order. The current test case is replaced with a new pick from
+    /// the minimized corpus or emptied if no entries are left.
     ///
     /// # Arguments
     ///
-    /// * `tdict` - A path to the file containing user tokens separated by newlines.
+    /// * `keep_max` - The maximum number of entries to keep.
     ///
-    /// # Returns
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
     ///
-    /// A `Vec<Vec<u8>>` containing the user tokens read from the file.
-    fn user_tokens_to_vec<T: AsRef<Path>>(&mut self, tdict: T) -> Vec<Vec<u8>> {
-        let mut file = File::open(tdict).expect("Failed to open dictionary file");
-        let mut data = Vec::new();
-        let mut buffer = [0; 8192];
-        let mut last_line = Vec::new();
-        loop {
-            let n = file.read(&mut buffer).expect("Failed to read file");
-            if n == 0 {
-                break;
-            }
-            let buffer = &buffer[..n];
-            for byte in buffer {
-                last_line.push(*byte);
-                if *byte == b'\n' {
-                    data.push(last_line[..last_line.len() - 1].to_vec());
-                    last_line.clear();
+    /// let mut mutator = MutationEngine::new().set_corpus(
+    ///     vec![b"AB".to_vec(), b"ABCD".to_vec(), b"XYZ".to_vec(), b"ABCD".to_vec()].into(),
+    /// );
+    /// mutator.minimize_corpus(8);
+    /// assert_eq!(*mutator.corpus, vec![b"ABCD".to_vec(), b"XYZ".to_vec()]);
+    /// ```
+    pub fn minimize_corpus(&mut self, keep_max: usize) {
+        let energy = |idx: usize| self.corpus_energy.get(idx).copied().unwrap_or(1);
+        let mut order: Vec<usize> = (0..self.corpus.len()).collect();
+        // Sorting lexicographically puts duplicates next to each other and each entry right in
+        // front of the entries it is a prefix of
+        order.sort_by(|&a, &b| self.corpus[a].cmp(&self.corpus[b]).then(a.cmp(&b)));
+
+        let mut unique: Vec<(usize, usize)> = Vec::with_capacity(order.len());
+        for idx in order {
+            match unique.last_mut() {
+                Some((last, e)) if self.corpus[*last] == self.corpus[idx] => {
+                    *e = (*e).max(energy(idx));
                 }
+                _ => unique.push((idx, energy(idx))),
+            }
+        }
+        let mut survivors: Vec<(usize, usize)> = (0..unique.len())
+            .filter(|&i| {
+                unique.get(i + 1).is_none_or(|&(next, _)| {
+                    !self.corpus[next].starts_with(&self.corpus[unique[i].0])
+                })
+            })
+            .map(|i| unique[i])
+            .collect();
+
+        if survivors.len() > keep_max {
+            survivors.sort_by_key(|&(idx, _)| (self.corpus[idx].len(), idx));
+            survivors.truncate(keep_max);
+        }
+        survivors.sort_by_key(|&(idx, _)| idx);
+
+        let has_energy = !self.corpus_energy.is_empty();
+        let corpus = Arc::make_mut(&mut self.corpus);
+        let mut old = std::mem::take(corpus);
+        *corpus = survivors
+            .iter()
+            .map(|&(idx, _)| std::mem::take(&mut old[idx]))
+            .collect();
+        if has_energy {
+            self.corpus_energy = survivors.iter().map(|&(_, e)| e).collect();
+        }
+
+        if self.corpus.is_empty() {
+            self.test_case = TestCase::default();
+        } else {
+            self.set_new_test_case();
+        }
+    }
+
+    /// Fills the TORC token dictionary used by the `AddWordFromTORC` mutator with tokens extracted
+    /// from the corpus, replacing any previous tokens. Tokens are substrings of printable ASCII
+    /// runs that occur in at least two corpus entries, such as keywords of a text based protocol.
+    /// Only the first 4096 bytes of each entry are scanned and tokens are at most 32 bytes long.
+    /// Tokens shared by more entries and longer tokens are kept first.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_len` - The minimum length of a token in bytes.
+    /// * `max_tokens` - The maximum number of tokens to keep.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_corpus(
+    ///     vec![b"user=admin&id=1".to_vec(), b"user=guest&id=2".to_vec()].into(),
+    /// );
+    /// mutator.extract_torc_from_corpus(4, 16);
+    /// assert!(mutator.torc_tokens().contains(&b"&id=".to_vec()));
+    /// ```
+    pub fn extract_torc_from_corpus(&mut self, min_len: usize, max_tokens: usize) {
+        self.torc_token_dict = torc::extract_tokens(&self.corpus, min_len, max_tokens);
+    }
+
+    /// Returns the tokens of the TORC token dictionary.
+    pub fn torc_tokens(&self) -> &[Vec<u8>] {
+        &self.torc_token_dict
+    }
+
+    /// Sets the energy of the corpus entry at `index`. Entries are picked as the base for a new
+    /// test case proportional to their energy. All entries start out with an energy of 1, while
+    /// entries with an energy of 0 are only picked rarely.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the corpus entry.
+    /// * `energy` - The energy to assign to the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is out of bounds of the corpus.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_corpus(vec![vec![1u8], vec![2u8]].into());
+    /// assert!(mutator.set_energy_for(1, 100).is_ok());
+    /// assert!(mutator.set_energy_for(2, 100).is_err());
+    /// ```
+    pub fn set_energy_for(&mut self, index: usize, energy: usize) -> Result<()> {
+        if index >= self.corpus.len() {
+            return Err(Error::new("Corpus index out of bounds"));
+        }
+        if self.corpus_energy.is_empty() {
+            self.corpus_energy = vec![1; self.corpus.len()];
+        }
+        self.corpus_energy[index] = energy;
+        Ok(())
+    }
+
+    /// Picks the index of a corpus entry, proportional to its energy if any energy was assigned.
+    /// Entries with an energy of 0 keep a small chance of being picked.
+    fn pick_corpus_index(&mut self) -> usize {
+        if self.corpus_energy.len() != self.corpus.len()
+            || self.corpus_energy.iter().all(|&energy| energy == 0)
+        {
+            return self.prng.rand_range(0, self.corpus.len());
+        }
+        let weight = |energy: usize| energy.saturating_mul(ENERGY_SCALE).max(1);
+        let total = self
+            .corpus_energy
+            .iter()
+            .fold(0usize, |acc, &energy| acc.saturating_add(weight(energy)));
+        let mut roll = self.prng.rand_range(0, total);
+        for (idx, &energy) in self.corpus_energy.iter().enumerate() {
+            if roll < weight(energy) {
+                return idx;
             }
+            roll -= weight(energy);
         }
-        data
+        self.corpus_energy.len() - 1
+    }
+
+    /// Reads user tokens from a file and converts them to a `Vec<Vec<u8>>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tdict` - A path to the file containing user tokens separated by newlines.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<(usize, Vec<u8>)>` containing the levels and user tokens read from the file.
+    fn user_tokens_to_vec<T: AsRef<Path>>(&mut self, tdict: T) -> Vec<(usize, Vec<u8>)> {
+        let data = std::fs::read(tdict).expect("Failed to read dictionary file");
+        data.split(|b| *b == b'\n')
+            .filter_map(parse_dict_line)
+            .collect()
     }
 
     /// Sets the user token dictionary by loading tokens from the given file.
+    /// See `set_token_dicts` for the supported format.
     ///
     /// # Arguments
     ///
@@ -352,20 +930,64 @@ impl MutationEngine {
     ///
     /// mutator = mutator.set_token_dict(token_file_path);
     /// ```
-    pub fn set_token_dict<T: AsRef<Path>>(mut self, token_dict: T) -> Self {
-        self.user_token_dict = self.user_tokens_to_vec(token_dict);
+    pub fn set_token_dict<T: AsRef<Path>>(self, token_dict: T) -> Self {
+        self.set_token_dicts(&[token_dict])
+    }
+
+    /// Sets the user token dictionary by loading and concatenating the tokens from all given
+    /// files. Every line holds one token. Empty lines and lines starting with `#` are skipped.
+    /// Lines in the AFL dictionary format, i.e. `name="value"` or just `"value"`, are decoded,
+    /// where `\xNN`, `\\` and `\"` escape raw bytes, backslashes and quotes. An AFL level given
+    /// as `name@level="value"` gates the token until the number of mutation passes reaches the
+    /// level. Whitespace around such a line is ignored. Any other line is taken as is, without
+    /// its line terminator, with a level of 0. Empty tokens are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `dicts` - Paths to the files containing the user tokens.
+    ///
+    /// # Returns
+    ///
+    /// Self with the updated user token dictionary.
+    ///
+    /// # Panics
+    ///
+    /// If one of the files cannot be read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mutator = MutationEngine::new().set_token_dicts(&["dicts/test.dict", "dicts/mixed.dict"]);
+    /// ```
+    pub fn set_token_dicts<T: AsRef<Path>>(mut self, dicts: &[T]) -> Self {
+        self.user_token_dict = Vec::new();
+        for dict in dicts {
+            let tokens = self.user_tokens_to_vec(dict);
+            self.user_token_dict.extend(tokens);
+        }
+        self.user_token_dict.sort_by_key(|(level, _)| *level);
         println!(
             "[HANTU] Loaded {} tokens from user dictionary",
             self.user_token_dict.len()
         );
-        self.mutators
-            .push(Mutators::Standard(StandardMutators::AddWordFromDict));
+        if !self
+            .mutators
+            .iter()
+            .any(|m| matches!(m, Mutators::Standard(StandardMutators::AddWordFromDict)))
+        {
+            self.mutators
+                .push(Mutators::Standard(StandardMutators::AddWordFromDict));
+        }
         self
     }
 
     /// Enables custom mutators that are not as stable/fast as the others.
-    /// This currently includes: `CustomMutator::Ni` and `CustomMutator::GrammarMutator`.
-    /// The former closely resembles radamsa, and the latter generates a requested grammar
+    /// This currently includes: `CustomMutator::Ni`, `CustomMutator::GrammarMutator` and
+    /// `CustomMutator::GrammarSplice`. The first closely resembles radamsa, the second generates
+    /// a requested grammar and the last splices fragments of a grammar into the test case.
+    /// Both grammar mutators share a single grammar, the last one enabled.
     ///
     ///
     /// # Returns
@@ -391,148 +1013,269 @@ impl MutationEngine {
                     self.mutators.push(Mutators::Custom(CustomMutators::Ni));
                 }
                 CustomMutators::GrammarGenerator(gt) => {
-                    let grammar: Grammar = Grammar::new(&gt).unwrap();
-
-                    // Wrap the method call in a closure
-                    self.grammar_start = grammar.start.unwrap();
-                    let generate_fn: GenerateFn = Box::new(move |depth, id, prng, out| {
-                        grammar.generate(depth, id, prng, out);
-                    });
-
-                    self.grammar_generator = GrammarCaller { generate_fn };
-
+                    self.load_grammar(&gt);
                     self.mutators
                         .push(Mutators::Custom(CustomMutators::GrammarGenerator(gt)));
                 }
+                CustomMutators::GrammarSplice(gt) => {
+                    self.load_grammar(&gt);
+                    self.mutators
+                        .push(Mutators::Custom(CustomMutators::GrammarSplice(gt)));
+                }
             }
         }
 
         self
     }
 
-    /// Clears the list of mutators.
-    pub fn clear_mutators(&mut self) {
-        self.mutators.clear();
-    }
-
-    /// Sets whether the mutated data should be printable ASCII characters.
+    /// Sets the maximum recursion depth of the grammar mutators, 128 by default. Applies to the
+    /// grammar of already enabled grammar mutators as well as to ones enabled later on.
     ///
     /// # Arguments
     ///
-    /// * `printable` - If true, the mutated data will be printable ASCII characters.
+    /// * `max_depth` - The maximum recursion depth of the grammar generator.
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// Self with the updated printable property.
+    /// ```
+    /// use grammar_mutator::{DataFormat, GrammarTemplate};
+    /// use mutation_engine::{CustomMutators, MutationEngine};
+    ///
+    /// let mutator = MutationEngine::new()
+    ///     .set_grammar_max_depth(16)
+    ///     .enable_custom_mutators(vec![CustomMutators::GrammarGenerator(
+    ///         GrammarTemplate::DataFormat(DataFormat::Json),
+    ///     )]);
+    /// ```
+    pub fn set_grammar_max_depth(mut self, max_depth: usize) -> Self {
+        self.grammar_max_depth = Some(max_depth);
+        self.grammar = self
+            .grammar
+            .map(|grammar| grammar.with_max_depth(max_depth));
+        self
+    }
+
+    /// Sets the tunables of the ni mutator. Fewer and shorter aimed searches make it faster,
+    /// more and longer ones make it find better positions to splice data at.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The tunables of the ni mutator.
     ///
     /// # Example
     ///
     /// ```
-    /// use mutation_engine::MutationEngine;
+    /// use mutation_engine::{CustomMutators, MutationEngine, NiConfig};
     ///
-    /// let mut mutator = MutationEngine::new();
-    /// mutator = mutator.set_printable(true);
+    /// let mutator = MutationEngine::new()
+    ///     .set_ni_config(NiConfig {
+    ///         aimrounds: 16,
+    ///         ..NiConfig::default()
+    ///     })
+    ///     .enable_custom_mutators(vec![CustomMutators::Ni]);
     /// ```
-    pub const fn set_printable(mut self, printable: bool) -> Self {
-        self.printable = printable;
+    pub const fn set_ni_config(mut self, config: NiConfig) -> Self {
+        self.ni_config = config;
         self
     }
 
-    /// Sets the maximum mutation size factor to use when mutating a test case in percentage
-    /// values. This is currently used in only two mutators `Mutator::erase_bytes` and `Mutator::insert_bytes`.
+    /// Loads the grammar used by the grammar mutators.
+    fn load_grammar(&mut self, gt: &GrammarTemplate) {
+        let mut grammar: Grammar = Grammar::new(gt).unwrap();
+        if let Some(max_depth) = self.grammar_max_depth {
+            grammar = grammar.with_max_depth(max_depth);
+        }
+
+        self.grammar_start = grammar.start.unwrap();
+        self.grammar_fragments = grammar.fragment_rules();
+        self.grammar = Some(grammar);
+    }
+
+    /// Parses a list of mutator names into standard mutators.
+    fn parse_mutator_names<S: AsRef<str>>(&self, names: &[S]) -> Result<Vec<StandardMutators>> {
+        let mutators = names
+            .iter()
+            .map(|n| n.as_ref().parse::<StandardMutators>())
+            .collect::<Result<Vec<_>>>()?;
+        if mutators.contains(&StandardMutators::AddWordFromDict) && self.user_token_dict.is_empty()
+        {
+            return Err(Error::new(
+                "AddWordFromDict requires a token dictionary to be set",
+            ));
+        }
+        if mutators.contains(&StandardMutators::MutateField) && self.field_layout.is_empty() {
+            return Err(Error::new("MutateField requires a field layout to be set"));
+        }
+        Ok(mutators)
+    }
+
+    /// Registers a user provided mutator and enables it alongside the other mutators. It is picked
+    /// like any standard mutator and counted as `StandardMutators::User` in the statistics.
     ///
     /// # Arguments
     ///
-    /// * `num_factor` - The maximum mutation size factor to set (must be between 1 and 99, inclusive).
+    /// * `mutator` - The mutator to register.
     ///
     /// # Returns
     ///
-    /// Self with the updated maximum mutation size factor.
+    /// Self with the mutator registered.
     ///
     /// # Example
     ///
     /// ```
-    /// use mutation_engine::MutationEngine;
+    /// use errors::Result;
+    /// use mutation_engine::{MutationEngine, Mutator};
+    /// use prng::{Generator, Rng};
+    /// use std::sync::Arc;
+    /// use test_case::TestCase;
     ///
-    /// let mut mutator = MutationEngine::new();
-    /// mutator = mutator.set_max_mutation_size(25);
+    /// struct Reverse;
+    ///
+    /// impl Mutator for Reverse {
+    ///     fn mutate(
+    ///         &self,
+    ///         tc: &mut TestCase,
+    ///         _prng: &mut Rng<Generator>,
+    ///         _corpus: &Arc<Vec<Vec<u8>>>,
+    ///     ) -> Result<()> {
+    ///         tc.data.reverse();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mutator = MutationEngine::new().register_custom(Box::new(Reverse));
     /// ```
-    pub const fn set_max_mutation_size(mut self, num_factor: usize) -> Self {
-        if num_factor == 0 || num_factor >= 100 {
-            self.max_mutation_factor = 10;
-        } else {
-            self.max_mutation_factor = num_factor;
-        }
+    pub fn register_custom(mut self, mutator: Box<dyn Mutator>) -> Self {
+        self.mutators.push(Mutators::User(self.user_mutators.len()));
+        self.user_mutators.push(mutator);
         self
     }
 
-    /// Sets the maximum size a test case can grow into in bytes
+    /// Sets a post-processor that repairs every test case at the end of `mutate`, e.g. to keep a
+    /// checksum or length field consistent with the mutated body. Multiple post-processors can be
+    /// combined by passing a `Vec<Box<dyn PostProcessor>>`.
     ///
     /// # Arguments
     ///
-    /// * `size` - The maximum size in bytes. The default is 4096.
+    /// * `post_processor` - The post-processor to apply.
     ///
     /// # Returns
     ///
-    /// Self with the updated maximum mutation test case size.
+    /// Self with the post-processor set.
     ///
     /// # Example
     ///
     /// ```
-    /// use mutation_engine::MutationEngine;
+    /// use mutation_engine::{Crc32Fixup, LengthFieldFixup, MutationEngine, PostProcessor};
     ///
-    /// let mut mutator = MutationEngine::new();
-    /// mutator = mutator.set_max_test_case_size(128);
+    /// // 2 byte big endian length and a CRC-32, both covering the body starting at offset 6
+    /// let fixups: Vec<Box<dyn PostProcessor>> = vec![
+    ///     Box::new(LengthFieldFixup::new(0, 2, false, 6)),
+    ///     Box::new(Crc32Fixup::new(2, 6..usize::MAX)),
+    /// ];
+    /// let mutator = MutationEngine::new().set_post_processor(Box::new(fixups));
     /// ```
-    pub const fn set_max_test_case_size(mut self, size: usize) -> Self {
-        self.max_test_case_size = size;
+    pub fn set_post_processor(mut self, post_processor: Box<dyn PostProcessor>) -> Self {
+        self.post_processor = Some(post_processor);
         self
     }
 
-    /// Sets the number of mutation passes per mutation. The default is 1 to avoid
-    /// slowing down the fuzzer too much. Higher values can be used to increase
-    /// the mutation rate of the test cases in each iteration.
+    /// Replaces all standard mutators with the ones given by name. Enabled custom and user
+    /// registered mutators are kept.
     ///
     /// # Arguments
     ///
-    /// * `rounds` - The number of mutation passes to set.
+    /// * `names` - The names of the standard mutators to use, e.g. `shuffle_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Self>` with the updated list of mutators.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is unknown, `AddWordFromDict` is requested without a token
+    /// dictionary, or no mutator would be left.
     ///
     /// # Example
     ///
     /// ```
     /// use mutation_engine::MutationEngine;
     ///
-    /// let mut mutator = MutationEngine::new();
-    /// mutator = mutator.set_mutation_passes(5);
+    /// let mutator = MutationEngine::new().set_mutators_by_name(&["change_bit", "NegateByte"]);
+    /// assert!(mutator.is_ok());
+    /// let mutator = MutationEngine::new().set_mutators_by_name(&["does_not_exist"]);
+    /// assert!(mutator.is_err());
     /// ```
-    pub const fn set_mutation_passes(mut self, rounds: usize) -> Self {
-        self.mutation_passes = rounds;
-        self
+    pub fn set_mutators_by_name<S: AsRef<str>>(mut self, names: &[S]) -> Result<Self> {
+        let selected = self.parse_mutator_names(names)?;
+        self.mutators
+            .retain(|m| !matches!(m, Mutators::Standard(_)));
+        for m in selected {
+            if !self
+                .mutators
+                .iter()
+                .any(|x| matches!(x, Mutators::Standard(s) if *s == m))
+            {
+                self.mutators.push(Mutators::Standard(m));
+            }
+        }
+        if self.mutators.is_empty() {
+            return Err(Error::new("No mutators left to use"));
+        }
+        Ok(self)
     }
 
-    /// Set a new test case from the corpus or generate a new byte array one if the corpus is empty.
-    fn set_new_test_case(&mut self) {
-        let corpus_len = self.corpus.len();
-        assert!(corpus_len > 0, "Corpus is empty");
-        self.test_case.data.clear();
-        self.test_case.data_ptr = 0;
-
-        let idx = self.prng.rand_range(0, corpus_len);
-        let chosen = &self.corpus[idx];
+    /// Removes the standard mutators given by name from the list of mutators.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the standard mutators to disable, e.g. `shuffle_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Self>` with the updated list of mutators.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is unknown or no mutator would be left.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mutator = MutationEngine::new().disable_mutators_by_name(&["truncate", "splice"]);
+    /// assert!(mutator.is_ok());
+    /// ```
+    pub fn disable_mutators_by_name<S: AsRef<str>>(mut self, names: &[S]) -> Result<Self> {
+        let disabled = names
+            .iter()
+            .map(|n| n.as_ref().parse::<StandardMutators>())
+            .collect::<Result<Vec<_>>>()?;
+        self.mutators
+            .retain(|m| !matches!(m, Mutators::Standard(s) if disabled.contains(s)));
+        if self.mutators.is_empty() {
+            return Err(Error::new("No mutators left to use"));
+        }
+        Ok(self)
+    }
 
-        self.test_case.data.extend_from_slice(chosen);
-        self.test_case.size = chosen.len();
+    /// Clears the list of mutators.
+    pub fn clear_mutators(&mut self) {
+        self.mutators.clear();
     }
 
-    /// Sets the test case with the given data.
+    /// Sets whether the mutated data should be printable ASCII characters.
+    /// This is a shorthand for `set_printable_mode`, where `true` maps to `PrintableMode::Bytewise`
+    /// and `false` maps to `PrintableMode::None`.
     ///
     /// # Arguments
     ///
-    /// * `data` - A reference to a `Vec<u8>` containing the test case data.
+    /// * `printable` - If true, the mutated data will be printable ASCII characters.
     ///
     /// # Returns
     ///
-    /// Mutable reference to Self with the updated test case.
+    /// Self with the updated printable property.
     ///
     /// # Example
     ///
@@ -540,66 +1283,133 @@ impl MutationEngine {
     /// use mutation_engine::MutationEngine;
     ///
     /// let mut mutator = MutationEngine::new();
-    /// let test_case_data = vec![1u8, 2, 3, 4, 5];
+    /// mutator = mutator.set_printable(true);
+    /// ```
+    pub const fn set_printable(mut self, printable: bool) -> Self {
+        self.printable = if printable {
+            PrintableMode::Bytewise
+        } else {
+            PrintableMode::None
+        };
+        self
+    }
+
+    /// Sets how strictly the mutated data is kept printable.
+    /// `PrintableMode::Bytewise` maps bytes produced by the mutators into the printable ASCII range,
+    /// whereas `PrintableMode::WordBoundary` bypasses the configured mutators and only replaces whole
+    /// whitespace-delimited tokens with random printable tokens of the same length. This keeps the
+    /// length and the whitespace structure of text inputs intact.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The `PrintableMode` to use.
+    ///
+    /// # Returns
+    ///
+    /// Self with the updated printable mode.
+    ///
+    /// # Example
     ///
-    /// mutator.set_test_case(&test_case_data);
-    /// assert!(mutator.test_case.data == test_case_data);
     /// ```
-    pub fn set_test_case(&mut self, data: &[u8]) -> &mut Self {
-        self.test_case = TestCase::new(data);
+    /// use mutation_engine::{MutationEngine, PrintableMode};
+    ///
+    /// let mut mutator = MutationEngine::new()
+    ///     .set_corpus(vec![b"GET /index.html HTTP/1.1".to_vec()].into())
+    ///     .set_printable_mode(PrintableMode::WordBoundary);
+    /// let tc = mutator.mutate();
+    /// assert_eq!(tc.data.len(), 24);
+    /// assert_eq!(tc.data[3], b' ');
+    /// ```
+    pub const fn set_printable_mode(mut self, mode: PrintableMode) -> Self {
+        self.printable = mode;
         self
     }
 
-    /// Sets a random entry from the corpus as the test case.
+    /// Sets how the `CrossOver` mutator combines the current test case with a corpus entry.
+    /// `CrossOverStrategy::SinglePoint` and `CrossOverStrategy::TwoPoint` cut both at the same
+    /// offsets, which keeps the fields of binary formats at their positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - The `CrossOverStrategy` to use.
     ///
     /// # Returns
     ///
-    /// Self with the updated test case.
+    /// Self with the updated cross over strategy.
     ///
     /// # Example
     ///
     /// ```
-    /// use mutation_engine::MutationEngine;
+    /// use mutation_engine::{CrossOverStrategy, MutationEngine};
     ///
-    /// let mut mutator = MutationEngine::new();
-    /// let mut corpus = vec![vec![1u8, 2, 3, 4, 5], vec![6, 7, 8, 9, 10]];
-    /// mutator = mutator.set_corpus(corpus.into());
-    /// mutator = mutator.set_random_test_case();
-    /// assert!(mutator.test_case.data == vec![6u8, 7, 8, 9, 10] || mutator.test_case.data == vec![1u8, 2, 3, 4, 5]);
+    /// let mutator = MutationEngine::new().set_cross_over_strategy(CrossOverStrategy::SinglePoint);
     /// ```
-    pub fn set_random_test_case(mut self) -> Self {
-        let tc = self.get_random_corpus_entry();
-        self.set_test_case(&tc);
+    pub const fn set_cross_over_strategy(mut self, strategy: CrossOverStrategy) -> Self {
+        self.cross_over_strategy = strategy;
         self
     }
 
-    /// This function will return a random entry from the corpus as a vector of bytes.
-    /// If the corpus is empty it will return a random vector of bytes with a size of 128.
-    fn get_random_corpus_entry(&mut self) -> Vec<u8> {
-        if let Some(tc) = self.corpus.get(self.prng.rand_range(0, self.corpus.len())) {
-            tc.clone()
-        } else {
-            // TODO: If coverage not increased for a while, try to increase the size of the test case
-            // if self.last_cov_tick > XXX && self.current_test_case_size < self.max_test_case_size {}
-            self.prng.rand_byte_vec(self.current_test_case_size)
-        }
+    /// Sets which part of the test case the `Truncate` mutator removes. `TruncateMode::Head` and
+    /// `TruncateMode::Middle` keep the end of the test case intact, e.g. for formats with a
+    /// footer or a trailing checksum.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The `TruncateMode` to use.
+    ///
+    /// # Returns
+    ///
+    /// Self with the updated truncate mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{MutationEngine, TruncateMode};
+    ///
+    /// let mutator = MutationEngine::new().set_truncate_mode(TruncateMode::Head);
+    /// ```
+    pub const fn set_truncate_mode(mut self, mode: TruncateMode) -> Self {
+        self.truncate_mode = mode;
+        self
     }
 
-    /// This is a helper function that will ensure that a byte is printable
-    fn ensure_printable(&mut self) -> u8 {
-        let b = self.prng.rand_byte();
-        if self.printable {
-            b.wrapping_sub(32) % 95 + 32
+    /// Sets the maximum mutation size factor to use when mutating a test case in percentage
+    /// values. This is currently used in only two mutators `Mutator::erase_bytes` and `Mutator::insert_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_factor` - The maximum mutation size factor to set (must be between 1 and 99, inclusive).
+    ///
+    /// # Returns
+    ///
+    /// Self with the updated maximum mutation size factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// mutator = mutator.set_max_mutation_size(25);
+    /// ```
+    pub const fn set_max_mutation_size(mut self, num_factor: usize) -> Self {
+        if num_factor == 0 || num_factor >= 100 {
+            self.max_mutation_factor = 10;
         } else {
-            b
+            self.max_mutation_factor = num_factor;
         }
+        self
     }
 
-    /// Mutates the current test case based on the selected mutation strategy and mutation passes.
+    /// Sets the maximum size a test case can grow into in bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The maximum size in bytes. The default is 4096.
     ///
     /// # Returns
     ///
-    /// Mutable reference to the mutated `TestCase`.
+    /// Self with the updated maximum mutation test case size.
     ///
     /// # Example
     ///
@@ -607,961 +1417,3930 @@ impl MutationEngine {
     /// use mutation_engine::MutationEngine;
     ///
     /// let mut mutator = MutationEngine::new();
-    /// let test_case_data = vec![1u8, 2, 3, 4, 5];
-    ///
-    /// mutator.set_test_case(&test_case_data);
-    /// let mutated_test_case = mutator.mutate();
-    /// assert!(mutated_test_case.data != test_case_data);
+    /// mutator = mutator.set_max_test_case_size(128);
     /// ```
-    pub fn mutate(&mut self) -> &mut TestCase {
-        self.set_new_test_case();
-        for _ in 0..self.mutation_passes {
-            let _ = match self.prng.pick(&self.mutators) {
-                Mutators::Standard(StandardMutators::ShuffleBytes) => self.shuffle_bytes(),
-                Mutators::Standard(StandardMutators::EraseBytes) => self.erase_bytes(),
-                Mutators::Standard(StandardMutators::InsertBytes) => self.insert_bytes(),
-                Mutators::Standard(StandardMutators::SwapNeighbors) => self.swap_neighbors(),
-                Mutators::Standard(StandardMutators::SwapEndianness) => self.swap_endianness(),
-                Mutators::Standard(StandardMutators::ChangeBit) => self.change_bit(),
-                Mutators::Standard(StandardMutators::ChangeByte) => self.change_byte(),
-                Mutators::Standard(StandardMutators::ArithmeticWidth) => self.arithmetic_width(),
-                Mutators::Standard(StandardMutators::NegateByte) => self.negate_byte(),
-                Mutators::Standard(StandardMutators::CopyPart) => self.copy_part(),
-                Mutators::Standard(StandardMutators::ChangeASCIIInteger) => {
-                    self.change_ascii_integer()
-                }
-                Mutators::Standard(StandardMutators::ChangeBinaryInteger) => {
-                    self.change_binary_integer()
-                }
-                Mutators::Standard(StandardMutators::CrossOver) => self.cross_over(),
-                Mutators::Standard(StandardMutators::Splice) => self.splice(),
-                Mutators::Standard(StandardMutators::Truncate) => self.truncate(),
-                Mutators::Standard(StandardMutators::Append) => self.append(),
-                Mutators::Standard(StandardMutators::AddFromMagic) => self.insert_constant(),
-                Mutators::Standard(StandardMutators::AddWordFromDict) => self.add_word_from_dict(),
-                Mutators::Standard(StandardMutators::AddWordFromTORC) => self.add_word_from_torc(),
-                Mutators::Custom(CustomMutators::Ni) => self.ni(),
-                Mutators::Custom(CustomMutators::GrammarGenerator(_)) => self.grammar_gen(),
-                _ => unreachable!(),
-            };
-        }
-        &mut self.test_case
+    pub const fn set_max_test_case_size(mut self, size: usize) -> Self {
+        self.max_test_case_size = size;
+        self
     }
 
-    /// Mutator that generates a grammar output based on the grammar requested
-    fn grammar_gen(&mut self) -> Result<()> {
-        let mut out: Vec<u8> = Vec::new();
-        self.grammar_generator
-            .call_generate(0, self.grammar_start, &mut self.prng, &mut out);
-        self.set_test_case(&out);
-        Ok(())
+    /// Sets a hard cap on the length of a test case in bytes. Unlike the maximum test case size,
+    /// which only bounds how far some mutators grow a test case, the cap is enforced by
+    /// truncating the test case after every mutation pass, regardless of the mutator applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The maximum length of a mutated test case in bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new()
+    ///     .set_corpus(vec![vec![0x41; 256]].into())
+    ///     .set_max_len(64);
+    /// assert!(mutator.mutate().data.len() <= 64);
+    /// ```
+    pub const fn set_max_len(mut self, len: usize) -> Self {
+        assert!(len > 0, "Maximum test case length must be greater than 0");
+        self.max_len = Some(len);
+        self
     }
 
-    /// Mutator based on <https://github.com/aoh/ni>
-    fn ni(&mut self) -> Result<()> {
-        let res = ni_mutate(
-            &self.test_case.data,
-            self.test_case.size,
-            &mut self.prng,
-            &self.corpus,
-        );
-        self.set_test_case(&res.unwrap());
-        Ok(())
+    /// Sets the number of mutation passes per mutation. The default is 1 to avoid
+    /// slowing down the fuzzer too much. Higher values can be used to increase
+    /// the mutation rate of the test cases in each iteration.
+    ///
+    /// # Arguments
+    ///
+    /// * `rounds` - The number of mutation passes to set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// mutator = mutator.set_mutation_passes(5);
+    /// ```
+    pub const fn set_mutation_passes(mut self, rounds: usize) -> Self {
+        self.mutation_passes = rounds;
+        self
     }
 
-    /// Shuffles a slice of `self.test_case.data` with a random length between 1 and 8 (inclusive),
-    /// starting at a random index. If `self.test_case.size` is less than or equal to 2, returns an
-    /// error indicating that there's nothing to shuffle.
-    fn shuffle_bytes(&mut self) -> Result<()> {
-        if self.test_case.size < 2 {
-            return Err(Error::new("Nothing to shuffle"));
-        }
-        let shuffle_amount = self
-            .prng
-            .rand_range(1, std::cmp::min(self.test_case.size, 8))
-            + 1;
-        let shuffle_start = self
-            .prng
-            .rand_range(0, self.test_case.size - shuffle_amount);
-        self.prng
-            .shuffle(&mut self.test_case.data[shuffle_start..shuffle_start + shuffle_amount]);
-        Ok(())
+    /// Sets the range of the number of bytes the `Append` mutator grows a test case by.
+    /// The appended bytes are a randomly sized slice of the test case itself, which wraps
+    /// around to its start if the slice is longer than the remaining data. Appends never
+    /// grow a test case beyond the maximum test case size.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The minimum number of bytes to append.
+    /// * `max` - The maximum number of bytes to append.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is 0 or greater than `max`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mutator = MutationEngine::new().set_append_size_range(4, 16);
+    /// ```
+    pub fn set_append_size_range(mut self, min: usize, max: usize) -> Self {
+        assert!(
+            min > 0 && min <= max,
+            "Append size range must satisfy 0 < min <= max"
+        );
+        self.append_size_range = (min, max);
+        self
     }
 
-    /// Mutator that erases a random amount ([1; min(100, `test_case.size` * 0.1)]) of bytes from the test case
-    fn erase_bytes(&mut self) -> Result<()> {
-        if self.test_case.size == 0 {
+    /// Sets whether `mutate_batch` should only return distinct test cases.
+    /// Duplicates are detected by hashing and discarded. To guarantee termination, at most
+    /// `8 * n` mutations are attempted for a batch of `n` test cases. Small seeds with few
+    /// reachable mutations may therefore yield fewer than `n` test cases.
+    ///
+    /// # Arguments
+    ///
+    /// * `distinct` - If true, `mutate_batch` deduplicates its output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_distinct_batches(true);
+    /// let batch = mutator.mutate_batch(16);
+    /// assert!(batch.len() <= 16);
+    /// ```
+    pub const fn set_distinct_batches(mut self, distinct: bool) -> Self {
+        self.distinct_batches = distinct;
+        self
+    }
+
+    /// Sets the overall probability that one of the enabled custom mutators, i.e. `Ni` or the
+    /// grammar generator, is applied instead of a standard mutator. This decouples the rate of
+    /// the usually expensive custom mutators from how many of them are enabled. The group is
+    /// drawn first, the mutator within the group is picked as usual afterwards. The frequency
+    /// only takes effect while both custom and standard mutators are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency` - The probability in `[0, 1]` to apply a custom mutator.
+    ///
+    /// # Panics
+    ///
+    /// If `frequency` is not within `[0, 1]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{CustomMutators, MutationEngine};
+    ///
+    /// let mutator = MutationEngine::new()
+    ///     .enable_custom_mutators(vec![CustomMutators::Ni])
+    ///     .set_custom_mutator_frequency(0.05);
+    /// ```
+    pub fn set_custom_mutator_frequency(mut self, frequency: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&frequency),
+            "Custom mutator frequency must be within [0, 1]"
+        );
+        self.custom_mutator_frequency = Some(frequency);
+        self
+    }
+
+    /// Sets relative weights to bias which of the enabled mutators is picked. A mutator is picked
+    /// with a probability proportional to its weight. Mutators that are not listed keep a weight
+    /// of 1 and a weight of 0 disables a mutator without removing it from the enabled set. Custom
+    /// mutators are weighted by their `kind`. If a custom mutator frequency is set, the group is
+    /// still drawn first and the weights apply within the group. A mutation pass is skipped if all
+    /// mutators of the drawn group have a weight of 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - Pairs of mutators and their relative weights.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{MutationEngine, Mutators, StandardMutators};
+    ///
+    /// let mutator = MutationEngine::new().set_mutator_weights(vec![
+    ///     (Mutators::Standard(StandardMutators::CrossOver), 8),
+    ///     (Mutators::Standard(StandardMutators::ShuffleBytes), 0),
+    /// ]);
+    /// ```
+    pub fn set_mutator_weights(mut self, weights: Vec<(Mutators, u32)>) -> Self {
+        self.mutator_weights = weights.into_iter().map(|(m, w)| (m.kind(), w)).collect();
+        self
+    }
+
+    /// Sets the minimum edit distance between a test case returned by `mutate` and the corpus
+    /// entry it was derived from. The distance is the number of differing bytes at the same
+    /// positions plus the difference in length. If the mutators fall short, `mutate` changes
+    /// further untouched bytes until the distance is reached. At most `MAX_EXTRA_EDITS` extra
+    /// edits are applied per test case, so short inputs or large distances are met on a best
+    /// effort basis only. Extra edits respect the printable mode. A distance of 0 disables this.
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - The minimum number of bytes a mutated test case differs from its parent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new()
+    ///     .set_corpus(vec![vec![0u8; 64]].into())
+    ///     .set_min_edit_distance(4);
+    /// let tc = mutator.mutate();
+    /// let dist = tc.data.iter().filter(|b| **b != 0).count() + tc.data.len().abs_diff(64);
+    /// assert!(dist >= 4);
+    /// ```
+    pub const fn set_min_edit_distance(mut self, distance: usize) -> Self {
+        self.min_edit_distance = distance;
+        self
+    }
+
+    /// Sets a fixed field layout for the test cases and enables the `MutateField` mutator, which
+    /// picks one field and mutates it according to its `FieldKind`. Integer fields see arithmetic
+    /// and magic values, string fields are overwritten with tokens from the user or TORC dictionary
+    /// and byte fields are changed bytewise. Bytes outside of the picked field are never touched
+    /// and fields that do not fit into the current test case are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The `FieldSpec`s describing the fields.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Self>` with the field layout set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an integer field is not 1, 2, 4 or 8 bytes long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{FieldKind, FieldSpec, MutationEngine};
+    ///
+    /// let mutator = MutationEngine::new().set_field_layout(vec![
+    ///     FieldSpec::new(0, 4, FieldKind::Bytes),
+    ///     FieldSpec::new(4, 2, FieldKind::Int { is_little_endian: true }),
+    ///     FieldSpec::new(6, 8, FieldKind::Str),
+    /// ]);
+    /// assert!(mutator.is_ok());
+    /// ```
+    pub fn set_field_layout(mut self, layout: Vec<FieldSpec>) -> Result<Self> {
+        if let Some(field) = layout
+            .iter()
+            .find(|f| matches!(f.kind, FieldKind::Int { .. }) && !matches!(f.len, 1 | 2 | 4 | 8))
+        {
+            return Err(Error::new(&format!(
+                "Integer field at offset {} has an invalid length of {}",
+                field.offset, field.len
+            )));
+        }
+        self.field_layout = layout;
+        if !self.field_layout.is_empty()
+            && !self
+                .mutators
+                .iter()
+                .any(|m| matches!(m, Mutators::Standard(StandardMutators::MutateField)))
+        {
+            self.mutators
+                .push(Mutators::Standard(StandardMutators::MutateField));
+        }
+        Ok(self)
+    }
+
+    /// Sets the encodings the `EncodedRegion` mutator scans a test case for.
+    /// Regions are runs of at least 8 characters of the encoding's alphabet.
+    ///
+    /// # Arguments
+    ///
+    /// * `encodings` - The `RegionEncoding`s to scan for. If empty, `EncodedRegion` never applies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{MutationEngine, RegionEncoding};
+    ///
+    /// let mutator = MutationEngine::new().set_region_encodings(&[RegionEncoding::Base64]);
+    /// ```
+    pub fn set_region_encodings(mut self, encodings: &[RegionEncoding]) -> Self {
+        self.region_encodings = encodings.to_vec();
+        self
+    }
+
+    /// Sets the delimiter separating the records that the `ShuffleRecords` mutator reorders,
+    /// e.g. `b","` for the elements of a JSON array. The default is `b"\n"`, i.e. lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `delimiter` - The byte sequence separating two records.
+    ///
+    /// # Panics
+    ///
+    /// If `delimiter` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mutator = MutationEngine::new().set_record_delimiter(b"\r\n");
+    /// ```
+    pub fn set_record_delimiter(mut self, delimiter: &[u8]) -> Self {
+        assert!(!delimiter.is_empty(), "Record delimiter must not be empty");
+        self.record_delimiter = delimiter.to_vec();
+        self
+    }
+
+    /// Adds tokens to the built-in dangerous tokens, such as `%n` or `../`, that the
+    /// `InjectDangerous` mutator splices into the test case, and enables the mutator.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The tokens to inject in addition to the built-in ones. Empty tokens are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mutator = MutationEngine::new().set_extra_dangerous_tokens(&["$(id)", "%x%x%x%x"]);
+    /// ```
+    pub fn set_extra_dangerous_tokens<T: AsRef<[u8]>>(mut self, tokens: &[T]) -> Self {
+        self.extra_dangerous_tokens = tokens
+            .iter()
+            .map(|t| t.as_ref().to_vec())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if !self
+            .mutators
+            .iter()
+            .any(|m| matches!(m, Mutators::Standard(StandardMutators::InjectDangerous)))
+        {
+            self.mutators
+                .push(Mutators::Standard(StandardMutators::InjectDangerous));
+        }
+        self
+    }
+
+    /// Adds values to the built-in magic constants that the `AddFromMagic` mutator inserts into
+    /// the test case. A user value is written with as many bytes as it needs.
+    ///
+    /// # Arguments
+    ///
+    /// * `vals` - The values to insert in addition to the built-in ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// mutator.add_magic_values(vec![0xCAFE_BABE, 0x1337]);
+    /// ```
+    pub fn add_magic_values(&mut self, vals: Vec<u64>) {
+        self.extra_magic.extend(vals);
+    }
+
+    /// Adds the magic values from the given file, see `add_magic_values`. Every line holds one
+    /// hexadecimal value with an optional `0x` prefix. Empty lines and lines starting with `#`
+    /// are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A path to the file containing the magic values.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read or a line is not a valid 64-bit hexadecimal value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// mutator.load_magic_from_file("magic.txt").unwrap();
+    /// ```
+    pub fn load_magic_from_file<T: AsRef<Path>>(&mut self, path: T) -> Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        let mut vals = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let hex = line
+                .strip_prefix("0x")
+                .or_else(|| line.strip_prefix("0X"))
+                .unwrap_or(line);
+            let val = u64::from_str_radix(hex, 16)
+                .map_err(|_| Error::new(&format!("Invalid magic value: {line}")))?;
+            vals.push(val);
+        }
+        self.add_magic_values(vals);
+        Ok(())
+    }
+
+    /// Set a new test case from the corpus. If the corpus is empty, it is seeded with a random
+    /// byte array first, which then becomes the test case.
+    fn set_new_test_case(&mut self) -> usize {
+        if self.corpus.is_empty() {
+            let tc = self.get_random_corpus_entry();
+            self.add_to_corpus(&tc);
+        }
+        self.test_case.data.clear();
+        self.test_case.data_ptr = 0;
+        self.test_case.bit_ptr = 0;
+
+        let idx = self.pick_corpus_index();
+        let chosen = &self.corpus[idx];
+
+        self.test_case.data.extend_from_slice(chosen);
+        self.test_case.size = chosen.len();
+        idx
+    }
+
+    /// Sets the test case with the given data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A reference to a `Vec<u8>` containing the test case data.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to Self with the updated test case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// let test_case_data = vec![1u8, 2, 3, 4, 5];
+    ///
+    /// mutator.set_test_case(&test_case_data);
+    /// assert!(mutator.test_case.data == test_case_data);
+    /// ```
+    pub fn set_test_case(&mut self, data: &[u8]) -> &mut Self {
+        self.test_case = TestCase::new(data);
+        self
+    }
+
+    /// Sets a random entry from the corpus as the test case.
+    ///
+    /// # Returns
+    ///
+    /// Self with the updated test case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// let mut corpus = vec![vec![1u8, 2, 3, 4, 5], vec![6, 7, 8, 9, 10]];
+    /// mutator = mutator.set_corpus(corpus.into());
+    /// mutator = mutator.set_random_test_case();
+    /// assert!(mutator.test_case.data == vec![6u8, 7, 8, 9, 10] || mutator.test_case.data == vec![1u8, 2, 3, 4, 5]);
+    /// ```
+    pub fn set_random_test_case(mut self) -> Self {
+        self.test_case = TestCase::from_vec(self.get_random_corpus_entry());
+        self
+    }
+
+    /// This function will return a random entry from the corpus as a vector of bytes.
+    /// If the corpus is empty it will return a random vector of bytes with a size of 128.
+    fn get_random_corpus_entry(&mut self) -> Vec<u8> {
+        if let Some(tc) = self.corpus.get(self.prng.rand_range(0, self.corpus.len())) {
+            tc.clone()
+        } else {
+            // TODO: If coverage not increased for a while, try to increase the size of the test case
+            // if self.last_cov_tick > XXX && self.current_test_case_size < self.max_test_case_size {}
+            self.prng.rand_byte_vec(self.current_test_case_size)
+        }
+    }
+
+    /// This is a helper function that will ensure that a byte is printable
+    fn ensure_printable(&mut self) -> u8 {
+        let b = self.prng.rand_byte();
+        if self.printable != PrintableMode::None {
+            b.wrapping_sub(32) % 95 + 32
+        } else {
+            b
+        }
+    }
+
+    /// Mutates the current test case based on the selected mutation strategy and mutation passes.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to the mutated `TestCase`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// let test_case_data = vec![1u8, 2, 3, 4, 5];
+    ///
+    /// mutator.set_test_case(&test_case_data);
+    /// let mutated_test_case = mutator.mutate();
+    /// assert!(mutated_test_case.data != test_case_data);
+    /// ```
+    pub fn mutate(&mut self) -> &mut TestCase {
+        self.set_new_test_case();
+        self.mutate_current()
+    }
+
+    /// Mutates a new test case like `mutate` and writes the result to `out`, replacing its
+    /// contents. Reusing `out` across calls avoids allocating a buffer for every test case, e.g.
+    /// when the bytes are handed to a harness.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The buffer the mutated test case is written to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// let mut buf = Vec::new();
+    /// for _ in 0..10 {
+    ///     mutator.mutate_into(&mut buf);
+    ///     assert_eq!(buf, mutator.test_case.data);
+    /// }
+    /// ```
+    pub fn mutate_into(&mut self, out: &mut Vec<u8>) {
+        let tc = self.mutate();
+        out.clear();
+        out.extend_from_slice(&tc.data[..tc.size]);
+    }
+
+    /// Mutates a copy of `data` instead of a test case picked from the corpus, e.g. to explore
+    /// the neighborhood of a crashing input. Mutators drawing from the corpus, such as
+    /// `CrossOver`, still do so.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The test case to mutate.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to the mutated `TestCase`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_mutators_by_name(&["negate_byte"]).unwrap();
+    /// let mutated = mutator.mutate_from(&[0u8; 8]);
+    /// assert_eq!(mutated.data.iter().filter(|b| **b == 0xff).count(), 1);
+    /// ```
+    pub fn mutate_from(&mut self, data: &[u8]) -> &mut TestCase {
+        self.set_test_case(data);
+        self.mutate_current()
+    }
+
+    /// Works like `mutate` but additionally returns a `MutationRecord`, which allows `replay` to
+    /// reproduce the mutated test case later on.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the mutated `TestCase` and the `MutationRecord` describing how it was created.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_generator_seed(0x1337);
+    /// let (tc, record) = mutator.mutate_with_log();
+    /// assert!(!tc.data.is_empty());
+    /// assert_eq!(record.mutators.len(), 1);
+    /// ```
+    pub fn mutate_with_log(&mut self) -> (&mut TestCase, MutationRecord) {
+        let corpus_index = self.set_new_test_case();
+        let prng_state = self.prng.snapshot();
+        self.mutate_current();
+        let record = MutationRecord {
+            corpus_index,
+            mutators: self.applied_mutators.clone(),
+            prng_state,
+        };
+        (&mut self.test_case, record)
+    }
+
+    /// Re-applies the mutations described by `records` in order, each one starting from its
+    /// corpus entry. Given the same configuration, PRNG kind and corpus as the engine that
+    /// recorded them, the resulting test cases are byte-identical to the recorded ones.
+    ///
+    /// Replaying is not side effect free: the PRNG of the engine is left in the state after the
+    /// last replayed mutation, so later calls to `mutate` continue from there, and the replayed
+    /// mutations count towards the mutator executions and stats like any other mutation.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The records returned by `mutate_with_log`.
+    ///
+    /// # Returns
+    ///
+    /// Mutable reference to the `TestCase` produced by the last record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a record holds no PRNG state, i.e., it was recorded with a generator
+    /// that does not expose its state via `GeneratorTrait::get_state`, such as most
+    /// `Generator::External` ones. No record is replayed in that case.
+    ///
+    /// # Panics
+    ///
+    /// If a corpus index is out of bounds or a PRNG state was recorded with a different kind of
+    /// generator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_generator_seed(0x1337);
+    /// let (tc, record) = mutator.mutate_with_log();
+    /// let data = tc.data.clone();
+    /// mutator.mutate();
+    /// assert_eq!(mutator.replay(&[record]).unwrap().data, data);
+    /// ```
+    pub fn replay(&mut self, records: &[MutationRecord]) -> Result<&mut TestCase> {
+        if records.iter().any(|record| record.prng_state.is_empty()) {
+            return Err(Error::new(
+                "Cannot replay mutations recorded with a generator without state snapshots",
+            ));
+        }
+        let corpus = Arc::clone(&self.corpus);
+        for record in records {
+            self.set_test_case(&corpus[record.corpus_index]);
+            self.prng.restore(&record.prng_state);
+            self.mutate_current();
+        }
+        Ok(&mut self.test_case)
+    }
+
+    /// Applies the mutators to the current test case and enforces the minimum edit distance.
+    fn mutate_current(&mut self) -> &mut TestCase {
+        self.applied_mutators.clear();
+        let parent = (self.min_edit_distance > 0).then(|| self.test_case.data.clone());
+        self.apply_mutators();
+        if let Some(parent) = parent {
+            self.enforce_min_edit_distance(&parent);
+        }
+        if let Some(post_processor) = &self.post_processor {
+            post_processor.fixup(&mut self.test_case.data);
+            self.test_case.size = self.test_case.data.len();
+        }
+        &mut self.test_case
+    }
+
+    /// Applies the configured number of mutation passes to the current test case.
+    fn apply_mutators(&mut self) {
+        if self.printable == PrintableMode::WordBoundary {
+            for _ in 0..self.mutation_passes {
+                let _ = self.replace_word();
+                self.enforce_max_len();
+            }
+            return;
+        }
+        for _ in 0..self.mutation_passes {
+            let Some(idx) = self.pick_mutator_index() else {
+                continue;
+            };
+            let mutator = &self.mutators[idx];
+            let kind = mutator.kind();
+            let size_before = self.test_case.data.len();
+            *self.mutator_executions.entry(kind).or_default() += 1;
+            self.applied_mutators.push(kind);
+            let res = match mutator {
+                Mutators::Standard(StandardMutators::ShuffleBytes) => self.shuffle_bytes(),
+                Mutators::Standard(StandardMutators::EraseBytes) => self.erase_bytes(),
+                Mutators::Standard(StandardMutators::InsertBytes) => self.insert_bytes(),
+                Mutators::Standard(StandardMutators::SwapNeighbors) => self.swap_neighbors(),
+                Mutators::Standard(StandardMutators::SwapEndianness) => self.swap_endianness(),
+                Mutators::Standard(StandardMutators::ChangeBit) => self.change_bit(),
+                Mutators::Standard(StandardMutators::ChangeByte) => self.change_byte(),
+                Mutators::Standard(StandardMutators::ArithmeticWidth) => self.arithmetic_width(),
+                Mutators::Standard(StandardMutators::NegateByte) => self.negate_byte(),
+                Mutators::Standard(StandardMutators::CopyPart) => self.copy_part(),
+                Mutators::Standard(StandardMutators::ChangeASCIIInteger) => {
+                    self.change_ascii_integer()
+                }
+                Mutators::Standard(StandardMutators::ChangeBinaryInteger) => {
+                    self.change_binary_integer()
+                }
+                Mutators::Standard(StandardMutators::CrossOver) => self.cross_over(),
+                Mutators::Standard(StandardMutators::Splice) => self.splice(),
+                Mutators::Standard(StandardMutators::SpliceOnBoundary) => self.splice_on_boundary(),
+                Mutators::Standard(StandardMutators::Truncate) => self.truncate(),
+                Mutators::Standard(StandardMutators::Append) => self.append(),
+                Mutators::Standard(StandardMutators::AddFromMagic) => self.insert_constant(),
+                Mutators::Standard(StandardMutators::AddFromFloatMagic) => {
+                    self.insert_float_constant()
+                }
+                Mutators::Standard(StandardMutators::AddWordFromDict) => self.add_word_from_dict(),
+                Mutators::Standard(StandardMutators::AddWordFromTORC) => self.add_word_from_torc(),
+                Mutators::Standard(StandardMutators::EncodedRegion) => self.encoded_region(),
+                Mutators::Standard(StandardMutators::MutateField) => self.mutate_field(),
+                Mutators::Standard(StandardMutators::InjectDangerous) => self.inject_dangerous(),
+                Mutators::Standard(StandardMutators::ShuffleRecords) => self.shuffle_records(),
+                Mutators::Standard(StandardMutators::Utf8CodepointMutate) => {
+                    self.utf8_codepoint_mutate()
+                }
+                Mutators::Standard(StandardMutators::DeterministicBitSweep) => {
+                    self.deterministic_bit_sweep()
+                }
+                Mutators::Custom(CustomMutators::Ni) => self.ni(),
+                Mutators::Custom(CustomMutators::GrammarGenerator(_)) => self.grammar_gen(),
+                Mutators::Custom(CustomMutators::GrammarSplice(_)) => self.grammar_splice(),
+                Mutators::User(i) => {
+                    let res = self.user_mutators[*i].mutate(
+                        &mut self.test_case,
+                        &mut self.prng,
+                        &self.corpus,
+                    );
+                    self.test_case.size = self.test_case.data.len();
+                    res
+                }
+                _ => unreachable!(),
+            };
+            self.enforce_max_len();
+            let stat = self.mutator_stats.entry(kind).or_default();
+            stat.times_selected += 1;
+            stat.times_errored += usize::from(res.is_err());
+            stat.bytes_delta_sum += self.test_case.data.len() as i64 - size_before as i64;
+        }
+    }
+
+    /// Truncates the test case to the maximum length if one is set.
+    fn enforce_max_len(&mut self) {
+        let Some(max_len) = self.max_len else {
+            return;
+        };
+        let tc = &mut self.test_case;
+        tc.data.truncate(max_len);
+        tc.size = tc.size.min(tc.data.len());
+        if tc.data_ptr >= tc.size {
+            tc.data_ptr = tc.size;
+            tc.bit_ptr = 0;
+        }
+    }
+
+    /// Picks the index of the mutator to apply next. If a custom mutator frequency is set and both
+    /// custom and standard mutators are enabled, the group is drawn first and the mutator is
+    /// picked within the group. Otherwise all mutators are candidates. Candidates are picked
+    /// uniformly unless mutator weights are set, in which case a cumulative weight draw is used.
+    /// Returns `None` if all candidates have a weight of 0.
+    fn pick_mutator_index(&mut self) -> Option<usize> {
+        let n_custom = self
+            .mutators
+            .iter()
+            .filter(|m| matches!(m, Mutators::Custom(_)))
+            .count();
+        let group = match self.custom_mutator_frequency {
+            Some(freq) if n_custom != 0 && n_custom != self.mutators.len() => {
+                Some(self.prng.rand_float::<f64>() < freq)
+            }
+            _ => None,
+        };
+        let in_group = |m: &Mutators| group.is_none_or(|c| matches!(m, Mutators::Custom(_)) == c);
+
+        if self.mutator_weights.is_empty() {
+            let group_len = match group {
+                Some(true) => n_custom,
+                Some(false) => self.mutators.len() - n_custom,
+                None => self.mutators.len(),
+            };
+            let nth = self.prng.rand_range(0, group_len);
+            return self
+                .mutators
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| in_group(m))
+                .nth(nth)
+                .map(|(i, _)| i);
+        }
+
+        let (indices, weights): (Vec<usize>, Vec<u32>) = self
+            .mutators
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| in_group(m))
+            .map(|(i, m)| (i, *self.mutator_weights.get(&m.kind()).unwrap_or(&1)))
+            .unzip();
+        if weights.iter().all(|&w| w == 0) {
+            return None;
+        }
+        Some(*self.prng.pick_weighted(&indices, &weights))
+    }
+
+    /// Returns the number of times each mutator was applied by `mutate` since the last call and
+    /// resets the counters. Custom mutators are counted under their `StandardMutators` variant.
+    ///
+    /// # Returns
+    ///
+    /// A `BTreeMap<StandardMutators, usize>` mapping each applied mutator to its executions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::{MutationEngine, StandardMutators};
+    ///
+    /// let mut mutator = MutationEngine::new()
+    ///     .set_mutators_by_name(&["change_bit"])
+    ///     .unwrap()
+    ///     .set_mutation_passes(2);
+    /// mutator.mutate();
+    /// mutator.mutate();
+    /// let executions = mutator.take_mutator_executions();
+    /// assert_eq!(executions[&StandardMutators::ChangeBit], 4);
+    /// assert!(mutator.take_mutator_executions().is_empty());
+    /// ```
+    pub fn take_mutator_executions(&mut self) -> BTreeMap<StandardMutators, usize> {
+        std::mem::take(&mut self.mutator_executions)
+    }
+
+    /// Returns the statistics of every mutator applied by `mutate` so far, sorted by how often
+    /// each mutator was selected in descending order. Custom mutators are reported under the name
+    /// of their `StandardMutators` variant. Unlike `take_mutator_executions` this does not reset
+    /// the statistics.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<(String, MutatorStat)>` holding the name and statistics of each applied mutator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new()
+    ///     .set_mutators_by_name(&["append"])
+    ///     .unwrap();
+    /// // Appending to an empty test case fails
+    /// mutator.mutate_from(b"");
+    /// let report = mutator.mutator_report();
+    /// assert_eq!(report.len(), 1);
+    /// assert_eq!(report[0].1.times_selected, 1);
+    /// assert_eq!(report[0].1.times_errored, 1);
+    /// ```
+    pub fn mutator_report(&self) -> Vec<(String, MutatorStat)> {
+        let mut report: Vec<_> = self
+            .mutator_stats
+            .iter()
+            .map(|(kind, stat)| (format!("{kind:?}"), *stat))
+            .collect();
+        report.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.times_selected));
+        report
+    }
+
+    /// Changes bytes of the test case that still match `parent` until it differs from `parent`
+    /// by at least `min_edit_distance` bytes or `MAX_EXTRA_EDITS` edits were applied.
+    fn enforce_min_edit_distance(&mut self, parent: &[u8]) {
+        let data = &self.test_case.data;
+        let dist = parent.iter().zip(data).filter(|(a, b)| a != b).count()
+            + parent.len().abs_diff(data.len());
+        if dist >= self.min_edit_distance {
+            return;
+        }
+        let word_boundary = self.printable == PrintableMode::WordBoundary;
+        let mut candidates = parent
+            .iter()
+            .zip(data)
+            .enumerate()
+            .filter(|(_, (a, b))| a == b && !(word_boundary && a.is_ascii_whitespace()))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let extra = (self.min_edit_distance - dist)
+            .min(MAX_EXTRA_EDITS)
+            .min(candidates.len());
+        for _ in 0..extra {
+            let idx = candidates.swap_remove(self.prng.rand_range(0, candidates.len()));
+            let b = self.test_case.data[idx];
+            // Shift the byte within the allowed range so it is guaranteed to change
+            self.test_case.data[idx] = match self.printable {
+                PrintableMode::None => b ^ self.prng.rand_range(1, 256) as u8,
+                PrintableMode::Bytewise => {
+                    (b.wrapping_sub(32) % 95 + self.prng.rand_range(1, 95) as u8) % 95 + 32
+                }
+                PrintableMode::WordBoundary => {
+                    (b.wrapping_sub(33) % 94 + self.prng.rand_range(1, 94) as u8) % 94 + 33
+                }
+            };
+        }
+    }
+
+    /// Generates a batch of `n` mutated test cases, each based on a freshly selected corpus entry.
+    /// If distinct batches are enabled via `set_distinct_batches`, duplicates are discarded and
+    /// the batch may contain fewer than `n` test cases once the retry bound is hit. Only the first
+    /// such batch prints a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of test cases to generate.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<Vec<u8>>` holding the mutated test cases.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// let batch = mutator.mutate_batch(8);
+    /// assert_eq!(batch.len(), 8);
+    /// ```
+    pub fn mutate_batch(&mut self, n: usize) -> Vec<Vec<u8>> {
+        let mut batch = Vec::with_capacity(n);
+        if !self.distinct_batches {
+            for _ in 0..n {
+                batch.push(self.mutate().data.clone());
+            }
+            return batch;
+        }
+
+        let mut seen = HashSet::with_capacity(n);
+        let max_attempts = n.saturating_mul(DISTINCT_BATCH_RETRY_FACTOR);
+        let mut attempts = 0;
+        while batch.len() < n && attempts < max_attempts {
+            attempts += 1;
+            // Keyed by content, so distinct test cases with colliding hashes are both kept
+            let data = &self.mutate().data;
+            if !seen.contains(data) {
+                seen.insert(data.clone());
+                batch.push(data.clone());
+            }
+        }
+        if batch.len() < n && !self.warned_short_batch {
+            self.warned_short_batch = true;
+            println!(
+                "[HANTU] Warning: Only generated {} out of {n} distinct test cases after {attempts} attempts",
+                batch.len()
+            );
+        }
+        batch
+    }
+
+    /// Fuzzes an in-process harness for a given number of iterations. Each iteration mutates a
+    /// test case and hands the resulting bytes to `harness`. Inputs that make the harness panic are
+    /// recorded as crashes.
+    ///
+    /// Panics are caught by unwinding, so this is useless in builds with `panic = "abort"`, such
+    /// as the release profile of hantu: the first crash aborts the process. To silence the
+    /// message of every crash, a no-op panic hook is installed for the duration of the call. The
+    /// hook is process-global, so panics of other threads are not reported in that time either.
+    /// The previous hook is restored on return, also if mutating panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `iterations` - The number of mutated inputs to feed to the harness.
+    /// * `harness` - The closure under test.
+    ///
+    /// # Returns
+    ///
+    /// A `BTreeSet<Vec<u8>>` containing all unique inputs that caused the harness to panic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new().set_corpus(vec![b"AAAA".to_vec()].into());
+    /// let crashes = mutator.run_in_process(1000, |data| {
+    ///     if data.len() > 4 {
+    ///         panic!("input too long");
+    ///     }
+    /// });
+    /// assert!(crashes.iter().all(|c| c.len() > 4));
+    /// ```
+    pub fn run_in_process<F: FnMut(&[u8])>(
+        &mut self,
+        iterations: usize,
+        mut harness: F,
+    ) -> BTreeSet<Vec<u8>> {
+        let mut crashes = BTreeSet::new();
+        // Silence the default panic message for each crashing input and restore it afterwards
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            for _ in 0..iterations {
+                let data = &self.mutate().data;
+                if panic::catch_unwind(AssertUnwindSafe(|| harness(data))).is_err() {
+                    crashes.insert(data.clone());
+                }
+            }
+        }));
+        panic::set_hook(prev_hook);
+        if let Err(e) = res {
+            panic::resume_unwind(e);
+        }
+        crashes
+    }
+
+    /// Deterministic stage that walks over the current test case and flips `bits` adjacent bits at
+    /// every bit position, starting with the most significant bit of the first byte. A test case
+    /// of `n` bytes yields `n * 8 - bits + 1` outputs, bounded by `DETERMINISTIC_MAX_STEPS`.
+    /// Bits beyond that bound are never flipped, and the stage is exhausted once the iterator
+    /// returns `None`. An empty test case yields nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The number of adjacent bits to flip per step. Has to be one of 1, 2 or 4.
+    ///
+    /// # Returns
+    ///
+    /// A `DeterministicIter` yielding the mutated copies of the current test case.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is not one of 1, 2 or 4.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mutation_engine::MutationEngine;
+    ///
+    /// let mut mutator = MutationEngine::new();
+    /// mutator.set_test_case(&[0x00, 0x00]);
+    /// let outputs: Vec<Vec<u8>> = mutator.deterministic_bitflips_n(4).collect();
+    /// assert_eq!(outputs.len(), 13);
+    /// assert_eq!(outputs[0], vec![0xf0, 0x00]);
+    /// assert_eq!(outputs[6], vec![0x03, 0xc0]);
+    /// ```
+    pub fn deterministic_bitflips_n(&mut self, bits: usize) -> DeterministicIter {
+        assert!(
+            matches!(bits, 1 | 2 | 4),
+            "bits must be one of 1, 2 or 4, got {bits}"
+        );
+        DeterministicIter::bitflips(self.test_case.data.clone(), bits)
+    }
+
+    /// Mutator that replaces a random whitespace-delimited token in the test case with random
+    /// printable, non-whitespace ASCII characters of the same length.
+    fn replace_word(&mut self) -> Result<()> {
+        let data = &self.test_case.data;
+        let mut words = Vec::new();
+        let mut start = None;
+        for (i, b) in data.iter().enumerate() {
+            match (b.is_ascii_whitespace(), start) {
+                (false, None) => start = Some(i),
+                (true, Some(s)) => {
+                    words.push((s, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push((s, data.len()));
+        }
+        if words.is_empty() {
+            return Err(Error::new("No word to replace"));
+        }
+        let (start, end) = self.prng.pick(&words);
+        for i in *start..*end {
+            self.test_case.data[i] = self.prng.rand_range(33, 127) as u8;
+        }
+        Ok(())
+    }
+
+    /// Mutator that generates a grammar output based on the grammar requested
+    fn grammar_gen(&mut self) -> Result<()> {
+        let grammar = self
+            .grammar
+            .as_ref()
+            .ok_or_else(|| Error::new("No grammar loaded"))?;
+        let mut out: Vec<u8> = Vec::new();
+        grammar.generate(0, self.grammar_start, &mut self.prng, &mut out);
+        self.test_case = TestCase::from_vec(out);
+        Ok(())
+    }
+
+    /// Mutator that generates a fragment from a random non-start rule of the grammar and splices
+    /// it into a random position of the test case, without exceeding the maximum test case size
+    fn grammar_splice(&mut self) -> Result<()> {
+        let grammar = self
+            .grammar
+            .as_ref()
+            .ok_or_else(|| Error::new("No grammar loaded"))?;
+        if self.grammar_fragments.is_empty() {
+            return Err(Error::new("Grammar has no rules to splice from"));
+        }
+        let room = self.max_test_case_size.saturating_sub(self.test_case.size);
+        if room == 0 {
+            return Err(Error::new("Splice would exceed the maximum test case size"));
+        }
+        let rule = *self.prng.pick(&self.grammar_fragments);
+        let mut fragment: Vec<u8> = Vec::new();
+        grammar.generate_bounded_from(rule, room, &mut self.prng, &mut fragment);
+        // The rule may not allow any output that short
+        if fragment.len() > room {
+            return Err(Error::new(
+                "Grammar fragment does not fit into the test case",
+            ));
+        }
+        let idx = self.prng.rand_range(0, self.test_case.size + 1);
+        self.test_case.data.splice(idx..idx, fragment);
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Mutator based on <https://github.com/aoh/ni>
+    fn ni(&mut self) -> Result<()> {
+        let res = ni_mutate(
+            &self.test_case.data,
+            self.test_case.size,
+            &mut self.prng,
+            &self.corpus,
+            &self.ni_config,
+        );
+        self.test_case = TestCase::from_vec(res?);
+        Ok(())
+    }
+
+    /// Shuffles a slice of `self.test_case.data` with a random length between 1 and 8 (inclusive),
+    /// starting at a random index. If `self.test_case.size` is less than or equal to 2, returns an
+    /// error indicating that there's nothing to shuffle.
+    fn shuffle_bytes(&mut self) -> Result<()> {
+        if self.test_case.size < 2 {
+            return Err(Error::new("Nothing to shuffle"));
+        }
+        let shuffle_amount = self
+            .prng
+            .rand_range(1, std::cmp::min(self.test_case.size, 8))
+            + 1;
+        let shuffle_start = self
+            .prng
+            .rand_range(0, self.test_case.size - shuffle_amount);
+        self.prng
+            .shuffle(&mut self.test_case.data[shuffle_start..shuffle_start + shuffle_amount]);
+        Ok(())
+    }
+
+    /// Mutator that walks all single bit flips and then all single byte flips of the test case,
+    /// one per call, each applied to a pristine copy of it. The sweep starts over whenever it is
+    /// applied to a different test case and errors once it is exhausted. As its output depends on
+    /// previous calls, test cases it produced cannot be reproduced via `replay`.
+    fn deterministic_bit_sweep(&mut self) -> Result<()> {
+        let data = &self.test_case.data;
+        if !self
+            .bit_sweep
+            .as_ref()
+            .is_some_and(|sweep| sweep.sweeps(data))
+        {
+            self.bit_sweep = Some(BitSweep::new(data));
+        }
+        let out = self
+            .bit_sweep
+            .as_mut()
+            .and_then(Iterator::next)
+            .ok_or_else(|| Error::new("Bit sweep exhausted"))?;
+        self.test_case = TestCase::from_vec(out);
+        Ok(())
+    }
+
+    /// Mutator that treats the test case as UTF-8 text and swaps, deletes, duplicates or replaces a
+    /// codepoint, so the result stays valid UTF-8. Invalid sequences are dropped. Replacements
+    /// can be multi-byte codepoints and are restricted to printable ones if the printable mode
+    /// is set.
+    fn utf8_codepoint_mutate(&mut self) -> Result<()> {
+        let mut chars = self.test_case.data[..self.test_case.size]
+            .utf8_chunks()
+            .flat_map(|chunk| chunk.valid().chars())
+            .collect::<Vec<_>>();
+        if chars.is_empty() {
+            return Err(Error::new("No valid UTF-8 in test case"));
+        }
+        let idx = self.prng.rand_range(0, chars.len());
+        match self.prng.rand_range(0, 4) {
+            0 => {
+                // Only swap with a different codepoint so the swap has an effect
+                let others = (0..chars.len())
+                    .filter(|&i| chars[i] != chars[idx])
+                    .collect::<Vec<_>>();
+                if others.is_empty() {
+                    return Err(Error::new("No distinct codepoints to swap"));
+                }
+                let other = self.prng.pick(others);
+                chars.swap(idx, other);
+            }
+            1 => {
+                chars.remove(idx);
+            }
+            2 => chars.insert(idx, chars[idx]),
+            3 => {
+                let printable = self.printable != PrintableMode::None;
+                let mut c = rand_codepoint(&mut self.prng, printable);
+                while c == chars[idx] {
+                    c = rand_codepoint(&mut self.prng, printable);
+                }
+                chars[idx] = c;
+            }
+            _ => unreachable!(),
+        }
+        self.test_case.data = chars.into_iter().collect::<String>().into_bytes();
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Mutator that splits the test case on the record delimiter and shuffles the order of the
+    /// records. A trailing delimiter terminates the last record and stays at the end.
+    fn shuffle_records(&mut self) -> Result<()> {
+        let data = &self.test_case.data;
+        let delimiter = self.record_delimiter.as_slice();
+        let mut records: Vec<&[u8]> = Vec::new();
+        let (mut start, mut i) = (0, 0);
+        while i + delimiter.len() <= data.len() {
+            if data[i..].starts_with(delimiter) {
+                records.push(&data[start..i]);
+                i += delimiter.len();
+                start = i;
+            } else {
+                i += 1;
+            }
+        }
+        let trailing_delimiter = start == data.len();
+        if !trailing_delimiter {
+            records.push(&data[start..]);
+        }
+        if records.len() < 2 {
+            return Err(Error::new("Less than two records to shuffle"));
+        }
+        self.prng.shuffle(&mut records);
+        let mut shuffled = records.join(delimiter);
+        if trailing_delimiter {
+            shuffled.extend_from_slice(delimiter);
+        }
+        self.test_case.data = shuffled;
+        Ok(())
+    }
+
+    /// Mutator that erases a random amount ([1; min(100, `test_case.size` * 0.1)]) of bytes from the test case
+    fn erase_bytes(&mut self) -> Result<()> {
+        if self.test_case.size == 0 {
             return Err(Error::new("Nothing to delete"));
         }
 
-        // Have a 50% chance to only remove one arbitrary byte
-        if self.prng.bool() {
-            let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-            self.test_case.data.remove(idx);
-            self.test_case.size -= 1;
-        } else {
-            // Delete at most 10% of the data but no more than 100 for large inputs as erasing is expensive
-            // and we don't want to have this as a bottleneck
-            let max_factor = if self.test_case.size < 20 {
-                self.test_case.size
-            } else {
-                std::cmp::min(100, self.test_case.size / self.max_mutation_factor)
-            };
+        // Have a 50% chance to only remove one arbitrary byte
+        if self.prng.bool() {
+            let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+            self.test_case.data.remove(idx);
+            self.test_case.size -= 1;
+        } else {
+            // Delete at most 10% of the data but no more than 100 for large inputs as erasing is expensive
+            // and we don't want to have this as a bottleneck
+            let max_factor = if self.test_case.size < 20 {
+                self.test_case.size
+            } else {
+                std::cmp::min(100, self.test_case.size / self.max_mutation_factor)
+            };
+
+            for _ in 0..max_factor {
+                let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+                self.test_case.data.remove(idx);
+                self.test_case.size -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mutator that inserts a random amount ([1; min(100, `test_case.size` * 0.1)]) of bytes into the test case
+    fn insert_bytes(&mut self) -> Result<()> {
+        let to_insert = self.ensure_printable();
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+        // 50% chance to only insert one byte
+        if self.prng.bool() {
+            let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+            self.test_case.data.insert(idx, to_insert);
+            self.test_case.size += 1;
+            return Ok(());
+        }
+        let max_factor = if self.test_case.size < 8 {
+            8
+        } else if self.test_case.size < 64 {
+            self.prng.rand_range(8, self.test_case.size)
+        } else {
+            self.prng.rand_range(
+                0,
+                std::cmp::min(
+                    self.max_test_case_size - self.test_case.size,
+                    self.test_case.size / self.max_mutation_factor,
+                ),
+            ) + 1
+        };
+        self.test_case
+            .data
+            .splice(idx..idx, std::iter::repeat(to_insert).take(max_factor));
+        self.test_case.size += max_factor;
+        Ok(())
+    }
+
+    /// Swaps two (q|d|w) word, or byte neighbors in the test case
+    fn swap_neighbors(&mut self) -> Result<()> {
+        let fun: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()> =
+            match self.prng.rand_range(0, 4) {
+                0 => swap_neighbors_width::<u8>,
+                1 => swap_neighbors_width::<u16>,
+                2 => swap_neighbors_width::<u32>,
+                3 => swap_neighbors_width::<u64>,
+                _ => unreachable!(),
+            };
+
+        fun_caller(
+            fun,
+            &mut self.test_case.data,
+            self.test_case.size,
+            &mut self.prng,
+        )
+    }
+
+    /// Swaps the endianess of a random amount ([2,4, 8]) of bytes in the test case
+    fn swap_endianness(&mut self) -> Result<()> {
+        let mut width = self.prng.pick([2, 4, 8]) as usize;
+        if self.test_case.size < width {
+            return Err(Error::new("Mutation size > test case"));
+        }
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, Some(1));
+        width = usize::min(width, self.test_case.size - idx);
+        let slice = &mut self.test_case.data[idx..idx + width];
+        let ptr = slice.as_mut_ptr();
+        for i in 0..(width >> 1) {
+            unsafe {
+                std::ptr::swap(ptr.add(i), ptr.add(width - i - 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Mutator that changes a random bit in the test case
+    fn change_bit(&mut self) -> Result<()> {
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+        let bit = self.prng.rand_range(0, 8);
+        self.test_case.data[idx] ^= 1 << bit;
+        Ok(())
+    }
+
+    /// Mutator that changes a random byte in the test case by either replacing it with a random byte or
+    /// XOR'ing it with a random byte
+    fn change_byte(&mut self) -> Result<()> {
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+        let byte = &mut self.test_case.data[idx];
+        let r = self.prng.rand_byte();
+        if self.prng.bool() {
+            if r == *byte {
+                *byte = r.wrapping_add(1);
+            } else {
+                *byte = r;
+            }
+        } else if r == 0 {
+            *byte ^= r + 1;
+        } else {
+            *byte ^= r;
+        }
+        Ok(())
+    }
+
+    /// Mutator that negates a random byte in the test case
+    fn negate_byte(&mut self) -> Result<()> {
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
+        let byte = &mut self.test_case.data[idx];
+        *byte = !*byte;
+        Ok(())
+    }
+
+    /// Mutator that treats [1,2,4,8] bytes in the test case as an integer and performs an arithmetic operation on it
+    fn arithmetic_width(&mut self) -> Result<()> {
+        let fun: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()> =
+            match self.prng.rand_range(0, 4) {
+                0 => arithmetic::<u8>,
+                1 => arithmetic::<u16>,
+                2 => arithmetic::<u32>,
+                3 => arithmetic::<u64>,
+                _ => unreachable!(),
+            };
+        fun_caller(
+            fun,
+            &mut self.test_case.data,
+            self.test_case.size,
+            &mut self.prng,
+        )
+    }
+
+    /// Mutator that parses a run of ASCII digits in the test case as a decimal number, changes its
+    /// value and writes the new decimal representation back in place of the old one. The run at or
+    /// after a random offset is picked, falling back to the first run in the test case. If there
+    /// are no digits at all the first byte is flipped instead.
+    fn change_ascii_integer(&mut self) -> Result<()> {
+        if self.test_case.data.is_empty() {
+            return Err(Error::new("Cannot change an integer in an empty test case"));
+        }
+        let skip_past = self.prng.rand_range(0, self.test_case.data.len());
+        let Some((start, end)) = find_digit_run(&self.test_case.data, skip_past)
+            .or_else(|| find_digit_run(&self.test_case.data, 0))
+        else {
+            self.test_case.data[0] = !self.test_case.data[0];
+            return Ok(());
+        };
+
+        let val = parse_ascii_integer(&self.test_case.data[start..end]);
+        let val = match self.prng.rand_range(0, 5) {
+            0 => val.saturating_add(1),
+            1 => val.saturating_sub(1),
+            2 => val / 2,
+            3 => val.saturating_mul(2),
+            4 => self.prng.rand_range(0, val.saturating_mul(val) as usize) as u64,
+            _ => unreachable!(),
+        };
+        self.test_case
+            .data
+            .splice(start..end, val.to_string().into_bytes());
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Changes a random byte in the test case that is not within ASCII range
+    fn change_binary_integer(&mut self) -> Result<()> {
+        let mut val: usize;
+        let bin_size: usize = *self.prng.pick(&[1, 2, 4, 8]) as usize;
+        if self.test_case.size < bin_size {
+            return Err(Error::new("Mutation size > test case"));
+        }
+        let off = self.prng.rand_range(0, self.test_case.size - bin_size + 1);
+        let add = (self.prng.rand_range(0, 21) as isize - 10).max(0) as usize;
+        val =
+            if off < 64 && self.prng.bool_chance(4) {
+                self.test_case.size
+            } else {
+                match bin_size {
+                    1 => u8::from_be_bytes(
+                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
+                    ) as usize,
+                    2 => u16::from_be_bytes(
+                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
+                    ) as usize,
+                    4 => u32::from_be_bytes(
+                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
+                    ) as usize,
+                    8 => u64::from_be_bytes(
+                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
+                    ) as usize,
+                    _ => unreachable!(),
+                }
+            };
+        if self.prng.bool() {
+            val = match bin_size {
+                1 => u8::swap_bytes(val as u8).wrapping_add(add as u8) as usize,
+                2 => u16::swap_bytes(val as u16).wrapping_add(add as u16) as usize,
+                4 => u32::swap_bytes(val as u32).wrapping_add(add as u32) as usize,
+                8 => u64::swap_bytes(val as u64).wrapping_add(add as u64) as usize,
+                _ => unreachable!(),
+            };
+        } else {
+            val = val.wrapping_add(add);
+        };
+
+        if add == 0 || self.prng.bool() {
+            if add == val {
+                val = self.prng.rand_byte() as usize;
+            }
+            val = val.wrapping_neg();
+        }
+        match bin_size {
+            1 => {
+                self.test_case.data[off..off + bin_size]
+                    .copy_from_slice(&((val & 0xFF) as u8).to_be_bytes());
+            }
+            2 => {
+                self.test_case.data[off..off + bin_size]
+                    .copy_from_slice(&((val & 0xFFFF) as u16).to_be_bytes());
+            }
+            4 => {
+                self.test_case.data[off..off + bin_size]
+                    .copy_from_slice(&((val & 0xFFFF_FFFF) as u32).to_be_bytes());
+            }
+            8 => {
+                self.test_case.data[off..off + bin_size]
+                    .copy_from_slice(&(val as u64).to_be_bytes());
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    /// Mutator that either copies a random part of another test case to a random location of the current
+    /// test case overwriting existing data, or inserts a random part of another test case into the
+    /// current test case at a random location without overwriting existing data.
+    fn copy_part(&mut self) -> Result<()> {
+        let rand_test_case = self.get_random_corpus_entry();
+        assert!(!rand_test_case.is_empty(), "Copy part candidate is empty");
+        if self.prng.bool() {
+            copy_part_of(&rand_test_case, &mut self.test_case, &mut self.prng)
+        } else {
+            let max_size = self.test_case.size + self.prng.rand_range(1, self.max_test_case_size);
+            insert_part_of(
+                &rand_test_case,
+                &mut self.test_case,
+                &mut self.prng,
+                max_size,
+            )
+        }
+    }
+
+    /// Mutator that combines two random test cases using a cross over operation.
+    fn cross_over(&mut self) -> Result<()> {
+        match self.cross_over_strategy {
+            CrossOverStrategy::RandomChunks => self.cross_over_chunks(),
+            CrossOverStrategy::SinglePoint | CrossOverStrategy::TwoPoint => {
+                self.cross_over_points()
+            }
+        }
+    }
+
+    /// Cross over that alternates randomly sized chunks of the test case and a corpus entry.
+    fn cross_over_chunks(&mut self) -> Result<()> {
+        let mut data2 = self.get_random_corpus_entry();
+        let size2 = data2.len();
+        assert!(size2 > 0, "Cross over candidate is empty");
+
+        let data1 = &mut self.test_case.data;
+        let size1 = self.test_case.size;
+        let max_out_size = self.prng.rand_range(2, self.max_test_case_size);
+        let mut out = vec![0u8; max_out_size];
+        let mut out_pos = 0;
+        let mut pos1 = 0;
+        let mut pos2 = 0;
+        let mut currently_using_first_data = true;
+        while out_pos < max_out_size && (pos1 < size1 || pos2 < size2) {
+            let out_size_left = max_out_size - out_pos;
+            let (in_pos, in_size, data) = if currently_using_first_data {
+                (&mut pos1, size1, data1.as_mut_slice())
+            } else {
+                (&mut pos2, size2, &mut *data2)
+            };
+            if *in_pos < in_size {
+                let in_size_left = in_size - *in_pos;
+                let max_extra_size = std::cmp::min(out_size_left, in_size_left);
+                let extra_size = self.prng.rand() % (max_extra_size + 1);
+                if *in_pos + extra_size <= data.len() && out_pos < max_out_size {
+                    out[out_pos..(out_pos + extra_size)]
+                        .copy_from_slice(&data[*in_pos..*in_pos + extra_size]);
+                    out_pos += extra_size;
+                    *in_pos += extra_size;
+                }
+            }
+            currently_using_first_data = !currently_using_first_data;
+        }
+        self.test_case.size = max_out_size;
+        self.test_case.data = out;
+        Ok(())
+    }
+
+    /// Cross over that cuts the test case and a corpus entry at the same one or two offsets. A
+    /// single point keeps the head of the test case and appends the tail of the entry, two points
+    /// replace the region between them with the one of the entry.
+    fn cross_over_points(&mut self) -> Result<()> {
+        let other = self.get_random_corpus_entry();
+        let data = &mut self.test_case.data;
+        let common = data.len().min(other.len());
+        if common < 2 {
+            return Err(Error::new("Cross over candidates are too small"));
+        }
+        if self.cross_over_strategy == CrossOverStrategy::SinglePoint {
+            let cut = self.prng.rand_range(1, common);
+            data.truncate(cut);
+            data.extend_from_slice(&other[cut..]);
+        } else {
+            let (start, end) = self.prng.rand_two(common);
+            data[start..end].copy_from_slice(&other[start..end]);
+        }
+        self.test_case.size = data.len();
+        Ok(())
+    }
+
+    /// Mutator that splices a random part of another test case into the current test case at
+    /// a random location.
+    fn splice(&mut self) -> Result<()> {
+        assert!(self.corpus.len() > 0, "corpus is empty");
+        // `Clone` is not implemented for `Arc` so we get our reference to a test case by index.
+        let splice_tc = self.prng.pick(self.corpus.as_slice());
+        let splice_idx = self.prng.rand_range(0, splice_tc.len());
+        let split_idx = self.prng.rand_range(0, self.test_case.size);
+        let mut new_data = vec![0u8; split_idx + splice_tc.len() - splice_idx];
+        new_data.extend_from_slice(&self.test_case.data[..split_idx]);
+        new_data.extend_from_slice(&splice_tc[splice_idx..]);
+        self.test_case.size = new_data.len();
+        self.test_case.data = new_data;
+        Ok(())
+    }
+
+    /// Mutator that splices a corpus entry into the current test case like `splice`, but only cuts
+    /// both right after the same delimiter byte, e.g. a newline or `}`, so that the result
+    /// consists of whole lines or elements of both.
+    fn splice_on_boundary(&mut self) -> Result<()> {
+        if self.corpus.is_empty() {
+            return Err(Error::new("Corpus is empty"));
+        }
+        let donor = self.prng.pick(self.corpus.as_slice());
+        let data = &self.test_case.data[..self.test_case.size];
+        // Delimiters found in both inputs, so a cut after one of them has a counterpart
+        let shared = SPLICE_BOUNDARIES
+            .iter()
+            .filter(|d| data.contains(d) && donor.contains(d))
+            .copied()
+            .collect::<Vec<_>>();
+        if shared.is_empty() {
+            return Err(Error::new("No delimiter shared with the splice candidate"));
+        }
+        let delim = self.prng.pick(shared);
+        let cut_after = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == delim)
+                .map(|(i, _)| i + 1)
+                .collect::<Vec<_>>()
+        };
+        let split_idx = self.prng.pick(cut_after(data));
+        let splice_idx = self.prng.pick(cut_after(donor));
+        self.test_case.data.truncate(split_idx);
+        self.test_case.data.extend_from_slice(&donor[splice_idx..]);
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Mutator that removes a randomly sized chunk of the current test case.
+    fn truncate(&mut self) -> Result<()> {
+        let trunc_fac = (self.prng.rand_range(0, 50) + 1) as f64 * 0.01;
+        let size = self.test_case.size;
+        let mut removed = size - (size as f64 * (1.0 - trunc_fac)) as usize;
+        match self.truncate_mode {
+            TruncateMode::Tail => self.test_case.data.truncate(size - removed),
+            TruncateMode::Head => {
+                self.test_case.data.drain(..removed);
+            }
+            TruncateMode::Middle => {
+                // Both the first and the last byte have to survive
+                removed = removed.min(size.saturating_sub(2));
+                if removed == 0 {
+                    return Err(Error::new("Test case too small to truncate in the middle"));
+                }
+                let start = self.prng.rand_range(1, size - removed);
+                self.test_case.data.drain(start..start + removed);
+            }
+        }
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Mutator that appends a random sized chunk of the current test case to itself. The chunk
+    /// size is drawn from the append size range and bounded by the maximum test case size.
+    fn append(&mut self) -> Result<()> {
+        let size = self.test_case.size;
+        if size == 0 {
+            return Err(Error::new("Cannot append to an empty test case"));
+        }
+        let (min, max) = self.append_size_range;
+        let room = self.max_test_case_size.saturating_sub(size);
+        if room < min {
+            return Err(Error::new("Append would exceed the maximum test case size"));
+        }
+        let len = self.prng.rand_range(min, max.min(room) + 1);
+        let mut from = self.prng.rand_range(0, size);
+        let mut left = len;
+        while left > 0 {
+            let chunk = left.min(size - from);
+            self.test_case.data.extend_from_within(from..from + chunk);
+            left -= chunk;
+            from = 0;
+        }
+        self.test_case.size += len;
+        Ok(())
+    }
+
+    /// Mutator that overwrites an aligned 4 or 8 byte window of the test case with a special
+    /// float from the magic set, e.g. NaN or a subnormal, in either endianness.
+    fn insert_float_constant(&mut self) -> Result<()> {
+        let little_endian = self.prng.bool();
+        let value = if self.prng.bool() {
+            let bits = self.prng.pick(MAGIC_F32).to_bits();
+            if little_endian {
+                bits.to_le_bytes().to_vec()
+            } else {
+                bits.to_be_bytes().to_vec()
+            }
+        } else {
+            let bits = self.prng.pick(MAGIC_F64).to_bits();
+            if little_endian {
+                bits.to_le_bytes().to_vec()
+            } else {
+                bits.to_be_bytes().to_vec()
+            }
+        };
+        let width = value.len();
+        if width > self.test_case.size {
+            return Err(Error::new("Mutation size > test case"));
+        }
+        let idx = self.prng.rand_range(0, self.test_case.size / width) * width;
+        self.test_case.data[idx..idx + width].copy_from_slice(&value);
+        Ok(())
+    }
+
+    /// Mutator that inserts a constant value from the magic set into the current test case.
+    fn insert_constant(&mut self) -> Result<()> {
+        // Roll a 4 sided dice to decide which val to read from, with a fifth side for the user
+        // provided values
+        let sides = if self.extra_magic.is_empty() { 4 } else { 5 };
+        let dice_roll = self.prng.rand_range(0, sides);
+        let val: usize;
+        let val_size: usize;
+        match dice_roll {
+            0 => {
+                val = self.prng.pick(MAGIC_8) as usize;
+                val_size = std::mem::size_of::<u8>();
+            }
+            1 => {
+                val = self.prng.pick(MAGIC_16) as usize;
+                val_size = std::mem::size_of::<u16>();
+            }
+            2 => {
+                val = self.prng.pick(MAGIC_32) as usize;
+                val_size = std::mem::size_of::<u32>();
+            }
+            3 => {
+                val = self.prng.pick(MAGIC_64) as usize;
+                val_size = std::mem::size_of::<u64>();
+            }
+            4 => {
+                let v = *self.prng.pick(&self.extra_magic);
+                val = v as usize;
+                val_size = (std::mem::size_of::<u64>() - v.leading_zeros() as usize / 8).max(1);
+            }
+            _ => unreachable!(),
+        }
+
+        // Roll a random index into data
+        if val_size > self.test_case.size {
+            return Err(Error::new("Mutation size > test case"));
+        }
+        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, Some(val_size));
+        if idx + val_size >= self.test_case.size {
+            return Err(Error::new("Mutation size > test case"));
+        }
+
+        if val == 0 {
+            // Write the val to data
+            for i in 0..val_size {
+                self.test_case.data[idx + i] = 0;
+            }
+        } else {
+            // Check if val has any unset upper bytes
+            let mut v = val;
+            let mut unset_bytes = 0;
+            while v > 0 {
+                v >>= 8;
+                unset_bytes += 1;
+            }
+            let start = idx + val_size - unset_bytes;
+            let end = idx + val_size;
+            for i in start..end {
+                self.test_case.data[i] = (val >> (8 * (i - start))) as u8;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mutator that inserts a random value from the user token dictionary into the current test
+    /// case. Only tokens with a dictionary level of at most the number of mutation passes are
+    /// considered.
+    fn add_word_from_dict(&mut self) -> Result<()> {
+        let n = self.eligible_user_tokens();
+        if n == 0 {
+            return Err(Error::new("No user token within the mutation passes level"));
+        }
+        let (_, token) = self.prng.pick(&self.user_token_dict[..n]);
+        add_from_dict(
+            token,
+            &mut self.test_case,
+            &mut self.prng,
+            self.max_test_case_size,
+        )
+    }
+
+    /// Returns the number of leading user tokens whose dictionary level is at most the number of
+    /// mutation passes.
+    fn eligible_user_tokens(&self) -> usize {
+        self.user_token_dict
+            .partition_point(|(level, _)| *level <= self.mutation_passes)
+    }
+
+    /// Mutator that inserts a random value from the TORC token dictionary into the current test case.
+    fn add_word_from_torc(&mut self) -> Result<()> {
+        if self.torc_token_dict.is_empty() {
+            return Err(Error::new("TORC token dict is empty"));
+        };
+        let token = self.prng.pick(&self.torc_token_dict);
+        add_from_dict(
+            token,
+            &mut self.test_case,
+            &mut self.prng,
+            self.max_test_case_size,
+        )
+    }
+
+    /// Mutator that splices a built-in or user provided dangerous token at a random offset into
+    /// the test case. Tokens that are not printable are skipped unless `PrintableMode::None` is set.
+    fn inject_dangerous(&mut self) -> Result<()> {
+        let printable = self.printable != PrintableMode::None;
+        let tokens = DANGEROUS_TOKENS
+            .iter()
+            .copied()
+            .chain(self.extra_dangerous_tokens.iter().map(Vec::as_slice))
+            .filter(|t| self.test_case.size + t.len() <= self.max_test_case_size)
+            .filter(|t| !printable || t.iter().all(|b| matches!(b, 32..=126)))
+            .collect::<Vec<_>>();
+        if tokens.is_empty() {
+            return Err(Error::new("No dangerous token fits into the test case"));
+        }
+        let token = self.prng.pick(&tokens).to_vec();
+        let idx = self.prng.rand_range(0, self.test_case.size + 1);
+        self.test_case.data.splice(idx..idx, token);
+        self.test_case.size = self.test_case.data.len();
+        Ok(())
+    }
+
+    /// Mutator that picks a field of the field layout that fits into the test case and mutates it
+    /// according to its kind, leaving all other bytes untouched.
+    fn mutate_field(&mut self) -> Result<()> {
+        let fields = self
+            .field_layout
+            .iter()
+            .filter(|f| f.fits(self.test_case.size))
+            .copied()
+            .collect::<Vec<_>>();
+        if fields.is_empty() {
+            return Err(Error::new("No field fits into the test case"));
+        }
+        let field = *self.prng.pick(&fields);
+        let range = field.offset..field.offset + field.len;
+        match field.kind {
+            FieldKind::Int { is_little_endian } => {
+                let bytes = &mut self.test_case.data[range];
+                let val = field::read_int(bytes, is_little_endian);
+                let val = match self.prng.rand_range(0, 4) {
+                    0 => val.wrapping_add(self.prng.rand_range(1, 36) as u64),
+                    1 => val.wrapping_sub(self.prng.rand_range(1, 36) as u64),
+                    2 => val.wrapping_neg(),
+                    3 => match field.len {
+                        1 => u64::from(self.prng.pick(MAGIC_8)),
+                        2 => u64::from(self.prng.pick(MAGIC_16)),
+                        4 => u64::from(self.prng.pick(MAGIC_32)),
+                        _ => self.prng.pick(MAGIC_64),
+                    },
+                    _ => unreachable!(),
+                };
+                field::write_int(bytes, val, is_little_endian);
+            }
+            FieldKind::Str => {
+                let n = self.eligible_user_tokens();
+                let token = if n != 0 {
+                    self.prng.pick(&self.user_token_dict[..n]).1.clone()
+                } else if !self.torc_token_dict.is_empty() {
+                    self.prng.pick(&self.torc_token_dict).clone()
+                } else {
+                    (0..field.len)
+                        .map(|_| self.prng.rand_range(32, 127) as u8)
+                        .collect()
+                };
+                let off = self.prng.rand_range(0, field.len);
+                let n = token.len().min(field.len - off);
+                self.test_case.data[range][off..off + n].copy_from_slice(&token[..n]);
+            }
+            FieldKind::Bytes => {
+                let idx = field.offset + self.prng.rand_range(0, field.len);
+                self.test_case.data[idx] ^= self.prng.rand_range(1, 256) as u8;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mutator that picks a hex or base64 encoded region in the test case, decodes it, applies a
+    /// size preserving mutation to the decoded bytes and writes the re-encoded region back.
+    /// The region keeps its length, so the surrounding data stays intact.
+    fn encoded_region(&mut self) -> Result<()> {
+        let regions = self
+            .region_encodings
+            .iter()
+            .flat_map(|enc| {
+                enc.find_regions(&self.test_case.data)
+                    .into_iter()
+                    .map(move |(start, end)| (*enc, start, end))
+            })
+            .collect::<Vec<_>>();
+        if regions.is_empty() {
+            return Err(Error::new("No encoded region found"));
+        }
+        let (enc, start, end) = *self.prng.pick(&regions);
+        let region = &self.test_case.data[start..end];
+        let uppercase = region.iter().any(u8::is_ascii_uppercase);
+        let decoded = TestCase::from_vec(enc.decode(region));
+
+        // Run the mutation on the decoded bytes in place of the actual test case
+        let orig = std::mem::replace(&mut self.test_case, decoded);
+        let res = match self.prng.rand_range(0, 4) {
+            0 => self.change_bit(),
+            1 => self.negate_byte(),
+            2 => self.arithmetic_width(),
+            3 => self.swap_neighbors(),
+            _ => unreachable!(),
+        };
+        let decoded = std::mem::replace(&mut self.test_case, orig);
+        res?;
+
+        enc.encode(
+            &decoded.data,
+            &mut self.test_case.data[start..end],
+            uppercase,
+        );
+        Ok(())
+    }
+}
+
+/// Returns the bounds of the first run of ASCII digits in `data` that contains or follows `from`.
+fn find_digit_run(data: &[u8], from: usize) -> Option<(usize, usize)> {
+    let pos = from + data.get(from..)?.iter().position(u8::is_ascii_digit)?;
+    let start = data[..pos]
+        .iter()
+        .rposition(|b| !b.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    let end = data[pos..]
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .map_or(data.len(), |i| i + pos);
+    Some((start, end))
+}
+
+/// Parses ASCII digits as a decimal number, saturating at `u64::MAX`.
+fn parse_ascii_integer(digits: &[u8]) -> u64 {
+    digits.iter().fold(0u64, |acc, &ch| {
+        acc.saturating_mul(10).saturating_add(u64::from(ch - b'0'))
+    })
+}
+
+/// Parses a line of a token dictionary into its AFL level and token. Returns `None` for empty and
+/// comment lines.
+fn parse_dict_line(line: &[u8]) -> Option<(usize, Vec<u8>)> {
+    // Only the line terminator is stripped from plain tokens, whitespace may be part of them
+    let raw = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = raw.trim_ascii();
+    if raw.is_empty() || line.first() == Some(&b'#') {
+        return None;
+    }
+    let Some(start) = line
+        .iter()
+        .position(|b| *b == b'"')
+        .filter(|&start| start + 1 < line.len() && line.ends_with(b"\""))
+    else {
+        return Some((0, raw.to_vec()));
+    };
+    let quoted = &line[start + 1..line.len() - 1];
+    // The name is followed by an optional `@level` and `=`
+    let level = line[..start]
+        .strip_suffix(b"=")
+        .and_then(|name| {
+            name.iter()
+                .rposition(|b| *b == b'@')
+                .map(|at| &name[at + 1..])
+        })
+        .and_then(|level| std::str::from_utf8(level).ok()?.parse().ok())
+        .unwrap_or(0);
+
+    let mut token = Vec::with_capacity(quoted.len());
+    let mut i = 0;
+    while i < quoted.len() {
+        let escaped = match quoted[i..] {
+            [b'\\', b'x', hi, lo, ..] => std::str::from_utf8(&[hi, lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .map(|b| (b, 4)),
+            [b'\\', b @ (b'\\' | b'"'), ..] => Some((b, 2)),
+            _ => None,
+        };
+        let (b, len) = escaped.unwrap_or((quoted[i], 1));
+        token.push(b);
+        i += len;
+    }
+    (!token.is_empty()).then_some((level, token))
+}
+
+/// Returns a random codepoint, encoded in UTF-8 with a random length of 1 to 4 bytes. Printable
+/// codepoints exclude control characters.
+fn rand_codepoint(prng: &mut Rng<Generator>, printable: bool) -> char {
+    loop {
+        let cp = match prng.rand_range(0, 4) {
+            0 if printable => prng.rand_range(0x20, 0x7f),
+            0 => prng.rand_range(0, 0x80),
+            1 => prng.rand_range(0x80, 0x800),
+            2 => prng.rand_range(0x800, 0x1_0000),
+            _ => prng.rand_range(0x1_0000, 0x11_0000),
+        };
+        // Surrogates are no valid codepoints
+        match char::from_u32(cp) {
+            Some(c) if !(printable && c.is_control()) => return c,
+            _ => {}
+        }
+    }
+}
+
+/// Returns a random index into data. If `exclude_off` is not None, the returned index will be at least
+/// `exclude_off` bytes away from the end of data.
+fn get_random_index(
+    data: &mut Vec<u8>,
+    prng: &mut Rng<Generator>,
+    exclude_off: Option<usize>,
+) -> usize {
+    assert!(!data.is_empty(), "Cannot get random index from empty data");
+    prng.rand_exp(0, data.len() - exclude_off.map_or(0, |x| x))
+}
+
+/// Writes a dictionary token over the test case at a random offset, reversing it at random. Half
+/// of the time, or whenever the token is larger than the test case, the token is inserted at the
+/// offset instead, growing the test case up to `max_size`. An empty test case becomes the token.
+fn add_from_dict(
+    token: &[u8],
+    to: &mut TestCase,
+    prng: &mut Rng<Generator>,
+    max_size: usize,
+) -> Result<()> {
+    let mut val = token.to_vec();
+    let val_size = val.len();
+    if to.data.is_empty() {
+        to.data = val;
+        to.size = val_size;
+        return Ok(());
+    }
+    if val_size > 1 && prng.bool() {
+        val.reverse();
+    };
+    if val_size > to.data.len() || prng.bool() {
+        if to.data.len() + val_size > max_size {
+            return Err(Error::new(
+                "Dictionary token exceeds the maximum test case size",
+            ));
+        }
+        let idx = prng.rand_range(0, to.data.len() + 1);
+        to.data.splice(idx..idx, val);
+        to.size = to.data.len();
+        return Ok(());
+    }
+    let idx = prng.rand_range(0, to.data.len() - val_size);
+    if val_size == 1 {
+        to.data[idx] = val[0];
+        return Ok(());
+    }
+    to.data[idx..idx + val_size].copy_from_slice(&val);
+    Ok(())
+}
+
+/// Unlike splicing this function will not modify the size of the test case. It will instead
+/// copy a random sized chunk of another test case into the current test case within its bounds.
+fn copy_part_of(from: &[u8], to: &mut TestCase, prng: &mut Rng<Generator>) -> Result<()> {
+    let mut to_idx = prng.rand_range(0, to.size);
+    let mut copy_size = std::cmp::min(prng.rand_range(1, to.size - to_idx + 1), from.len());
+    let mut from_idx = prng.rand_range(0, from.len() - copy_size + 1);
+    if copy_size == 1 {
+        let b = from[from_idx];
+        to.data[to_idx] ^= if b == 0 { b + 1 } else { b };
+        return Ok(());
+    }
+    if from_idx == to_idx {
+        if from_idx > 0 {
+            from_idx -= 1;
+        } else if to_idx > 0 {
+            to_idx -= 1;
+        } else if from_idx + 1 + copy_size < from.len() {
+            from_idx += 1;
+        } else {
+            from_idx += 1;
+            copy_size -= 1;
+        }
+    }
+    let copy_slice = &from[from_idx..from_idx + copy_size];
+    to.data[to_idx..to_idx + copy_size].clone_from_slice(copy_slice);
+    Ok(())
+}
+
+/// This function modifies the test case by inserting a portion from another corpus entry
+/// at a random position, without overwriting any data.
+fn insert_part_of(
+    from: &[u8],
+    to: &mut TestCase,
+    prng: &mut Rng<Generator>,
+    max_size: usize,
+) -> Result<()> {
+    let available_space = max_size - to.size;
+    let max_copy_size = std::cmp::min(available_space, from.len());
+    assert!(max_copy_size > 0, "Insertion size is 0");
+
+    let copy_size = prng.rand_range(1, max_copy_size + 1);
+    let from_idx = prng.rand_range(0, from.len() - copy_size + 1);
+    let to_idx = if to.data.is_empty() {
+        0
+    } else {
+        prng.rand_range(0, to.size)
+    };
+
+    /*
+    to.data.resize(to.size + copy_size, 0);
+
+    // Rotate the slice to the right
+    to.data[to_idx..].rotate_right(copy_size);
+
+    // Copy the data from the other corpus entry into the open spot
+    to.data[to_idx..to_idx + copy_size].copy_from_slice(&from[from_idx..from_idx + copy_size]);
+
+    to.size = to.data.len();
+    Ok(())
+    */
+
+    // Allocate new memory for the data.
+    // This seems to be faster than relying on the `resize` and `rotate_right` functions
+    // that are implemented on `Vec`. Experiments show a 6% speedup.
+    let new_size = to.size + copy_size;
+    let mut new_data: Vec<u8> = vec![0u8; new_size];
+    unsafe {
+        new_data.set_len(new_size);
+    }
+
+    // Copy the data before the insertion point
+    new_data[..to_idx].copy_from_slice(&to.data[..to_idx]);
+
+    // Copy the data from `from`
+    new_data[to_idx..to_idx + copy_size].copy_from_slice(&from[from_idx..from_idx + copy_size]);
+
+    // Copy the data after the insertion point
+    new_data[to_idx + copy_size..].copy_from_slice(&to.data[to_idx..]);
+
+    to.data = new_data;
+    to.size = new_size;
+    Ok(())
+}
+
+fn arithmetic<T>(data: &mut Vec<u8>, data_size: usize, prng: &mut Rng<Generator>) -> Result<()>
+where
+    T: num_traits::PrimInt
+        + num_traits::Unsigned
+        + WrappingAdd
+        + WrappingSub
+        + WrappingNeg
+        + WrappingMul
+        + WrappingShl
+        + WrappingShr
+        + std::ops::BitOrAssign
+        + num::cast::AsPrimitive<u8>
+        + std::convert::From<u8>,
+    u8: AsPrimitive<T>,
+{
+    let bytes = std::mem::size_of::<T>();
+    if data_size < bytes {
+        return Err(Error::new("Mutation size > test case"));
+    }
+    let idx = get_random_index(data, prng, Some(bytes));
+    let mut val: T = 0.into();
+    for i in 0..bytes {
+        val |= <u8 as AsPrimitive<T>>::as_(data[idx + i]) << (8 * (bytes - i - 1));
+    }
+    let op = prng.rand_range(0, 6);
+    val = match op {
+        0 => val.wrapping_sub(&1.into()),
+        1 => val.wrapping_add(&1.into()),
+        2 => val.wrapping_mul(&2.into()),
+        3 => val.wrapping_neg(),
+        4 => val.wrapping_shl(2),
+        5 => val.wrapping_shr(2),
+        _ => unreachable!(),
+    };
+    for i in 0..bytes {
+        let a = 8 * (bytes - i - 1);
+        let b = val >> a;
+        data[idx + i] = b.as_();
+    }
+    Ok(())
+}
+
+fn swap_neighbors_width<T>(
+    data: &mut Vec<u8>,
+    data_size: usize,
+    prng: &mut Rng<Generator>,
+) -> Result<()>
+where
+    T: num_traits::PrimInt + num_traits::Unsigned,
+{
+    let bytes = std::mem::size_of::<T>();
+    if data_size <= bytes {
+        return Err(Error::new("Mutation size > test case"));
+    }
+    let idx = get_random_index(data, prng, Some(data_size - 1 - bytes));
+    // Check if we can swap with the right neighbor
+    if idx + 2 * bytes < data_size {
+        for i in 0..bytes {
+            unsafe { ptr::swap(&mut data[idx + i], &mut data[idx + bytes + i]) }
+        }
+    } else if (idx as i64 - bytes as i64) >= 0 && idx + bytes < data_size {
+        // Otherwise swap with the left neighbors
+        for i in 0..bytes {
+            unsafe {
+                ptr::swap(&mut data[idx - bytes + i], &mut data[idx + i]);
+            }
+        }
+    } else {
+        // If swapping with the left or the right is not possible,
+        // calculate the maximum number of bytes that can be swapped
+        let max_bytes = (data_size - idx).min(idx).min(bytes);
+        if max_bytes < 2 {
+            // Otherwise just negate the byte
+            data[max_bytes] = !data[max_bytes];
+            return Ok(());
+        }
+        let half_bytes = max_bytes / 2;
+        if (idx as i64 - half_bytes as i64) >= 0 {
+            // swap with the left
+            for i in 0..half_bytes {
+                unsafe {
+                    ptr::swap(&mut data[idx - i], &mut data[idx + half_bytes - i]);
+                }
+            }
+        } else {
+            // swap with the right
+            for i in 0..half_bytes {
+                unsafe {
+                    ptr::swap(&mut data[idx + i], &mut data[data_size - i - 1]);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A function that calls a function pointer
+fn fun_caller(
+    func: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()>,
+    data: &mut Vec<u8>,
+    data_size: usize,
+    prng: &mut Rng<Generator>,
+) -> Result<()> {
+    func(data, data_size, prng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammar_mutator::DataFormat;
+
+    enum TestCondition {
+        DataInequality,
+        SizeInequality,
+        GeneralErrorChecker,
+    }
+
+    fn corpus() -> Arc<Vec<Vec<u8>>> {
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec![
+            "ThisIsSomeTest".as_bytes().to_vec(),
+            "YetAnotherSimpleInput".as_bytes().to_vec(),
+            [0xff].to_vec(),
+        ]);
+        corpus
+    }
+
+    fn engine(corp: &Arc<Vec<Vec<u8>>>) -> MutationEngine {
+        let mut me = MutationEngine::new()
+            .set_corpus(corp.clone())
+            .set_generator(&Generators::Romuduojr)
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .set_token_dict("dicts/test.dict");
+        for _ in 0..128 {
+            let tc_size = me.prng.rand_range(1, 4096);
+            let tc = me.prng.rand_byte_vec(tc_size);
+            me.add_to_corpus(&tc);
+        }
+        me
+    }
+
+    fn run<F>(fun: F, tcond: TestCondition)
+    where
+        F: Fn(&mut MutationEngine) -> Result<()>,
+    {
+        let corpus = corpus();
+        let mut engine = engine(&corpus);
+
+        for _ in 0..100_000 {
+            engine = engine.set_random_test_case();
+            let tc_orig = engine.test_case.data.clone();
+            if fun(&mut engine).is_ok() {
+                let tc = engine.test_case.data.clone();
+
+                match tcond {
+                    TestCondition::DataInequality => {
+                        assert_ne!(tc_orig, tc);
+                    }
+                    TestCondition::SizeInequality => {
+                        assert_ne!(tc_orig.len(), tc.len());
+                    }
+                    TestCondition::GeneralErrorChecker => {}
+                };
+            }
+        }
+    }
+
+    /// Replayable property check: every iteration reseeds the PRNG with a seed derived from the
+    /// iteration number, so a failing case can be reproduced from the seed in the panic message.
+    fn check<F, P>(fun: F, prop: P)
+    where
+        F: Fn(&mut MutationEngine) -> Result<()>,
+        P: Fn(&[u8], &[u8]) -> bool,
+    {
+        let corpus = corpus();
+        let mut engine = engine(&corpus);
+
+        for i in 0..2_000 {
+            let seed = 0xdeadbeefcafebabe ^ i;
+            engine.prng.set_seed(seed);
+            engine = engine.set_random_test_case();
+            let tc_orig = engine.test_case.data.clone();
+            if fun(&mut engine).is_ok() {
+                let tc = &engine.test_case;
+                assert_eq!(tc.size, tc.data.len(), "size drifted (seed: {seed:#x})");
+                assert!(
+                    prop(&tc_orig, &tc.data),
+                    "property violated (seed: {seed:#x})"
+                );
+            }
+        }
+    }
+
+    fn same_len(orig: &[u8], new: &[u8]) -> bool {
+        orig.len() == new.len()
+    }
 
-            for _ in 0..max_factor {
-                let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-                self.test_case.data.remove(idx);
-                self.test_case.size -= 1;
-            }
+    fn same_bytes(orig: &[u8], new: &[u8]) -> bool {
+        let mut a = orig.to_vec();
+        let mut b = new.to_vec();
+        a.sort_unstable();
+        b.sort_unstable();
+        a == b
+    }
+
+    fn changed_bytes(orig: &[u8], new: &[u8]) -> usize {
+        orig.iter().zip(new).filter(|(a, b)| a != b).count()
+    }
+
+    fn is_subsequence(needle: &[u8], haystack: &[u8]) -> bool {
+        let mut it = haystack.iter();
+        needle.iter().all(|n| it.any(|h| h == n))
+    }
+
+    #[test]
+    fn test_shuffle_bytes() {
+        // We check against `TestCondition::GeneralErrorChecker` as a low shuffle amount, with
+        // a possible small slice to shuffle may yield the same result.
+        // We don't care about the `sub_slice` being potentially the same.
+        // We favor executions/second over ensuring the mutated version is != the original.
+        run(
+            MutationEngine::shuffle_bytes,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_erase_bytes() {
+        run(MutationEngine::erase_bytes, TestCondition::SizeInequality);
+    }
+
+    #[test]
+    fn test_insert_bytes() {
+        run(MutationEngine::insert_bytes, TestCondition::SizeInequality);
+    }
+
+    #[test]
+    fn test_swap_endianness() {
+        // We check against `TestCondition::GeneralErrorChecker` as a low swap amount, with
+        // a possible small slice to swap may yield the same result, for example in such a
+        // scenario:
+        //
+        // `Swapping 2 bytes - [249, 249]`
+        run(
+            MutationEngine::swap_endianness,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_change_bit() {
+        run(MutationEngine::change_bit, TestCondition::DataInequality);
+    }
+
+    #[test]
+    fn test_change_byte() {
+        run(MutationEngine::change_byte, TestCondition::DataInequality);
+    }
+
+    #[test]
+    // Same argumentation as for `swap_endianness`.
+    // The newly generated ASCII character can still be the same as before.
+    fn test_change_ascii_integer() {
+        run(
+            MutationEngine::change_ascii_integer,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_change_ascii_integer_in_place() {
+        assert_eq!(find_digit_run(b"len=4999;", 0), Some((4, 8)));
+        assert_eq!(find_digit_run(b"len=4999;", 6), Some((4, 8)));
+        assert_eq!(find_digit_run(b"len=4999;", 8), None);
+        assert_eq!(find_digit_run(b"12", 0), Some((0, 2)));
+        assert_eq!(parse_ascii_integer(b"4999"), 4999);
+        assert_eq!(parse_ascii_integer(&[b'9'; 32]), u64::MAX);
+
+        let mut engine = MutationEngine::new().set_generator_seed(0xdeadbeefcafebabe);
+        let mut seen = BTreeSet::new();
+        for _ in 0..1000 {
+            engine.set_test_case(b"len=4999;");
+            engine.change_ascii_integer().unwrap();
+            let data = &engine.test_case.data;
+            assert_eq!(engine.test_case.size, data.len());
+            assert!(data.starts_with(b"len=") && data.ends_with(b";"));
+            let val: u64 = std::str::from_utf8(&data[4..data.len() - 1])
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!([5000, 4998, 2499, 9998].contains(&val) || val < 4999 * 4999);
+            seen.insert(val);
         }
+        assert!(seen.is_superset(&[5000, 4998, 2499, 9998].into()));
 
-        Ok(())
+        // The representation grows and shrinks with the value
+        engine.set_test_case(b"9");
+        while engine.test_case.data.len() == 1 {
+            engine.change_ascii_integer().unwrap();
+        }
+        assert!(engine.test_case.data.len() > 1);
     }
 
-    /// Mutator that inserts a random amount ([1; min(100, `test_case.size` * 0.1)]) of bytes into the test case
-    fn insert_bytes(&mut self) -> Result<()> {
-        let to_insert = self.ensure_printable();
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-        // 50% chance to only insert one byte
-        if self.prng.bool() {
-            let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-            self.test_case.data.insert(idx, to_insert);
-            self.test_case.size += 1;
-            return Ok(());
+    #[test]
+    // Same argumentation as for `swap_endianness`.
+    // The done type casting can produce a value which the original data slice
+    // at the rolled index already holds.
+    fn test_change_binary_integer() {
+        run(
+            MutationEngine::change_binary_integer,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_negate_byte() {
+        run(MutationEngine::negate_byte, TestCondition::DataInequality);
+    }
+
+    #[test]
+    fn test_swap_neighbors() {
+        // Same argumentation as for `swap_endianness`.
+        run(
+            MutationEngine::swap_neighbors,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        // Same argumentation as for `swap_endianness`.
+        run(
+            MutationEngine::arithmetic_width,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        run(MutationEngine::truncate, TestCondition::SizeInequality);
+    }
+
+    #[test]
+    fn test_truncate_modes() {
+        let data = (0..100u8).collect::<Vec<_>>();
+        let mut me = MutationEngine::new();
+        for mode in [TruncateMode::Tail, TruncateMode::Head, TruncateMode::Middle] {
+            me = me.set_truncate_mode(mode);
+            for _ in 0..100 {
+                me.set_test_case(&data);
+                me.truncate().unwrap();
+                let tc = &me.test_case.data;
+                assert_eq!(tc.len(), me.test_case.size);
+                assert!(tc.len() < data.len() && tc.len() >= data.len() / 2);
+                let removed = data.len() - tc.len();
+                match mode {
+                    TruncateMode::Tail => assert!(data.starts_with(tc)),
+                    TruncateMode::Head => assert!(data.ends_with(tc)),
+                    TruncateMode::Middle => {
+                        // A single slice is missing, somewhere between the first and last byte
+                        let start = (0..tc.len()).find(|&i| tc[i] != data[i]).unwrap();
+                        assert!(start > 0);
+                        assert_eq!(tc[start..], data[start + removed..]);
+                        assert_eq!(tc.last(), data.last());
+                    }
+                }
+            }
         }
-        let max_factor = if self.test_case.size < 8 {
-            8
-        } else if self.test_case.size < 64 {
-            self.prng.rand_range(8, self.test_case.size)
-        } else {
-            self.prng.rand_range(
-                0,
-                std::cmp::min(
-                    self.max_test_case_size - self.test_case.size,
-                    self.test_case.size / self.max_mutation_factor,
-                ),
-            ) + 1
-        };
-        self.test_case
-            .data
-            .splice(idx..idx, std::iter::repeat(to_insert).take(max_factor));
-        self.test_case.size += max_factor;
-        Ok(())
+
+        me.set_test_case(b"AB");
+        assert!(me.truncate().is_err());
     }
 
-    /// Swaps two (q|d|w) word, or byte neighbors in the test case
-    fn swap_neighbors(&mut self) -> Result<()> {
-        let fun: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()> =
-            match self.prng.rand_range(0, 4) {
-                0 => swap_neighbors_width::<u8>,
-                1 => swap_neighbors_width::<u16>,
-                2 => swap_neighbors_width::<u32>,
-                3 => swap_neighbors_width::<u64>,
-                _ => unreachable!(),
-            };
+    #[test]
+    fn test_append() {
+        run(MutationEngine::append, TestCondition::SizeInequality);
+    }
 
-        fun_caller(
-            fun,
-            &mut self.test_case.data,
-            self.test_case.size,
-            &mut self.prng,
-        )
+    #[test]
+    fn test_append_small_inputs() {
+        let mut me = MutationEngine::new()
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .set_max_test_case_size(16)
+            .set_append_size_range(2, 8);
+        me.set_test_case(&[]);
+        assert!(me.append().is_err());
+        for data in [&b"A"[..], b"AB", b"ABCDEFGHIJKLMN"] {
+            for _ in 0..1000 {
+                me.set_test_case(data);
+                me.append().unwrap();
+                let tc = &me.test_case.data;
+                let grown = tc.len() - data.len();
+                assert!((2..=8).contains(&grown));
+                assert!(tc.len() <= 16);
+                assert_eq!(me.test_case.size, tc.len());
+                assert!(tc.starts_with(data));
+                assert!(tc[data.len()..].iter().all(|b| data.contains(b)));
+            }
+        }
+        me.set_test_case(b"ABCDEFGHIJKLMNO");
+        assert!(me.append().is_err());
     }
 
-    /// Swaps the endianess of a random amount ([2,4, 8]) of bytes in the test case
-    fn swap_endianness(&mut self) -> Result<()> {
-        let mut width = self.prng.pick([2, 4, 8]) as usize;
-        if self.test_case.size < width {
-            return Err(Error::new("Mutation size > test case"));
+    #[test]
+    fn test_add_from_dict() {
+        // Same argumentation as for `swap_endianness`.
+        run(
+            MutationEngine::add_word_from_dict,
+            TestCondition::DataInequality,
+        );
+    }
+
+    #[test]
+    fn test_add_from_dict_insert() {
+        let token = b"0123456789abcdef";
+        let mut me = MutationEngine::new();
+        me.user_token_dict = vec![(0, token.to_vec())];
+        for _ in 0..50 {
+            me.set_test_case(b"ABCD");
+            me.add_word_from_dict().unwrap();
+            let tc = &me.test_case.data;
+            assert_eq!(me.test_case.size, 20);
+            assert_eq!(tc.len(), 20);
+            let mut reversed = token.to_vec();
+            reversed.reverse();
+            assert!(tc
+                .windows(token.len())
+                .any(|w| w == token || w == reversed.as_slice()));
         }
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, Some(1));
-        width = usize::min(width, self.test_case.size - idx);
-        let slice = &mut self.test_case.data[idx..idx + width];
-        let ptr = slice.as_mut_ptr();
-        for i in 0..(width >> 1) {
-            unsafe {
-                std::ptr::swap(ptr.add(i), ptr.add(width - i - 1));
+
+        me.set_test_case(b"");
+        me.add_word_from_dict().unwrap();
+        assert_eq!(me.test_case.data, token);
+        assert_eq!(me.test_case.size, token.len());
+
+        // Inserting and overwriting are both taken for tokens that fit
+        let (mut grown, mut kept) = (0, 0);
+        for _ in 0..100 {
+            me.set_test_case(&[0x41; 32]);
+            me.add_word_from_dict().unwrap();
+            match me.test_case.size {
+                48 => grown += 1,
+                32 => kept += 1,
+                _ => unreachable!(),
             }
         }
-        Ok(())
+        assert!(grown > 0 && kept > 0);
     }
 
-    /// Mutator that changes a random bit in the test case
-    fn change_bit(&mut self) -> Result<()> {
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-        let bit = self.prng.rand_range(0, 8);
-        self.test_case.data[idx] ^= 1 << bit;
-        Ok(())
+    #[test]
+    fn test_add_from_magic() {
+        // Same argumentation as for `swap_endianness`.
+        run(
+            MutationEngine::insert_constant,
+            TestCondition::GeneralErrorChecker,
+        );
     }
 
-    /// Mutator that changes a random byte in the test case by either replacing it with a random byte or
-    /// XOR'ing it with a random byte
-    fn change_byte(&mut self) -> Result<()> {
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-        let byte = &mut self.test_case.data[idx];
-        let r = self.prng.rand_byte();
-        if self.prng.bool() {
-            if r == *byte {
-                *byte = r + 1;
-            } else {
-                *byte = r;
+    #[test]
+    fn test_add_magic_values() {
+        let data = [0x41u8; 32];
+        let mut me = MutationEngine::new();
+        me.add_magic_values(vec![0xCAFE_BABE]);
+        let needle = 0xCAFE_BABEu32.to_le_bytes();
+        let mut found = 0;
+        for _ in 0..500 {
+            me.set_test_case(&data);
+            me.insert_constant().unwrap();
+            if me.test_case.data.windows(4).any(|w| w == needle) {
+                found += 1;
             }
-        } else if r == 0 {
-            *byte ^= r + 1;
-        } else {
-            *byte ^= r;
         }
-        Ok(())
+        assert!(found > 0);
     }
 
-    /// Mutator that negates a random byte in the test case
-    fn negate_byte(&mut self) -> Result<()> {
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, None);
-        let byte = &mut self.test_case.data[idx];
-        *byte = !*byte;
-        Ok(())
+    #[test]
+    fn test_load_magic_from_file() {
+        let path = std::env::temp_dir().join(format!("hantu_magic_{}.txt", std::process::id()));
+        std::fs::write(&path, "# magic values\n0xCAFEBABE\n\ndeadbeef\n").unwrap();
+        let mut me = MutationEngine::new();
+        me.load_magic_from_file(&path).unwrap();
+        assert_eq!(me.extra_magic, [0xCAFE_BABE, 0xDEAD_BEEF]);
+
+        std::fs::write(&path, "0xnope\n").unwrap();
+        assert!(me.load_magic_from_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_add_from_float_magic() {
+        let data = [0x41u8; 24];
+        let mut me = MutationEngine::new();
+        for _ in 0..100 {
+            me.set_test_case(&data);
+            me.insert_float_constant().unwrap();
+            let out = &me.test_case.data;
+            assert_eq!(out.len(), data.len());
+            // Only a single aligned window changed, holding a magic float in either endianness
+            let decodes = |width: usize| {
+                (0..out.len() / width).any(|w| {
+                    let (start, end) = (w * width, (w + 1) * width);
+                    if out[..start] != data[..start] || out[end..] != data[end..] {
+                        return false;
+                    }
+                    let window = &out[start..end];
+                    if width == 4 {
+                        let le = u32::from_le_bytes(window.try_into().unwrap());
+                        let be = u32::from_be_bytes(window.try_into().unwrap());
+                        MAGIC_F32.iter().any(|f| [le, be].contains(&f.to_bits()))
+                    } else {
+                        let le = u64::from_le_bytes(window.try_into().unwrap());
+                        let be = u64::from_be_bytes(window.try_into().unwrap());
+                        MAGIC_F64.iter().any(|f| [le, be].contains(&f.to_bits()))
+                    }
+                })
+            };
+            assert!(decodes(4) || decodes(8), "{out:02x?}");
+        }
+        me.set_test_case(&[0; 3]);
+        assert!(me.insert_float_constant().is_err());
     }
 
-    /// Mutator that treats [1,2,4,8] bytes in the test case as an integer and performs an arithmetic operation on it
-    fn arithmetic_width(&mut self) -> Result<()> {
-        let fun: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()> =
-            match self.prng.rand_range(0, 4) {
-                0 => arithmetic::<u8>,
-                1 => arithmetic::<u16>,
-                2 => arithmetic::<u32>,
-                3 => arithmetic::<u64>,
-                _ => unreachable!(),
-            };
-        fun_caller(
-            fun,
-            &mut self.test_case.data,
-            self.test_case.size,
-            &mut self.prng,
-        )
+    #[test]
+    fn test_copy_part() {
+        run(MutationEngine::copy_part, TestCondition::DataInequality);
     }
 
-    /// Mutator that changes a random byte in the test case that is within ASCII range
-    fn change_ascii_integer(&mut self) -> Result<()> {
-        let skip_past = self.prng.rand_range(0, self.test_case.size);
+    #[test]
+    fn test_cross_over() {
+        // Seems to sometimes due to bad PRNG luck produce an identical test_case.
+        // May need to revist later, for now speed > slowing down constraints.
+        run(
+            MutationEngine::cross_over,
+            TestCondition::GeneralErrorChecker,
+        );
+    }
 
-        let data = &self.test_case.data[skip_past..];
-        let ascii_digits = data
-            .windows(data.len())
-            .position(|window| window.iter().all(u8::is_ascii_digit))
-            .map(|i| (i + skip_past, i + data.len() + skip_past));
-        let (start, end) = ascii_digits.unwrap_or((self.test_case.size, self.test_case.size));
+    #[test]
+    fn test_cross_over_strategies() {
+        let parent = [0x41u8; 32];
+        let other = vec![0x42u8; 48];
+        let mut me = MutationEngine::new().set_corpus(Arc::new(vec![other]));
+        let both_parents = |data: &[u8]| data.contains(&0x41) && data.contains(&0x42);
 
-        // No range of ASCII digits found. Simply flip the first byte and return;
-        if start == self.test_case.size && end == self.test_case.size {
-            self.test_case.data[0] = !self.test_case.data[0];
-            return Ok(());
+        me = me.set_cross_over_strategy(CrossOverStrategy::SinglePoint);
+        for _ in 0..100 {
+            me.set_test_case(&parent);
+            me.cross_over().unwrap();
+            let data = &me.test_case.data;
+            assert_eq!((data.len(), me.test_case.size), (48, 48));
+            assert!(both_parents(data));
+            // The head of the test case is followed by the tail of the corpus entry
+            let cut = data.iter().position(|&b| b == 0x42).unwrap();
+            assert!(data[cut..].iter().all(|&b| b == 0x42));
         }
-        let mut val: u8 = self.test_case.data[start..end]
-            .iter()
-            .enumerate()
-            .fold(0u8, |acc, (i, &ch)| {
-                acc + (ch - b'0') * (10_u8.wrapping_mul(i as u8))
-            });
-        match self.prng.rand_range(0, 5) {
-            0 => val = val.wrapping_add(1),
-            1 => val = val.wrapping_sub(1),
-            2 => val /= 2,
-            3 => val = val.wrapping_mul(2),
-            4 => val = (self.prng.rand_range(0, val as usize * val as usize)) as u8,
-            _ => unreachable!(),
+
+        me = me.set_cross_over_strategy(CrossOverStrategy::TwoPoint);
+        for _ in 0..100 {
+            me.set_test_case(&parent);
+            me.cross_over().unwrap();
+            let data = &me.test_case.data;
+            assert_eq!((data.len(), me.test_case.size), (32, 32));
+            assert!(both_parents(data));
+            // A single region of the corpus entry ends before the end of the test case
+            let start = data.iter().position(|&b| b == 0x42).unwrap();
+            let end = data.iter().rposition(|&b| b == 0x42).unwrap();
+            assert!(data[start..=end].iter().all(|&b| b == 0x42));
+            assert!(end < 31);
         }
-        if val > 9 {
-            val = 9;
+
+        me = me.set_cross_over_strategy(CrossOverStrategy::RandomChunks);
+        let mut mixed = 0;
+        for _ in 0..100 {
+            me.set_test_case(&parent);
+            me.cross_over().unwrap();
+            let data = &me.test_case.data;
+            assert_eq!(data.len(), me.test_case.size);
+            assert!(data.len() < me.max_test_case_size);
+            mixed += usize::from(both_parents(data));
         }
-        self.test_case.data[start..end]
-            .iter_mut()
-            .enumerate()
-            .rev()
-            .for_each(|(_, ch)| *ch = val + b'0');
+        assert!(mixed > 0);
 
-        Ok(())
+        me = me.set_cross_over_strategy(CrossOverStrategy::SinglePoint);
+        me.set_test_case(b"A");
+        assert!(me.cross_over().is_err());
     }
 
-    /// Changes a random byte in the test case that is not within ASCII range
-    fn change_binary_integer(&mut self) -> Result<()> {
-        let mut val: usize;
-        let bin_size: usize = *self.prng.pick(&[1, 2, 4, 8]) as usize;
-        if self.test_case.size < bin_size {
-            return Err(Error::new("Mutation size > test case"));
-        }
-        let off = self.prng.rand_range(0, self.test_case.size - bin_size + 1);
-        let add = (self.prng.rand_range(0, 21) as isize - 10).max(0) as usize;
-        val =
-            if off < 64 && self.prng.bool_chance(4) {
-                self.test_case.size
-            } else {
-                match bin_size {
-                    1 => u8::from_be_bytes(
-                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
-                    ) as usize,
-                    2 => u16::from_be_bytes(
-                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
-                    ) as usize,
-                    4 => u32::from_be_bytes(
-                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
-                    ) as usize,
-                    8 => u64::from_be_bytes(
-                        self.test_case.data[off..off + bin_size].try_into().unwrap(),
-                    ) as usize,
-                    _ => unreachable!(),
-                }
-            };
-        if self.prng.bool() {
-            val = match bin_size {
-                1 => u8::swap_bytes(val as u8).wrapping_add(add as u8) as usize,
-                2 => u16::swap_bytes(val as u16).wrapping_add(add as u16) as usize,
-                4 => u32::swap_bytes(val as u32).wrapping_add(add as u32) as usize,
-                8 => u64::swap_bytes(val as u64).wrapping_add(add as u64) as usize,
-                _ => unreachable!(),
-            };
-        } else {
-            val = val.wrapping_add(add);
-        };
+    #[test]
+    fn test_splice() {
+        // On bad rolls when two small test cases are selected, the splice may not
+        // actually change the test case.
+        run(MutationEngine::splice, TestCondition::GeneralErrorChecker);
+    }
 
-        if add == 0 || self.prng.bool() {
-            if add == val {
-                val = self.prng.rand_byte() as usize;
-            }
-            val = val.wrapping_neg();
+    #[test]
+    fn test_splice_on_boundary() {
+        let data = b"alpha\nbeta\ngamma\n".to_vec();
+        let corpus = Arc::new(vec![b"one\ntwo\nthree\nfour".to_vec()]);
+        let lines = ["alpha", "beta", "gamma", "one", "two", "three", "four", ""];
+        let mut me = MutationEngine::new().set_corpus(corpus);
+        for _ in 0..100 {
+            me.set_test_case(&data);
+            me.splice_on_boundary().unwrap();
+            let out = String::from_utf8(me.test_case.data.clone()).unwrap();
+            assert_eq!(me.test_case.size, out.len());
+            // Both cuts happen right after a newline, so no line is ever split
+            assert!(out.split('\n').all(|l| lines.contains(&l)), "{out:?}");
         }
-        match bin_size {
-            1 => {
-                self.test_case.data[off..off + bin_size]
-                    .copy_from_slice(&((val & 0xFF) as u8).to_be_bytes());
-            }
-            2 => {
-                self.test_case.data[off..off + bin_size]
-                    .copy_from_slice(&((val & 0xFFFF) as u16).to_be_bytes());
-            }
-            4 => {
-                self.test_case.data[off..off + bin_size]
-                    .copy_from_slice(&((val & 0xFFFF_FFFF) as u32).to_be_bytes());
-            }
-            8 => {
-                self.test_case.data[off..off + bin_size]
-                    .copy_from_slice(&(val as u64).to_be_bytes());
-            }
-            _ => unreachable!(),
-        };
-        Ok(())
+
+        me.set_test_case(b"no delimiter here");
+        assert!(me.splice_on_boundary().is_err());
     }
 
-    /// Mutator that either copies a random part of another test case to a random location of the current
-    /// test case overwriting existing data, or inserts a random part of another test case into the
-    /// current test case at a random location without overwriting existing data.
-    fn copy_part(&mut self) -> Result<()> {
-        let rand_test_case = self.get_random_corpus_entry();
-        assert!(!rand_test_case.is_empty(), "Copy part candidate is empty");
-        if self.prng.bool() {
-            copy_part_of(&rand_test_case, &mut self.test_case, &mut self.prng)
-        } else {
-            let max_size = self.test_case.size + self.prng.rand_range(1, self.max_test_case_size);
-            insert_part_of(
-                &rand_test_case,
-                &mut self.test_case,
-                &mut self.prng,
-                max_size,
-            )
+    #[test]
+    fn test_utf8_codepoint_mutate() {
+        let data = "héllo wörld € 𝄞".as_bytes();
+        let mut me = MutationEngine::new();
+        for _ in 0..200 {
+            me.set_test_case(data);
+            me.utf8_codepoint_mutate().unwrap();
+            let out = std::str::from_utf8(&me.test_case.data).unwrap();
+            assert_eq!(me.test_case.size, out.len());
+            assert_ne!(out.as_bytes(), data);
         }
-    }
 
-    /// Mutator that combines two random test cases using a cross over operation.
-    fn cross_over(&mut self) -> Result<()> {
-        let mut data2 = self.get_random_corpus_entry();
-        let size2 = data2.len();
-        assert!(size2 > 0, "Cross over candidate is empty");
+        // Invalid sequences are dropped
+        me.set_test_case(b"ab\xffcd");
+        me.utf8_codepoint_mutate().unwrap();
+        assert!(std::str::from_utf8(&me.test_case.data).is_ok());
 
-        let data1 = &mut self.test_case.data;
-        let size1 = self.test_case.size;
-        let max_out_size = self.prng.rand_range(2, self.max_test_case_size);
-        let mut out = vec![0u8; max_out_size];
-        let mut out_pos = 0;
-        let mut pos1 = 0;
-        let mut pos2 = 0;
-        let mut currently_using_first_data = true;
-        while out_pos < max_out_size && (pos1 < size1 || pos2 < size2) {
-            let out_size_left = max_out_size - out_pos;
-            let (in_pos, in_size, data) = if currently_using_first_data {
-                (&mut pos1, size1, data1.as_mut_slice())
-            } else {
-                (&mut pos2, size2, &mut *data2)
-            };
-            if *in_pos < in_size {
-                let in_size_left = in_size - *in_pos;
-                let max_extra_size = std::cmp::min(out_size_left, in_size_left);
-                let extra_size = self.prng.rand() % (max_extra_size + 1);
-                if *in_pos + extra_size <= data.len() && out_pos < max_out_size {
-                    out[out_pos..(out_pos + extra_size)]
-                        .copy_from_slice(&data[*in_pos..*in_pos + extra_size]);
-                    out_pos += extra_size;
-                    *in_pos += extra_size;
-                }
-            }
-            currently_using_first_data = !currently_using_first_data;
+        let mut me = me.set_printable(true);
+        for _ in 0..200 {
+            me.set_test_case(data);
+            me.utf8_codepoint_mutate().unwrap();
+            let out = std::str::from_utf8(&me.test_case.data).unwrap();
+            assert!(!out.chars().any(char::is_control), "{out:?}");
         }
-        self.test_case.size = max_out_size;
-        self.test_case.data = out;
-        Ok(())
-    }
 
-    /// Mutator that splices a random part of another test case into the current test case at
-    /// a random location.
-    fn splice(&mut self) -> Result<()> {
-        assert!(self.corpus.len() > 0, "corpus is empty");
-        // `Clone` is not implemented for `Arc` so we get our reference to a test case by index.
-        let splice_tc = self.prng.pick(self.corpus.as_slice());
-        let splice_idx = self.prng.rand_range(0, splice_tc.len());
-        let split_idx = self.prng.rand_range(0, self.test_case.size);
-        let mut new_data = vec![0u8; split_idx + splice_tc.len() - splice_idx];
-        new_data.extend_from_slice(&self.test_case.data[..split_idx]);
-        new_data.extend_from_slice(&splice_tc[splice_idx..]);
-        self.test_case.size = new_data.len();
-        self.test_case.data = new_data;
-        Ok(())
+        me.set_test_case(b"\xff\xfe");
+        assert!(me.utf8_codepoint_mutate().is_err());
     }
 
-    /// Mutator that removes a randomly sized chunk of the current test case.
-    fn truncate(&mut self) -> Result<()> {
-        let trunc_fac = (self.prng.rand_range(0, 50) + 1) as f64 * 0.01;
-        self.test_case.size = (self.test_case.size as f64 * (1.0 - trunc_fac)) as usize;
-        self.test_case.data.truncate(self.test_case.size);
-        Ok(())
-    }
+    #[test]
+    fn test_ni() {
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec!["
+<!DOCTYPE html>
+  <html>
+    <body>
+      <h1>My 1337 Heading</h1>
+      <p>My first paragraph.</p>
+    </body>
+  </html>"
+            .as_bytes()
+            .to_vec()]);
 
-    /// Mutator that appends a random sized chunk of the current test case to itself.
-    fn append(&mut self) -> Result<()> {
-        // We favor smaller appends to avoid blowing up the test case size too much.
-        let (from, to) = self.prng.rand_two_range(self.test_case.size, 128);
-        self.test_case.data.extend_from_within(from..to);
-        self.test_case.size += to - from;
-        Ok(())
+        let mut me = MutationEngine::new().set_corpus(corpus.clone());
+        me = me.set_random_test_case();
+        let _ = me.ni();
+        assert_ne!(corpus[0], me.test_case.data);
     }
 
-    /// Mutator that inserts a constant value from the magic set into the current test case.
-    fn insert_constant(&mut self) -> Result<()> {
-        // Roll a 4 sided dice to decide which val to read from
-        let dice_roll = self.prng.rand_range(0, 4);
-        let val: usize;
-        let val_size: usize;
-        match dice_roll {
-            0 => {
-                val = self.prng.pick(MAGIC_8) as usize;
-                val_size = std::mem::size_of::<u8>();
-            }
-            1 => {
-                val = self.prng.pick(MAGIC_16) as usize;
-                val_size = std::mem::size_of::<u16>();
-            }
-            2 => {
-                val = self.prng.pick(MAGIC_32) as usize;
-                val_size = std::mem::size_of::<u32>();
-            }
-            3 => {
-                val = self.prng.pick(MAGIC_64) as usize;
-                val_size = std::mem::size_of::<u64>();
+    #[test]
+    fn test_grammar_splice() {
+        let original = b"AAAAAAAAAAAAAAAA".to_vec();
+        let mut me =
+            MutationEngine::new().enable_custom_mutators(vec![CustomMutators::GrammarSplice(
+                GrammarTemplate::DataFormat(DataFormat::Json),
+            )]);
+        for _ in 0..100 {
+            me.set_test_case(&original);
+            me.grammar_splice().unwrap();
+            let data = &me.test_case.data;
+            assert_eq!(me.test_case.size, data.len());
+            // The original bytes surround the spliced fragment
+            let inserted = data.len() - original.len();
+            assert!((0..=original.len())
+                .any(|idx| data[..idx] == original[..idx]
+                    && data[idx + inserted..] == original[idx..]));
+            if data.iter().any(|&b| b != b'A') {
+                return;
             }
-            _ => unreachable!(),
-        }
-
-        // Roll a random index into data
-        if val_size > self.test_case.size {
-            return Err(Error::new("Mutation size > test case"));
-        }
-        let idx = get_random_index(&mut self.test_case.data, &mut self.prng, Some(val_size));
-        if idx + val_size >= self.test_case.size {
-            return Err(Error::new("Mutation size > test case"));
         }
+        panic!("No grammar fragment was spliced into the test case");
+    }
 
-        if val == 0 {
-            // Write the val to data
-            for i in 0..val_size {
-                self.test_case.data[idx + i] = 0;
-            }
-        } else {
-            // Check if val has any unset upper bytes
-            let mut v = val;
-            let mut unset_bytes = 0;
-            while v > 0 {
-                v >>= 8;
-                unset_bytes += 1;
-            }
-            let start = idx + val_size - unset_bytes;
-            let end = idx + val_size;
-            for i in start..end {
-                self.test_case.data[i] = (val >> (8 * (i - start))) as u8;
+    #[test]
+    fn test_grammar_splice_max_size() {
+        let mut me = MutationEngine::new()
+            .set_max_test_case_size(24)
+            .enable_custom_mutators(vec![CustomMutators::GrammarSplice(
+                GrammarTemplate::DataFormat(DataFormat::Json),
+            )]);
+        let mut spliced = 0;
+        for _ in 0..100 {
+            me.set_test_case(&[b'A'; 16]);
+            if me.grammar_splice().is_ok() {
+                assert!(me.test_case.size <= 24);
+                spliced += 1;
             }
         }
-        Ok(())
-    }
+        assert!(spliced > 0);
 
-    /// Mutator that inserts a random value from the user token dictionary into the current test case.
-    fn add_word_from_dict(&mut self) -> Result<()> {
-        add_from_dict(
-            &self.user_token_dict,
-            &mut self.test_case.data,
-            &mut self.prng,
-        )
+        me.set_test_case(&[b'A'; 24]);
+        assert!(me.grammar_splice().is_err());
+        assert_eq!(me.test_case.data, [b'A'; 24]);
     }
 
-    /// Mutator that inserts a random value from the TORC token dictionary into the current test case.
-    fn add_word_from_torc(&mut self) -> Result<()> {
-        if self.torc_token_dict.is_empty() {
-            return Err(Error::new("TORC token dict is empty"));
+    #[test]
+    fn test_grammar_max_depth_order() {
+        let json = || {
+            vec![CustomMutators::GrammarGenerator(
+                GrammarTemplate::DataFormat(DataFormat::Json),
+            )]
         };
-        add_from_dict(
-            &self.torc_token_dict,
-            &mut self.test_case.data,
-            &mut self.prng,
-        )
+        let mut before = MutationEngine::new()
+            .set_generator_seed(0x1337)
+            .set_grammar_max_depth(4)
+            .enable_custom_mutators(json());
+        let mut after = MutationEngine::new()
+            .set_generator_seed(0x1337)
+            .enable_custom_mutators(json())
+            .set_grammar_max_depth(4);
+        let mut unlimited = MutationEngine::new()
+            .set_generator_seed(0x1337)
+            .enable_custom_mutators(json());
+        let mut differs = false;
+        for _ in 0..50 {
+            before.grammar_gen().unwrap();
+            after.grammar_gen().unwrap();
+            unlimited.grammar_gen().unwrap();
+            assert_eq!(before.test_case.data, after.test_case.data);
+            differs |= before.test_case.data != unlimited.test_case.data;
+        }
+        assert!(differs);
     }
-}
-
-/// Returns a random index into data. If `exclude_off` is not None, the returned index will be at least
-/// `exclude_off` bytes away from the end of data.
-fn get_random_index(
-    data: &mut Vec<u8>,
-    prng: &mut Rng<Generator>,
-    exclude_off: Option<usize>,
-) -> usize {
-    assert!(!data.is_empty(), "Cannot get random index from empty data");
-    prng.rand_exp(0, data.len() - exclude_off.map_or(0, |x| x))
-}
 
-/// Adds a random value from dict to data.
-fn add_from_dict(dict: &[Vec<u8>], data: &mut Vec<u8>, prng: &mut Rng<Generator>) -> Result<()> {
-    assert!(!dict.is_empty(), "Cannot add from empty dict");
-    let mut val = prng.pick(dict).clone();
-    let val_size = val.len();
-    if val_size > data.len() {
-        return Err(Error::new("Dictionary token larger than test case"));
-    }
-    let to = prng.rand_range(0, data.len() - val_size);
-    if val_size == 1 {
-        data[to] = val[0];
-        return Ok(());
+    #[test]
+    fn test_ni_large_number() {
+        let data = format!("<a>{}</a>", "9".repeat(40)).into_bytes();
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec![data.clone()]);
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(1337)));
+        for _ in 0..1000 {
+            ni_mutate(&data, data.len(), &mut prng, &corpus, &NiConfig::default()).unwrap();
+        }
     }
-    if prng.bool() {
-        val.reverse();
-    };
-    (0..val_size).for_each(|i| {
-        data[to + i] = val[i];
-    });
-    Ok(())
-}
 
-/// Unlike splicing this function will not modify the size of the test case. It will instead
-/// copy a random sized chunk of another test case into the current test case within its bounds.
-fn copy_part_of(from: &[u8], to: &mut TestCase, prng: &mut Rng<Generator>) -> Result<()> {
-    let mut to_idx = prng.rand_range(0, to.size);
-    let mut copy_size = std::cmp::min(prng.rand_range(1, to.size - to_idx + 1), from.len());
-    let mut from_idx = prng.rand_range(0, from.len() - copy_size + 1);
-    if copy_size == 1 {
-        let b = from[from_idx];
-        to.data[to_idx] ^= if b == 0 { b + 1 } else { b };
-        return Ok(());
+    #[test]
+    fn test_ni_config() {
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec![b"<a><b>1337</b><c>42</c><b>a</b></a>
+<a><c>x</c><b>1</b><b>2</b></a>"
+            .repeat(4)]);
+        let mutate_with = |aimrounds, seed| {
+            let mut me = MutationEngine::new()
+                .set_generator_seed(seed)
+                .set_corpus(corpus.clone())
+                .set_ni_config(NiConfig {
+                    aimrounds,
+                    ..NiConfig::default()
+                });
+            me.set_test_case(&corpus[0]);
+            me.ni().unwrap();
+            me.test_case.data
+        };
+
+        let mut differing = 0;
+        for seed in 1..=32 {
+            let few = mutate_with(1, seed);
+            let many = mutate_with(4096, seed);
+            differing += usize::from(few != many);
+        }
+        assert!(differing > 0);
     }
-    if from_idx == to_idx {
-        if from_idx > 0 {
-            from_idx -= 1;
-        } else if to_idx > 0 {
-            to_idx -= 1;
-        } else if from_idx + 1 + copy_size < from.len() {
-            from_idx += 1;
-        } else {
-            from_idx += 1;
-            copy_size -= 1;
+
+    #[test]
+    fn prop_size_preserving_mutators() {
+        let mutators: [fn(&mut MutationEngine) -> Result<()>; 12] = [
+            MutationEngine::shuffle_bytes,
+            MutationEngine::swap_neighbors,
+            MutationEngine::swap_endianness,
+            MutationEngine::change_bit,
+            MutationEngine::change_byte,
+            MutationEngine::negate_byte,
+            MutationEngine::arithmetic_width,
+            MutationEngine::change_binary_integer,
+            MutationEngine::insert_constant,
+            MutationEngine::insert_float_constant,
+            MutationEngine::encoded_region,
+            MutationEngine::shuffle_records,
+        ];
+        for fun in mutators {
+            check(fun, same_len);
         }
     }
-    let copy_slice = &from[from_idx..from_idx + copy_size];
-    to.data[to_idx..to_idx + copy_size].clone_from_slice(copy_slice);
-    Ok(())
-}
 
-/// This function modifies the test case by inserting a portion from another corpus entry
-/// at a random position, without overwriting any data.
-fn insert_part_of(
-    from: &[u8],
-    to: &mut TestCase,
-    prng: &mut Rng<Generator>,
-    max_size: usize,
-) -> Result<()> {
-    let available_space = max_size - to.size;
-    let max_copy_size = std::cmp::min(available_space, from.len());
-    assert!(max_copy_size > 0, "Insertion size is 0");
+    #[test]
+    fn prop_reordering_mutators() {
+        check(MutationEngine::shuffle_bytes, same_bytes);
+        check(MutationEngine::swap_endianness, same_bytes);
+        check(MutationEngine::swap_neighbors, same_bytes);
+        check(MutationEngine::shuffle_records, same_bytes);
+    }
 
-    let copy_size = prng.rand_range(1, max_copy_size + 1);
-    let from_idx = prng.rand_range(0, from.len() - copy_size + 1);
-    let to_idx = if to.data.is_empty() {
-        0
-    } else {
-        prng.rand_range(0, to.size)
-    };
+    #[test]
+    fn prop_negate_byte() {
+        check(MutationEngine::negate_byte, |orig, new| {
+            let diff: Vec<usize> = (0..orig.len()).filter(|&i| orig[i] != new[i]).collect();
+            diff.len() == 1 && new[diff[0]] == !orig[diff[0]]
+        });
+    }
 
-    /*
-    to.data.resize(to.size + copy_size, 0);
+    #[test]
+    fn prop_change_bit() {
+        check(MutationEngine::change_bit, |orig, new| {
+            let flipped: u32 = orig
+                .iter()
+                .zip(new)
+                .map(|(a, b)| (a ^ b).count_ones())
+                .sum();
+            flipped == 1
+        });
+    }
 
-    // Rotate the slice to the right
-    to.data[to_idx..].rotate_right(copy_size);
+    #[test]
+    fn prop_change_byte() {
+        check(MutationEngine::change_byte, |orig, new| {
+            changed_bytes(orig, new) == 1
+        });
+    }
 
-    // Copy the data from the other corpus entry into the open spot
-    to.data[to_idx..to_idx + copy_size].copy_from_slice(&from[from_idx..from_idx + copy_size]);
+    #[test]
+    fn prop_erase_bytes() {
+        check(MutationEngine::erase_bytes, |orig, new| {
+            new.len() < orig.len() && is_subsequence(new, orig)
+        });
+    }
 
-    to.size = to.data.len();
-    Ok(())
-    */
+    #[test]
+    fn prop_insert_bytes() {
+        check(MutationEngine::insert_bytes, |orig, new| {
+            new.len() > orig.len() && is_subsequence(orig, new)
+        });
+    }
 
-    // Allocate new memory for the data.
-    // This seems to be faster than relying on the `resize` and `rotate_right` functions
-    // that are implemented on `Vec`. Experiments show a 6% speedup.
-    let new_size = to.size + copy_size;
-    let mut new_data: Vec<u8> = vec![0u8; new_size];
-    unsafe {
-        new_data.set_len(new_size);
+    #[test]
+    fn prop_add_word_from_dict() {
+        // Tokens either overwrite bytes in place or are inserted, growing the test case
+        check(MutationEngine::add_word_from_dict, |orig, new| {
+            new.len() == orig.len() || (new.len() > orig.len() && is_subsequence(orig, new))
+        });
     }
 
-    // Copy the data before the insertion point
-    new_data[..to_idx].copy_from_slice(&to.data[..to_idx]);
+    #[test]
+    fn prop_truncate() {
+        check(MutationEngine::truncate, |orig, new| {
+            new.len() < orig.len() && orig.starts_with(new)
+        });
+    }
 
-    // Copy the data from `from`
-    new_data[to_idx..to_idx + copy_size].copy_from_slice(&from[from_idx..from_idx + copy_size]);
+    #[test]
+    fn prop_append() {
+        check(MutationEngine::append, |orig, new| {
+            new.len() > orig.len() && new.starts_with(orig)
+        });
+    }
 
-    // Copy the data after the insertion point
-    new_data[to_idx + copy_size..].copy_from_slice(&to.data[to_idx..]);
+    #[test]
+    fn test_run_in_process() {
+        let corpus = corpus();
+        let mut engine = engine(&corpus);
+        let mut runs = 0;
+        let crashes = engine.run_in_process(1000, |data| {
+            runs += 1;
+            if data.first() == Some(&b'T') {
+                panic!("boom");
+            }
+        });
+        assert_eq!(runs, 1000);
+        assert!(!crashes.is_empty());
+        assert!(crashes.iter().all(|c| c.first() == Some(&b'T')));
+    }
 
-    to.data = new_data;
-    to.size = new_size;
-    Ok(())
-}
+    #[test]
+    fn test_mutators_by_name() {
+        assert_eq!(
+            "shuffle_bytes".parse::<StandardMutators>().unwrap(),
+            StandardMutators::ShuffleBytes
+        );
+        assert_eq!(
+            "Change-ASCII-Integer".parse::<StandardMutators>().unwrap(),
+            StandardMutators::ChangeASCIIInteger
+        );
+        assert!("ni".parse::<StandardMutators>().is_err());
 
-fn arithmetic<T>(data: &mut Vec<u8>, data_size: usize, prng: &mut Rng<Generator>) -> Result<()>
-where
-    T: num_traits::PrimInt
-        + num_traits::Unsigned
-        + WrappingAdd
-        + WrappingSub
-        + WrappingNeg
-        + WrappingMul
-        + WrappingShl
-        + WrappingShr
-        + std::ops::BitOrAssign
-        + num::cast::AsPrimitive<u8>
-        + std::convert::From<u8>,
-    u8: AsPrimitive<T>,
-{
-    let bytes = std::mem::size_of::<T>();
-    if data_size < bytes {
-        return Err(Error::new("Mutation size > test case"));
+        let me = MutationEngine::new()
+            .set_mutators_by_name(&["negate_byte", "change_bit"])
+            .unwrap();
+        assert_eq!(me.mutators.len(), 2);
+        assert!(MutationEngine::new()
+            .set_mutators_by_name(&["add_word_from_dict"])
+            .is_err());
+
+        let me = MutationEngine::new()
+            .disable_mutators_by_name(&["truncate"])
+            .unwrap();
+        assert!(!me
+            .mutators
+            .iter()
+            .any(|m| matches!(m, Mutators::Standard(StandardMutators::Truncate))));
+        let all: Vec<String> = StandardMutators::ALL
+            .iter()
+            .map(|m| format!("{m:?}"))
+            .collect();
+        assert!(MutationEngine::new()
+            .disable_mutators_by_name(&all)
+            .is_err());
     }
-    let idx = get_random_index(data, prng, Some(bytes));
-    let mut val: T = 0.into();
-    for i in 0..bytes {
-        val |= <u8 as AsPrimitive<T>>::as_(data[idx + i]) << (8 * (bytes - i - 1));
+
+    #[test]
+    fn test_printable_word_boundary() {
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec![
+            b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec(),
+            b"  leading and trailing  ".to_vec(),
+        ]);
+        let mut me = MutationEngine::new()
+            .set_corpus(corpus.clone())
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .set_printable_mode(PrintableMode::WordBoundary)
+            .set_mutation_passes(3);
+        for _ in 0..1000 {
+            let tc = me.mutate().data.clone();
+            let orig = corpus.iter().find(|c| c.len() == tc.len()).unwrap();
+            for (a, b) in orig.iter().zip(&tc) {
+                if a.is_ascii_whitespace() {
+                    assert_eq!(a, b);
+                } else {
+                    assert!(b.is_ascii_graphic());
+                }
+            }
+        }
     }
-    let op = prng.rand_range(0, 6);
-    val = match op {
-        0 => val.wrapping_sub(&1.into()),
-        1 => val.wrapping_add(&1.into()),
-        2 => val.wrapping_mul(&2.into()),
-        3 => val.wrapping_neg(),
-        4 => val.wrapping_shl(2),
-        5 => val.wrapping_shr(2),
-        _ => unreachable!(),
-    };
-    for i in 0..bytes {
-        let a = 8 * (bytes - i - 1);
-        let b = val >> a;
-        data[idx + i] = b.as_();
+
+    #[test]
+    fn test_mutate_batch() {
+        let corpus = corpus();
+        let batch = |seed| {
+            MutationEngine::new()
+                .set_corpus(corpus.clone())
+                .set_generator_seed(seed)
+                .mutate_batch(64)
+        };
+        let first = batch(0x1337);
+        assert_eq!(first.len(), 64);
+        assert_eq!(first, batch(0x1337));
+        assert_ne!(first, batch(0x1338));
+        assert!(MutationEngine::new().mutate_batch(0).is_empty());
     }
-    Ok(())
-}
 
-fn swap_neighbors_width<T>(
-    data: &mut Vec<u8>,
-    data_size: usize,
-    prng: &mut Rng<Generator>,
-) -> Result<()>
-where
-    T: num_traits::PrimInt + num_traits::Unsigned,
-{
-    let bytes = std::mem::size_of::<T>();
-    if data_size <= bytes {
-        return Err(Error::new("Mutation size > test case"));
+    #[test]
+    fn test_mutate_batch_distinct() {
+        let corpus = corpus();
+        let mut me = engine(&corpus).set_distinct_batches(true);
+        let batch = me.mutate_batch(256);
+        assert_eq!(batch.len(), 256);
+        let unique: HashSet<&Vec<u8>> = batch.iter().collect();
+        assert_eq!(unique.len(), batch.len());
+
+        // A single byte that is always negated can only ever yield one distinct output
+        let mut me = MutationEngine::new()
+            .set_corpus(Arc::new(vec![vec![0x41]]))
+            .set_mutators_by_name(&["negate_byte"])
+            .unwrap()
+            .set_distinct_batches(true);
+        assert!(!me.warned_short_batch);
+        assert_eq!(me.mutate_batch(16), vec![vec![0xbe]]);
+        assert!(me.warned_short_batch);
+        // Later short batches are not warned about again
+        assert_eq!(me.mutate_batch(16), vec![vec![0xbe]]);
     }
-    let idx = get_random_index(data, prng, Some(data_size - 1 - bytes));
-    // Check if we can swap with the right neighbor
-    if idx + 2 * bytes < data_size {
-        for i in 0..bytes {
-            unsafe { ptr::swap(&mut data[idx + i], &mut data[idx + bytes + i]) }
+
+    #[test]
+    fn test_external_generator_replay() {
+        #[derive(Clone)]
+        struct Replay {
+            values: Arc<Vec<usize>>,
+            pos: usize,
         }
-    } else if (idx as i64 - bytes as i64) >= 0 && idx + bytes < data_size {
-        // Otherwise swap with the left neighbors
-        for i in 0..bytes {
-            unsafe {
-                ptr::swap(&mut data[idx - bytes + i], &mut data[idx + i]);
+
+        impl GeneratorTrait for Replay {
+            fn rand(&mut self) -> usize {
+                let val = self.values[self.pos % self.values.len()];
+                self.pos += 1;
+                val
             }
-        }
-    } else {
-        // If swapping with the left or the right is not possible,
-        // calculate the maximum number of bytes that can be swapped
-        let max_bytes = (data_size - idx).min(idx).min(bytes);
-        if max_bytes < 2 {
-            // Otherwise just negate the byte
-            data[max_bytes] = !data[max_bytes];
-            return Ok(());
-        }
-        let half_bytes = max_bytes / 2;
-        if (idx as i64 - half_bytes as i64) >= 0 {
-            // swap with the left
-            for i in 0..half_bytes {
-                unsafe {
-                    ptr::swap(&mut data[idx - i], &mut data[idx + half_bytes - i]);
-                }
+
+            fn set_seed(&mut self, _seed: usize) {
+                self.pos = 0;
             }
-        } else {
-            // swap with the right
-            for i in 0..half_bytes {
-                unsafe {
-                    ptr::swap(&mut data[idx + i], &mut data[data_size - i - 1]);
-                }
+        }
+
+        let mut recorder = MutationEngine::new().set_generator_seed(0xdeadbeefcafebabe);
+        let values = Arc::new((0..4096).map(|_| recorder.prng.rand()).collect::<Vec<_>>());
+        let replay = || Replay {
+            values: values.clone(),
+            pos: 0,
+        };
+
+        let mut outputs = Vec::new();
+        for _ in 0..2 {
+            let mut me = MutationEngine::new()
+                .set_corpus(corpus())
+                .set_external_generator(replay());
+            assert_eq!(me.current_generator(), Generators::External);
+            assert_eq!(me.current_seed(), 0);
+            outputs.push(me.mutate_batch(32));
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_deterministic_bitflips_n() {
+        let data = [0x00, 0xff, 0x5a];
+        let mut me = MutationEngine::new();
+        me.set_test_case(&data);
+        for bits in [1, 2, 4] {
+            let mut stage = me.deterministic_bitflips_n(bits);
+            assert_eq!(stage.len(), data.len() * 8 - bits + 1);
+            let outputs: Vec<Vec<u8>> = stage.by_ref().collect();
+            assert_eq!(outputs.len(), data.len() * 8 - bits + 1);
+            for (pos, out) in outputs.iter().enumerate() {
+                let flipped: Vec<usize> = (0..data.len() * 8)
+                    .filter(|&bit| (out[bit / 8] ^ data[bit / 8]) & (0x80 >> (bit % 8)) != 0)
+                    .collect();
+                assert_eq!(flipped, (pos..pos + bits).collect::<Vec<_>>());
             }
+            // Exhausted stages stay exhausted
+            assert_eq!(stage.next(), None);
+            assert_eq!(stage.len(), 0);
         }
+        // The stage works on a snapshot and leaves the test case untouched
+        assert_eq!(me.test_case.data, data);
+
+        me.set_test_case(&[]);
+        assert_eq!(me.deterministic_bitflips_n(1).count(), 0);
+        me.set_test_case(&vec![0; DETERMINISTIC_MAX_STEPS]);
+        assert_eq!(
+            me.deterministic_bitflips_n(2).len(),
+            DETERMINISTIC_MAX_STEPS
+        );
     }
-    Ok(())
-}
 
-/// A function that calls a function pointer
-fn fun_caller(
-    func: fn(&mut Vec<u8>, usize, &mut Rng<Generator>) -> Result<()>,
-    data: &mut Vec<u8>,
-    data_size: usize,
-    prng: &mut Rng<Generator>,
-) -> Result<()> {
-    func(data, data_size, prng)
-}
+    #[test]
+    fn test_deterministic_bit_sweep() {
+        let data = vec![0x12, 0x34];
+        let mut me = MutationEngine::new()
+            .set_corpus(Arc::new(vec![data.clone()]))
+            .set_mutators_by_name(&["deterministic_bit_sweep"])
+            .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let outputs: Vec<Vec<u8>> = (0..18).map(|_| me.mutate().data.clone()).collect();
+        for bit in 0..16 {
+            let mut variant = data.clone();
+            variant[bit / 8] ^= 0x80 >> (bit % 8);
+            assert_eq!(outputs.iter().filter(|out| **out == variant).count(), 1);
+        }
+        // Followed by the byte flips
+        assert_eq!(outputs[16], [0xed, 0x34]);
+        assert_eq!(outputs[17], [0x12, 0xcb]);
 
-    enum TestCondition {
-        DataInequality,
-        SizeInequality,
-        GeneralErrorChecker,
+        // An exhausted sweep leaves the test case untouched until it changes
+        assert_eq!(me.mutate().data, data);
+        me.set_test_case(&[0x00]);
+        me.deterministic_bit_sweep().unwrap();
+        assert_eq!(me.test_case.data, [0x80]);
     }
 
-    fn corpus() -> Arc<Vec<Vec<u8>>> {
-        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec![
-            "ThisIsSomeTest".as_bytes().to_vec(),
-            "YetAnotherSimpleInput".as_bytes().to_vec(),
-            [0xff].to_vec(),
-        ]);
-        corpus
+    #[test]
+    #[should_panic(expected = "bits must be one of 1, 2 or 4")]
+    fn test_deterministic_bitflips_n_invalid() {
+        let _ = MutationEngine::new().deterministic_bitflips_n(3);
     }
 
-    fn engine(corp: &Arc<Vec<Vec<u8>>>) -> MutationEngine {
-        let mut me = MutationEngine::new()
-            .set_corpus(corp.clone())
-            .set_generator(&Generators::Romuduojr)
+    #[test]
+    fn test_custom_mutator_frequency() {
+        let count_ni = |me: &mut MutationEngine| {
+            for _ in 0..2000 {
+                me.mutate();
+            }
+            let executions = me.take_mutator_executions();
+            let total: usize = executions.values().sum();
+            (
+                executions.get(&StandardMutators::Ni).copied().unwrap_or(0),
+                total,
+            )
+        };
+        let engine = || {
+            MutationEngine::new()
+                .set_corpus(corpus())
+                .set_generator_seed(0xdeadbeefcafebabe)
+                .enable_custom_mutators(vec![CustomMutators::Ni])
+        };
+
+        // Uniformly Ni is one out of many mutators
+        let (ni, total) = count_ni(&mut engine());
+        assert!(ni * 10 < total);
+
+        let (ni, total) = count_ni(&mut engine().set_custom_mutator_frequency(0.5));
+        assert!(ni * 3 > total && ni * 3 < total * 2);
+
+        let (ni, _) = count_ni(&mut engine().set_custom_mutator_frequency(0.0));
+        assert_eq!(ni, 0);
+    }
+
+    #[test]
+    fn test_mutator_weights() {
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus())
             .set_generator_seed(0xdeadbeefcafebabe)
-            .set_token_dict("dicts/test.dict");
-        for _ in 0..128 {
-            let tc_size = me.prng.rand_range(1, 4096);
-            let tc = me.prng.rand_byte_vec(tc_size);
-            me.add_to_corpus(&tc);
+            .set_mutator_weights(vec![
+                (Mutators::Standard(StandardMutators::ChangeBit), 10_000),
+                (Mutators::Standard(StandardMutators::ChangeByte), 0),
+            ]);
+        for _ in 0..10_000 {
+            engine.mutate();
         }
-        me
+        let executions = engine.take_mutator_executions();
+        let total: usize = executions.values().sum();
+        assert!(executions[&StandardMutators::ChangeBit] * 10 > total * 9);
+        assert!(!executions.contains_key(&StandardMutators::ChangeByte));
+
+        // Passes are skipped when every enabled mutator is disabled
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus())
+            .set_mutators_by_name(&["change_bit"])
+            .unwrap()
+            .set_mutator_weights(vec![(Mutators::Standard(StandardMutators::ChangeBit), 0)]);
+        let parent = engine.corpus[0].clone();
+        assert_eq!(engine.mutate_from(&parent).data, parent);
+        assert!(engine.take_mutator_executions().is_empty());
     }
 
-    fn run<F>(fun: F, tcond: TestCondition)
-    where
-        F: Fn(&mut MutationEngine) -> Result<()>,
-    {
-        let corpus = corpus();
-        let mut engine = engine(&corpus);
+    #[test]
+    fn test_mutator_report() {
+        let mut engine = MutationEngine::new()
+            .set_mutators_by_name(&["append", "truncate"])
+            .unwrap()
+            .set_mutator_weights(vec![(Mutators::Standard(StandardMutators::Append), 3)]);
+        for _ in 0..100 {
+            engine.mutate_from(b"");
+        }
+        let report = engine.mutator_report();
+        assert_eq!(report[0].0, "Append");
+        assert!(report[0].1.times_selected > report[1].1.times_selected);
+        assert_eq!(report[0].1.times_errored, report[0].1.times_selected);
+        assert_eq!(report[0].1.bytes_delta_sum, 0);
 
-        for _ in 0..100_000 {
-            engine = engine.set_random_test_case();
-            let tc_orig = engine.test_case.data.clone();
-            if fun(&mut engine).is_ok() {
-                let tc = engine.test_case.data.clone();
+        let mut engine = MutationEngine::new()
+            .set_mutators_by_name(&["truncate"])
+            .unwrap();
+        let len = engine.mutate_from(&[0u8; 100]).data.len();
+        let report = engine.mutator_report();
+        assert_eq!(
+            report,
+            vec![(
+                "Truncate".to_string(),
+                MutatorStat {
+                    times_selected: 1,
+                    times_errored: 0,
+                    bytes_delta_sum: len as i64 - 100,
+                }
+            )]
+        );
+    }
 
-                match tcond {
-                    TestCondition::DataInequality => {
-                        assert_ne!(tc_orig, tc);
-                    }
-                    TestCondition::SizeInequality => {
-                        assert_ne!(tc_orig.len(), tc.len());
-                    }
-                    TestCondition::GeneralErrorChecker => {}
-                };
-            }
+    #[test]
+    fn test_worker_streams() {
+        for generator in [Generators::Romuduojr, Generators::Xorshiro128ss] {
+            let draws = |thread_id| {
+                let mut engine = MutationEngine::new()
+                    .set_generator(&generator)
+                    .set_generator_seed(0x1337)
+                    .set_worker_stream(thread_id);
+                (0..1000).map(|_| engine.prng.rand()).collect::<Vec<_>>()
+            };
+            let (first, second) = (draws(0), draws(1));
+            let shared: HashSet<_> = first.iter().collect();
+            assert!(
+                second.iter().filter(|v| shared.contains(v)).count() < 10,
+                "{generator:?}"
+            );
+            assert_eq!(first, draws(0), "{generator:?}");
         }
     }
 
     #[test]
-    fn test_shuffle_bytes() {
-        // We check against `TestCondition::GeneralErrorChecker` as a low shuffle amount, with
-        // a possible small slice to shuffle may yield the same result.
-        // We don't care about the `sub_slice` being potentially the same.
-        // We favor executions/second over ensuring the mutated version is != the original.
-        run(
-            MutationEngine::shuffle_bytes,
-            TestCondition::GeneralErrorChecker,
-        );
+    fn test_mutate_into() {
+        let corpus = corpus();
+        let mut a = MutationEngine::new()
+            .set_corpus(corpus.clone())
+            .set_generator_seed(0x1337);
+        let mut b = MutationEngine::new()
+            .set_corpus(corpus)
+            .set_generator_seed(0x1337);
+        let mut out = b"stale".to_vec();
+        for _ in 0..1000 {
+            a.mutate_into(&mut out);
+            assert_eq!(out, b.mutate().data);
+        }
     }
 
     #[test]
-    fn test_erase_bytes() {
-        run(MutationEngine::erase_bytes, TestCondition::SizeInequality);
+    fn test_mutate_empty_corpus() {
+        let mut me = MutationEngine::new()
+            .set_generator_seed(0x1337)
+            .set_corpus(Arc::new(Vec::new()));
+        for _ in 0..10 {
+            me.mutate();
+        }
+        assert_eq!(me.corpus.len(), 1);
+        assert_eq!(me.corpus[0].len(), 128);
+
+        let mut me = MutationEngine::new().set_corpus(Arc::new(Vec::new()));
+        let (_, record) = me.mutate_with_log();
+        assert_eq!(record.corpus_index, 0);
     }
 
     #[test]
-    fn test_insert_bytes() {
-        run(MutationEngine::insert_bytes, TestCondition::SizeInequality);
+    fn test_energy_selection() {
+        let corpus: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i]).collect();
+        let mut engine = MutationEngine::new()
+            .set_generator_seed(0x1337)
+            .set_corpus(corpus.into());
+        engine.set_energy_for(1, 100).unwrap();
+        engine.set_energy_for(3, 0).unwrap();
+        let mut picks = [0usize; 4];
+        for _ in 0..20_000 {
+            picks[engine.set_new_test_case()] += 1;
+        }
+        assert!(picks[1] > 30 * picks[0] && picks[1] > 30 * picks[2]);
+        assert!(picks[3] > 0 && picks[3] < picks[0]);
     }
 
     #[test]
-    fn test_swap_endianness() {
-        // We check against `TestCondition::GeneralErrorChecker` as a low swap amount, with
-        // a possible small slice to swap may yield the same result, for example in such a
-        // scenario:
-        //
-        // `Swapping 2 bytes - [249, 249]`
-        run(
-            MutationEngine::swap_endianness,
-            TestCondition::GeneralErrorChecker,
+    fn test_minimize_corpus() {
+        let corpus: Vec<Vec<u8>> = vec![
+            b"GET /".to_vec(),
+            b"GET /index.html".to_vec(),
+            b"POST".to_vec(),
+            b"GET /".to_vec(),
+            b"".to_vec(),
+            b"POST".to_vec(),
+            b"PUT /a".to_vec(),
+            b"GET /index.html".to_vec(),
+        ];
+        let mut engine = MutationEngine::new().set_corpus(corpus.clone().into());
+        engine.set_energy_for(2, 5).unwrap();
+        engine.minimize_corpus(usize::MAX);
+        assert_eq!(
+            *engine.corpus,
+            vec![
+                b"GET /index.html".to_vec(),
+                b"POST".to_vec(),
+                b"PUT /a".to_vec()
+            ]
         );
+        assert_eq!(engine.corpus_energy, vec![1, 5, 1]);
+        assert!(engine.corpus.contains(&engine.test_case.data));
+
+        // Only the shortest survivors are kept
+        let mut engine = MutationEngine::new().set_corpus(corpus.into());
+        engine.minimize_corpus(2);
+        assert_eq!(*engine.corpus, vec![b"POST".to_vec(), b"PUT /a".to_vec()]);
+
+        engine.minimize_corpus(0);
+        assert!(engine.corpus.is_empty());
+        assert!(engine.test_case.data.is_empty());
     }
 
     #[test]
-    fn test_change_bit() {
-        run(MutationEngine::change_bit, TestCondition::DataInequality);
+    fn test_max_len() {
+        let corpus: Vec<Vec<u8>> = (0..16).map(|i| vec![i; 512 + 64 * i as usize]).collect();
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus.into())
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .enable_custom_mutators(vec![CustomMutators::Ni])
+            .set_max_len(64);
+        for _ in 0..10_000 {
+            let tc = engine.mutate();
+            assert!(tc.data.len() <= 64);
+            assert!(tc.size <= tc.data.len());
+            assert!(tc.data_ptr <= tc.size);
+        }
     }
 
     #[test]
-    fn test_change_byte() {
-        run(MutationEngine::change_byte, TestCondition::DataInequality);
+    #[should_panic(expected = "Maximum test case length must be greater than 0")]
+    fn test_max_len_zero() {
+        let _ = MutationEngine::new().set_max_len(0);
     }
 
     #[test]
-    // Same argumentation as for `swap_endianness`.
-    // The newly generated ASCII character can still be the same as before.
-    fn test_change_ascii_integer() {
-        run(
-            MutationEngine::change_ascii_integer,
-            TestCondition::GeneralErrorChecker,
+    fn test_register_custom() {
+        struct XorA;
+
+        impl Mutator for XorA {
+            fn mutate(
+                &self,
+                tc: &mut TestCase,
+                _prng: &mut Rng<Generator>,
+                _corpus: &Arc<Vec<Vec<u8>>>,
+            ) -> Result<()> {
+                tc.data.iter_mut().for_each(|b| *b ^= 0x41);
+                Ok(())
+            }
+        }
+
+        let mut engine = MutationEngine::new()
+            .register_custom(Box::new(XorA))
+            .set_mutators_by_name(&["change_bit"])
+            .unwrap()
+            .set_mutator_weights(vec![(Mutators::Standard(StandardMutators::ChangeBit), 0)]);
+        assert_eq!(engine.mutate_from(b"hantu").data, b") /54");
+        assert_eq!(
+            engine.take_mutator_executions(),
+            [(StandardMutators::User, 1)].into()
         );
     }
 
     #[test]
-    // Same argumentation as for `swap_endianness`.
-    // The done type casting can produce a value which the original data slice
-    // at the rolled index already holds.
-    fn test_change_binary_integer() {
-        run(
-            MutationEngine::change_binary_integer,
-            TestCondition::GeneralErrorChecker,
-        );
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        let mut data = b"....123456789".to_vec();
+        let fixup = Crc32Fixup {
+            is_little_endian: true,
+            ..Crc32Fixup::new(0, 4..usize::MAX)
+        };
+        fixup.fixup(&mut data);
+        assert_eq!(data[..4], 0xcbf4_3926u32.to_le_bytes());
+
+        // Fields that do not fit into the test case are left untouched
+        let mut data = b"abc".to_vec();
+        Crc32Fixup::new(0, 0..3).fixup(&mut data);
+        LengthFieldFixup::new(2, 2, false, 0).fixup(&mut data);
+        assert_eq!(data, b"abc");
     }
 
     #[test]
-    fn test_negate_byte() {
-        run(MutationEngine::negate_byte, TestCondition::DataInequality);
+    fn test_length_field_fixup_wide() {
+        // Fields wider than a `u64` zero-fill the excess bytes instead of overflowing the shift
+        let mut data = vec![0xff; 20];
+        LengthFieldFixup::new(0, 16, false, 4).fixup(&mut data);
+        assert_eq!(data[..8], [0; 8]);
+        assert_eq!(data[8..16], 16u64.to_be_bytes());
+        assert_eq!(data[16..], [0xff; 4]);
+
+        let mut data = vec![0xff; 20];
+        LengthFieldFixup::new(0, 16, true, 4).fixup(&mut data);
+        assert_eq!(data[..8], 16u64.to_le_bytes());
+        assert_eq!(data[8..16], [0; 8]);
     }
 
     #[test]
-    fn test_swap_neighbors() {
-        // Same argumentation as for `swap_endianness`.
-        run(
-            MutationEngine::swap_neighbors,
-            TestCondition::GeneralErrorChecker,
+    fn test_post_processor() {
+        // 2 byte length and 4 byte CRC-32 header, both covering the body starting at offset 6
+        const BODY_START: usize = 6;
+        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(
+            ["hantu", "mutation engine", "post processor"]
+                .iter()
+                .map(|s| [&[0u8; BODY_START][..], s.as_bytes()].concat())
+                .collect(),
         );
+        let fixups: Vec<Box<dyn PostProcessor>> = vec![
+            Box::new(LengthFieldFixup::new(0, 2, false, BODY_START)),
+            Box::new(Crc32Fixup::new(2, BODY_START..usize::MAX)),
+        ];
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus)
+            .set_generator_seed(0x1337)
+            .set_post_processor(Box::new(fixups));
+        let mut checked = 0;
+        for _ in 0..1000 {
+            let tc = engine.mutate();
+            assert_eq!(tc.size, tc.data.len());
+            if tc.data.len() < BODY_START {
+                continue;
+            }
+            let body = &tc.data[BODY_START..];
+            assert_eq!(
+                u16::from_be_bytes([tc.data[0], tc.data[1]]),
+                body.len() as u16
+            );
+            assert_eq!(tc.data[2..BODY_START], crc32(body).to_be_bytes());
+            checked += 1;
+        }
+        assert!(checked > 0);
     }
 
     #[test]
-    fn test_arithmetic() {
-        // Same argumentation as for `swap_endianness`.
-        run(
-            MutationEngine::arithmetic_width,
-            TestCondition::GeneralErrorChecker,
+    fn test_replay() {
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus())
+            .set_generator(&Generators::Romutrio)
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .enable_custom_mutators(vec![CustomMutators::Ni]);
+        let mut records = Vec::new();
+        let mut outputs = Vec::new();
+        for _ in 0..100 {
+            let (tc, record) = engine.mutate_with_log();
+            outputs.push(tc.data.clone());
+            assert_eq!(record.mutators.len(), 1);
+            records.push(record);
+        }
+        for (record, output) in records.iter().zip(&outputs).rev() {
+            let tc = engine.replay(std::slice::from_ref(record)).unwrap();
+            assert_eq!(&tc.data, output);
+        }
+        assert_eq!(
+            &engine.replay(&records).unwrap().data,
+            outputs.last().unwrap()
         );
     }
 
     #[test]
-    fn test_truncate() {
-        run(MutationEngine::truncate, TestCondition::SizeInequality);
+    fn test_replay_external_generator() {
+        // Keeps the default `get_state`, so its state cannot be restored
+        #[derive(Clone)]
+        struct Counter(usize);
+        impl GeneratorTrait for Counter {
+            fn rand(&mut self) -> usize {
+                self.0 += 1;
+                self.0
+            }
+            fn set_seed(&mut self, seed: usize) {
+                self.0 = seed;
+            }
+        }
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus())
+            .set_external_generator(Counter(0));
+        let (_, record) = engine.mutate_with_log();
+        assert!(engine.replay(&[record]).is_err());
     }
 
     #[test]
-    fn test_append() {
-        run(MutationEngine::append, TestCondition::SizeInequality);
+    #[should_panic(expected = "Custom mutator frequency must be within [0, 1]")]
+    fn test_custom_mutator_frequency_invalid() {
+        let _ = MutationEngine::new().set_custom_mutator_frequency(1.5);
     }
 
     #[test]
-    fn test_add_from_dict() {
-        // Same argumentation as for `swap_endianness`.
-        run(
-            MutationEngine::add_word_from_dict,
-            TestCondition::DataInequality,
-        );
+    fn test_min_edit_distance() {
+        let parent = b"The quick brown fox jumps over the lazy dog".to_vec();
+        let dist = |tc: &[u8]| {
+            parent.iter().zip(tc).filter(|(a, b)| a != b).count() + parent.len().abs_diff(tc.len())
+        };
+        for mode in [
+            PrintableMode::None,
+            PrintableMode::Bytewise,
+            PrintableMode::WordBoundary,
+        ] {
+            let mut me = MutationEngine::new()
+                .set_corpus(vec![parent.clone()].into())
+                .set_generator_seed(0xdeadbeefcafebabe)
+                .set_printable_mode(mode)
+                .set_min_edit_distance(8);
+            for _ in 0..1000 {
+                let tc = me.mutate().data.clone();
+                assert!(dist(&tc) >= 8, "{mode:?}: {tc:?}");
+                if mode == PrintableMode::WordBoundary {
+                    assert_eq!(tc.len(), parent.len());
+                    for (a, b) in parent.iter().zip(&tc) {
+                        assert_eq!(a.is_ascii_whitespace(), b.is_ascii_whitespace());
+                    }
+                }
+            }
+        }
+
+        // Inputs that are too short are changed as much as possible
+        let mut me = MutationEngine::new()
+            .set_corpus(vec![vec![0u8; 4]].into())
+            .set_mutators_by_name(&["change_bit"])
+            .unwrap()
+            .set_min_edit_distance(16);
+        assert!(me.mutate().data.iter().all(|b| *b != 0));
     }
 
     #[test]
-    fn test_add_from_magic() {
-        // Same argumentation as for `swap_endianness`.
-        run(
-            MutationEngine::insert_constant,
-            TestCondition::GeneralErrorChecker,
+    fn test_mutate_field() {
+        // Magic, little endian version, big endian length, name and payload
+        let data = b"HNTU\x01\x00\x00\x00\x00\x10name____payload".to_vec();
+        let layout = vec![
+            FieldSpec::new(0, 4, FieldKind::Bytes),
+            FieldSpec::new(
+                4,
+                2,
+                FieldKind::Int {
+                    is_little_endian: true,
+                },
+            ),
+            FieldSpec::new(
+                6,
+                4,
+                FieldKind::Int {
+                    is_little_endian: false,
+                },
+            ),
+            FieldSpec::new(10, 8, FieldKind::Str),
+        ];
+        let mut me = MutationEngine::new()
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .set_token_dict("dicts/test.dict")
+            .set_field_layout(layout.clone())
+            .unwrap();
+        let mut hits = vec![0; layout.len()];
+        for _ in 0..1000 {
+            me.set_test_case(&data);
+            me.mutate_field().unwrap();
+            let tc = &me.test_case.data;
+            assert_eq!(tc.len(), data.len());
+            let diff: Vec<usize> = (0..data.len()).filter(|&i| tc[i] != data[i]).collect();
+            if diff.is_empty() {
+                continue;
+            }
+            // All changed bytes lie within a single field
+            let field = layout
+                .iter()
+                .position(|f| {
+                    diff.iter()
+                        .all(|i| (f.offset..f.offset + f.len).contains(i))
+                })
+                .unwrap();
+            hits[field] += 1;
+        }
+        assert!(hits.iter().all(|&h| h > 0));
+
+        // Fields that do not fit into the test case are skipped
+        let mut me = MutationEngine::new()
+            .set_field_layout(vec![FieldSpec::new(2, 4, FieldKind::Bytes)])
+            .unwrap();
+        me.set_test_case(&data[..4]);
+        assert!(me.mutate_field().is_err());
+        me.set_test_case(&data[..6]);
+        assert!(me.mutate_field().is_ok());
+        assert_eq!(me.test_case.data[..2], data[..2]);
+
+        assert!(MutationEngine::new()
+            .set_field_layout(vec![FieldSpec::new(
+                0,
+                3,
+                FieldKind::Int {
+                    is_little_endian: true
+                }
+            )])
+            .is_err());
+        assert!(MutationEngine::new()
+            .set_mutators_by_name(&["mutate_field"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_encoded_region_roundtrip() {
+        let hex = b"00ff7fDEADbeef".to_vec();
+        let b64 = b"aGVsbG8gd29ybGQhIQ".to_vec();
+        for (enc, data) in [(RegionEncoding::Hex, hex), (RegionEncoding::Base64, b64)] {
+            assert_eq!(enc.find_regions(&data), vec![(0, data.len())]);
+            let mut out = data.clone();
+            enc.encode(&enc.decode(&data), &mut out, false);
+            assert!(out.eq_ignore_ascii_case(&data));
+        }
+        assert_eq!(
+            RegionEncoding::Base64.decode(b"aGVsbG8gd29ybGQhIQ"),
+            b"hello world!!"
         );
+        assert!(RegionEncoding::Hex.find_regions(b"cafe").is_empty());
     }
 
     #[test]
-    fn test_copy_part() {
-        run(MutationEngine::copy_part, TestCondition::DataInequality);
+    fn test_encoded_region_base64_field() {
+        let data = br#"{"user":"bob","token":"aGVsbG8gd29ybGQhIQ==","n":1}"#.to_vec();
+        let start = data.windows(4).position(|w| w == b"aGVs").unwrap();
+        let end = start + "aGVsbG8gd29ybGQhIQ".len();
+        let mut me = MutationEngine::new().set_generator_seed(0xdeadbeefcafebabe);
+        let mut changed = 0;
+        for _ in 0..1000 {
+            me.set_test_case(&data);
+            if me.encoded_region().is_err() {
+                continue;
+            }
+            let tc = &me.test_case.data;
+            assert_eq!(tc.len(), data.len());
+            assert_eq!(tc[..start], data[..start]);
+            assert_eq!(tc[end..], data[end..]);
+            let decoded = RegionEncoding::Base64.decode(&tc[start..end]);
+            assert_eq!(decoded.len(), b"hello world!!".len());
+            if decoded != b"hello world!!" {
+                changed += 1;
+            }
+        }
+        assert!(changed > 0);
+
+        // Only scanning for hex leaves nothing to mutate in this input
+        let mut me = MutationEngine::new().set_region_encodings(&[RegionEncoding::Hex]);
+        me.set_test_case(&data);
+        assert!(me.encoded_region().is_err());
+        assert_eq!(me.test_case.data, data);
     }
 
     #[test]
-    fn test_cross_over() {
-        // Seems to sometimes due to bad PRNG luck produce an identical test_case.
-        // May need to revist later, for now speed > slowing down constraints.
-        run(
-            MutationEngine::cross_over,
-            TestCondition::GeneralErrorChecker,
-        );
+    fn test_shuffle_records() {
+        let data = b"header\nalpha\nbeta\ngamma\ndelta\n".to_vec();
+        let mut lines = data.split(|b| *b == b'\n').collect::<Vec<_>>();
+        lines.sort_unstable();
+        let mut me = MutationEngine::new().set_generator_seed(0xdeadbeefcafebabe);
+        let mut reordered = 0;
+        for _ in 0..100 {
+            me.set_test_case(&data);
+            me.shuffle_records().unwrap();
+            let tc = &me.test_case.data;
+            assert_eq!(tc.len(), data.len());
+            assert!(tc.ends_with(b"\n"));
+            let mut new_lines = tc.split(|b| *b == b'\n').collect::<Vec<_>>();
+            new_lines.sort_unstable();
+            assert_eq!(new_lines, lines);
+            if *tc != data {
+                reordered += 1;
+            }
+        }
+        assert!(reordered > 50);
+
+        let mut me = me.set_record_delimiter(b", ");
+        me.set_test_case(b"[1, 22, 333]");
+        me.shuffle_records().unwrap();
+        let tc = String::from_utf8(me.test_case.data.clone()).unwrap();
+        let mut elements = tc.split(", ").collect::<Vec<_>>();
+        elements.sort_unstable();
+        assert_eq!(elements, ["22", "333]", "[1"]);
+
+        me.set_test_case(b"single record");
+        assert!(me.shuffle_records().is_err());
     }
 
     #[test]
-    fn test_splice() {
-        // On bad rolls when two small test cases are selected, the splice may not
-        // actually change the test case.
-        run(MutationEngine::splice, TestCondition::GeneralErrorChecker);
+    fn test_inject_dangerous() {
+        let data = b"GET /index.html HTTP/1.1".to_vec();
+        let mut me = MutationEngine::new()
+            .set_generator_seed(0xdeadbeefcafebabe)
+            .set_extra_dangerous_tokens(&["$(id)"]);
+        assert!(me
+            .mutators
+            .iter()
+            .any(|m| matches!(m, Mutators::Standard(StandardMutators::InjectDangerous))));
+        let mut seen = HashSet::new();
+        for _ in 0..1000 {
+            me.set_test_case(&data);
+            me.inject_dangerous().unwrap();
+            let tc = &me.test_case.data;
+            assert_eq!(me.test_case.size, tc.len());
+            let token = DANGEROUS_TOKENS
+                .iter()
+                .copied()
+                .chain([b"$(id)".as_slice()])
+                .find(|t| {
+                    tc.len() == data.len() + t.len()
+                        && (0..=data.len()).any(|i| {
+                            tc[..i] == data[..i]
+                                && &tc[i..i + t.len()] == *t
+                                && tc[i + t.len()..] == data[i..]
+                        })
+                });
+            seen.insert(token.unwrap());
+        }
+        assert_eq!(seen.len(), DANGEROUS_TOKENS.len() + 1);
+
+        // Non-printable tokens are never injected in printable mode
+        let mut me = me.set_printable_mode(PrintableMode::Bytewise);
+        for _ in 0..1000 {
+            me.set_test_case(&data);
+            me.inject_dangerous().unwrap();
+            assert!(!me.test_case.data.contains(&0));
+        }
     }
 
     #[test]
-    fn test_ni() {
-        let corpus: Arc<Vec<Vec<u8>>> = Arc::new(vec!["
-<!DOCTYPE html>
-  <html>
-    <body>
-      <h1>My 1337 Heading</h1>
-      <p>My first paragraph.</p>
-    </body>
-  </html>"
-            .as_bytes()
-            .to_vec()]);
+    fn test_set_token_dicts() {
+        let engine =
+            MutationEngine::new().set_token_dicts(&["dicts/test.dict", "dicts/mixed.dict"]);
+        let tokens: Vec<&[u8]> = engine
+            .user_token_dict
+            .iter()
+            .map(|(_, token)| token.as_slice())
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                &b"foo"[..],
+                b"foobar",
+                b"GET",
+                b"POST",
+                b"HTTP/1.1",
+                b"\x7fELF\x00",
+                b"say \"hi\"\\",
+                b"raw token",
+            ]
+        );
+        let dict_mutators = engine
+            .mutators
+            .iter()
+            .filter(|m| matches!(m, Mutators::Standard(StandardMutators::AddWordFromDict)))
+            .count();
+        assert_eq!(dict_mutators, 1);
 
-        let mut me = MutationEngine::new().set_corpus(corpus.clone());
-        me = me.set_random_test_case();
-        let _ = me.ni();
-        assert_ne!(corpus[0], me.test_case.data);
+        assert_eq!(parse_dict_line(b"\"\\x4\""), Some((0, b"\\x4".to_vec())));
+        assert_eq!(parse_dict_line(b"\"\\xzz\""), Some((0, b"\\xzz".to_vec())));
+        assert_eq!(parse_dict_line(b"\""), Some((0, b"\"".to_vec())));
+        // Whitespace is kept in plain tokens, only the line terminator is stripped
+        assert_eq!(parse_dict_line(b"   "), Some((0, b"   ".to_vec())));
+        assert_eq!(parse_dict_line(b" a b \r"), Some((0, b" a b ".to_vec())));
+        assert_eq!(parse_dict_line(b""), None);
+        assert_eq!(parse_dict_line(b"\r"), None);
+        assert_eq!(parse_dict_line(b"\"\""), None);
+        assert_eq!(parse_dict_line(b"empty=\"\""), None);
+        assert_eq!(parse_dict_line(b"kw@7=\"x\""), Some((7, b"x".to_vec())));
+        assert_eq!(parse_dict_line(b"kw@x=\"x\""), Some((0, b"x".to_vec())));
+    }
+
+    #[test]
+    fn test_token_dict_levels() {
+        // Tokens may be inserted reversed, so they are compared by their sorted bytes
+        let sorted = |token: &[u8]| {
+            let mut token = token.to_vec();
+            token.sort_unstable();
+            token
+        };
+        let picked = |passes: usize| {
+            let mut engine = MutationEngine::new()
+                .set_token_dict("dicts/levels.dict")
+                .set_mutation_passes(passes);
+            let mut seen = BTreeSet::new();
+            for _ in 0..500 {
+                engine.set_test_case(&[b'.'; 16]);
+                engine.add_word_from_dict().unwrap();
+                let token: Vec<u8> = engine
+                    .test_case
+                    .data
+                    .iter()
+                    .filter(|b| **b != b'.')
+                    .copied()
+                    .collect();
+                seen.insert(sorted(&token));
+            }
+            seen
+        };
+        let words = |w: &[&str]| w.iter().map(|w| sorted(w.as_bytes())).collect();
+        assert_eq!(picked(1), words(&["plain", "one"]));
+        assert_eq!(picked(3), words(&["plain", "one", "three"]));
+        assert_eq!(picked(9), words(&["plain", "one", "three", "nine"]));
     }
 
     #[test]
-    #[ignore]
-    fn test_torc() {}
+    fn test_torc() {
+        let corpus: Vec<Vec<u8>> = [
+            "GET /index.html HTTP/1.1\r\nHost: a\r\n\r\n",
+            "POST /login HTTP/1.1\r\nHost: b\r\n\r\n",
+            "GET /about HTTP/1.1\r\n\r\n",
+            "POST /form HTTP/1.1\r\n\r\n",
+            "\x00\x01\x02GET /",
+        ]
+        .iter()
+        .map(|s| s.as_bytes().to_vec())
+        .collect();
+        let mut engine = MutationEngine::new()
+            .set_corpus(corpus.into())
+            .set_mutators_by_name(&["add_word_from_torc"])
+            .unwrap();
+        assert!(engine.add_word_from_torc().is_err());
+
+        engine.extract_torc_from_corpus(4, 8);
+        let tokens = engine.torc_tokens();
+        for token in ["GET /", "POST /", "HTTP/1.1", "Host: "] {
+            assert!(
+                tokens.contains(&token.trim().as_bytes().to_vec()),
+                "{token} missing"
+            );
+        }
+        assert!(!tokens.contains(&b"HTTP/1.".to_vec()));
+        assert!(tokens.iter().all(|t| t.len() >= 4));
+
+        engine.extract_torc_from_corpus(4, 2);
+        assert_eq!(engine.torc_tokens().len(), 2);
+        engine.set_test_case(&[0u8; 64]);
+        assert!(engine.add_word_from_torc().is_ok());
+    }
 }
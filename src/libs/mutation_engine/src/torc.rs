@@ -0,0 +1,81 @@
+// Extraction of a TORC token dictionary from the corpus. Tokens are substrings of printable ASCII
+// runs that are shared by multiple corpus entries, such as keywords of a text based protocol.
+
+use std::collections::{HashMap, HashSet};
+
+// Maximum length of an extracted token. Longer shared substrings are cut into tokens of this size
+const TORC_MAX_TOKEN_LEN: usize = 32;
+// Number of leading bytes of each corpus entry that are scanned for tokens
+const TORC_MAX_SCAN_LEN: usize = 4096;
+
+/// Returns up to `max_tokens` distinct tokens of at least `min_len` bytes that occur in at least
+/// two corpus entries. Only substrings that cannot be extended by a byte without occurring in
+/// fewer entries are considered, so e.g. `HTTP/1.` is dropped in favor of `HTTP/1.1`. Surrounding
+/// whitespace is trimmed. Tokens shared by more entries and longer tokens are preferred.
+pub(crate) fn extract_tokens(
+    corpus: &[Vec<u8>],
+    min_len: usize,
+    max_tokens: usize,
+) -> Vec<Vec<u8>> {
+    let min_len = min_len.max(1);
+    // Number of entries each substring occurs in, with the index of the last entry it was seen in
+    let mut freq: HashMap<&[u8], (usize, usize)> = HashMap::new();
+    for (idx, entry) in corpus.iter().enumerate() {
+        let scanned = &entry[..entry.len().min(TORC_MAX_SCAN_LEN)];
+        for run in scanned.split(|b| !is_printable(*b)) {
+            for start in 0..run.len() {
+                let longest = (run.len() - start).min(TORC_MAX_TOKEN_LEN);
+                for len in min_len..=longest {
+                    let (count, last) = freq
+                        .entry(&run[start..start + len])
+                        .or_insert((0, usize::MAX));
+                    if *last != idx {
+                        *count += 1;
+                        *last = idx;
+                    }
+                }
+            }
+        }
+    }
+
+    // A substring is not closed if extending it by a byte on either side yields a substring that
+    // occurs in as many entries
+    let mut not_closed: HashSet<&[u8]> = HashSet::new();
+    for (&sub, &(count, _)) in &freq {
+        if sub.len() > min_len {
+            for parent in [&sub[1..], &sub[..sub.len() - 1]] {
+                if freq[parent].0 == count {
+                    not_closed.insert(parent);
+                }
+            }
+        }
+    }
+
+    let mut tokens: HashMap<&[u8], usize> = HashMap::new();
+    for (&sub, &(count, _)) in &freq {
+        if count < 2 || not_closed.contains(sub) {
+            continue;
+        }
+        let token = sub.trim_ascii();
+        if token.len() >= min_len {
+            let best = tokens.entry(token).or_default();
+            *best = (*best).max(count);
+        }
+    }
+
+    let mut tokens: Vec<(&[u8], usize)> = tokens.into_iter().collect();
+    tokens.sort_by(|(a, a_count), (b, b_count)| {
+        (b_count * b.len())
+            .cmp(&(a_count * a.len()))
+            .then_with(|| a.cmp(b))
+    });
+    tokens
+        .into_iter()
+        .take(max_tokens)
+        .map(|(token, _)| token.to_vec())
+        .collect()
+}
+
+const fn is_printable(b: u8) -> bool {
+    matches!(b, 0x20..=0x7e)
+}
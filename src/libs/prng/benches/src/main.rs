@@ -1,5 +1,7 @@
 use image::{ImageBuffer, Rgb};
+use prng::chacha::ChaCha20;
 use prng::lehmer::Lehmer64;
+use prng::pcg::Pcg64;
 use prng::romuduojr::RomuDuoJr;
 use prng::romutrio::RomuTrio;
 use prng::shishua::ShiShua;
@@ -119,7 +121,7 @@ fn test_prng(prng_info: &mut PRNGInfo) {
 fn main() {
     let args = env::args().count();
     // Define an array of PRNGs to test
-    let mut prngs: [PRNGInfo; 9] = [
+    let mut prngs: [PRNGInfo; 11] = [
         PRNGInfo {
             name: "Xorshift64",
             rng: Rng::new(Generator::Xorshift64(Xorshift64::new(SEED))),
@@ -156,6 +158,18 @@ fn main() {
             total_duration: Duration::default(),
             obsv_freqs: vec![0; NUM_BINS],
         },
+        PRNGInfo {
+            name: "ChaCha20",
+            rng: Rng::new(Generator::ChaCha20(ChaCha20::new(SEED))),
+            total_duration: Duration::default(),
+            obsv_freqs: vec![0; NUM_BINS],
+        },
+        PRNGInfo {
+            name: "Pcg64",
+            rng: Rng::new(Generator::Pcg64(Pcg64::new(SEED))),
+            total_duration: Duration::default(),
+            obsv_freqs: vec![0; NUM_BINS],
+        },
         PRNGInfo {
             name: "Wyhash64",
             rng: Rng::new(Generator::Wyhash64(Wyhash64::new(SEED))),
@@ -0,0 +1,193 @@
+// Bulk byte operations for large buffers. By default the buffers are processed in chunks of
+// `usize` bytes. With the `simd` feature enabled XOR and negation operate on 32 byte vectors.
+// Any remainder that does not fill a whole chunk is handled byte by byte.
+#[cfg(feature = "simd")]
+use packed_simd_2::u8x32;
+
+#[cfg(feature = "simd")]
+const CHUNK: usize = 32;
+#[cfg(not(feature = "simd"))]
+const CHUNK: usize = std::mem::size_of::<usize>();
+
+/// Fills `buf` with random bytes drawn from `next`, using all bytes of each drawn `usize`.
+///
+/// # Arguments
+///
+/// * `buf` - The buffer to fill.
+/// * `next` - A closure returning a random `usize`, e.g. `|| prng.rand()`.
+///
+/// # Example
+///
+/// ```
+/// use prng::bulk;
+///
+/// let mut buf = [0u8; 10];
+/// bulk::fill(&mut buf, || usize::MAX);
+/// assert_eq!(buf, [0xff; 10]);
+/// ```
+#[inline]
+pub fn fill<F: FnMut() -> usize>(buf: &mut [u8], mut next: F) {
+    let mut chunks = buf.chunks_exact_mut(std::mem::size_of::<usize>());
+    for chunk in &mut chunks {
+        chunk.copy_from_slice(&next().to_le_bytes());
+    }
+    let rem = chunks.into_remainder();
+    if !rem.is_empty() {
+        let len = rem.len();
+        rem.copy_from_slice(&next().to_le_bytes()[..len]);
+    }
+}
+
+/// XORs `buf` with the repeating `key`. Keys whose length evenly divides the chunk size
+/// (e.g. 1, 2, 4 or 8 bytes) take the fast path, all others are processed byte by byte.
+///
+/// # Arguments
+///
+/// * `buf` - The buffer to modify in place.
+/// * `key` - The key to XOR `buf` with. An empty key leaves `buf` untouched.
+///
+/// # Example
+///
+/// ```
+/// use prng::bulk;
+///
+/// let mut buf = [0x00, 0xff, 0x00, 0xff, 0x00];
+/// bulk::xor_key(&mut buf, &[0x0f, 0xf0]);
+/// assert_eq!(buf, [0x0f, 0x0f, 0x0f, 0x0f, 0x0f]);
+/// ```
+#[inline]
+pub fn xor_key(buf: &mut [u8], key: &[u8]) {
+    if key.is_empty() {
+        return;
+    }
+    if !CHUNK.is_multiple_of(key.len()) {
+        xor_key_scalar(buf, key);
+        return;
+    }
+    let mut key_chunk = [0u8; CHUNK];
+    for (i, k) in key_chunk.iter_mut().enumerate() {
+        *k = key[i % key.len()];
+    }
+    let mut chunks = buf.chunks_exact_mut(CHUNK);
+    for chunk in &mut chunks {
+        xor_chunk(chunk, &key_chunk);
+    }
+    // Chunks are a multiple of the key length, so the remainder starts at key offset 0
+    xor_key_scalar(chunks.into_remainder(), key);
+}
+
+/// Negates every byte in `buf`.
+///
+/// # Arguments
+///
+/// * `buf` - The buffer to modify in place.
+///
+/// # Example
+///
+/// ```
+/// use prng::bulk;
+///
+/// let mut buf = [0x00, 0xff, 0x0f];
+/// bulk::negate(&mut buf);
+/// assert_eq!(buf, [0xff, 0x00, 0xf0]);
+/// ```
+#[inline]
+pub fn negate(buf: &mut [u8]) {
+    xor_key(buf, &[0xff]);
+}
+
+fn xor_key_scalar(buf: &mut [u8], key: &[u8]) {
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b ^= key[i % key.len()];
+    }
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn xor_chunk(chunk: &mut [u8], key_chunk: &[u8; CHUNK]) {
+    let v = u8x32::from_slice_unaligned(chunk) ^ u8x32::from_slice_unaligned(key_chunk);
+    v.write_to_slice_unaligned(chunk);
+}
+
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn xor_chunk(chunk: &mut [u8], key_chunk: &[u8; CHUNK]) {
+    let v = usize::from_ne_bytes(chunk.try_into().unwrap()) ^ usize::from_ne_bytes(*key_chunk);
+    chunk.copy_from_slice(&v.to_ne_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xorshift::Xorshift64;
+    use crate::{Generator, Rng};
+    use test::{black_box, Bencher};
+
+    const SEED: usize = 0xb3959f04cb8af237;
+    const SIZE: usize = 100 * 1024;
+
+    fn data(size: usize) -> Vec<u8> {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        prng.rand_byte_vec(size)
+    }
+
+    #[test]
+    fn test_xor_key_matches_scalar() {
+        for key_len in 1..=33 {
+            let key: Vec<u8> = (1..=key_len as u8).collect();
+            for size in [0, 1, 7, 8, 31, 32, 33, 1000] {
+                let mut fast = data(size);
+                let mut slow = fast.clone();
+                xor_key(&mut fast, &key);
+                xor_key_scalar(&mut slow, &key);
+                assert_eq!(fast, slow, "key_len: {key_len}, size: {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_negate() {
+        let orig = data(1027);
+        let mut buf = orig.clone();
+        negate(&mut buf);
+        assert!(orig.iter().zip(&buf).all(|(a, b)| *b == !a));
+    }
+
+    #[bench]
+    pub fn bulk_fill_bench(b: &mut Bencher) {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        let mut buf = vec![0u8; SIZE];
+        b.iter(|| fill(black_box(&mut buf), || prng.rand()));
+    }
+
+    #[bench]
+    pub fn bytewise_fill_bench(b: &mut Bencher) {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        let mut buf = vec![0u8; SIZE];
+        b.iter(|| black_box(&mut buf).fill_with(|| prng.rand_byte()));
+    }
+
+    #[bench]
+    pub fn bulk_xor_key_bench(b: &mut Bencher) {
+        let mut buf = data(SIZE);
+        b.iter(|| xor_key(black_box(&mut buf), &[0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    #[bench]
+    pub fn bytewise_xor_key_bench(b: &mut Bencher) {
+        let mut buf = data(SIZE);
+        b.iter(|| xor_key_scalar(black_box(&mut buf), &[0xde, 0xad, 0xbe, 0xef]));
+    }
+
+    #[bench]
+    pub fn bulk_negate_bench(b: &mut Bencher) {
+        let mut buf = data(SIZE);
+        b.iter(|| negate(black_box(&mut buf)));
+    }
+
+    #[bench]
+    pub fn bytewise_negate_bench(b: &mut Bencher) {
+        let mut buf = data(SIZE);
+        b.iter(|| black_box(&mut buf).iter_mut().for_each(|x| *x = !*x));
+    }
+}
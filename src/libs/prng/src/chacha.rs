@@ -0,0 +1,173 @@
+// ChaCha20 stream cipher used as a cryptographically secure generator, for input fields such
+// as nonces or keys that should look like real entropy. It is a lot slower than the other
+// generators, so only pick it when the quality of the stream matters.
+use crate::get_seeds;
+use crate::seed::Seeds;
+use crate::{state_words, GeneratorTrait};
+
+// "expand 32-byte k"
+const SIGMA: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+// Number of `u64` outputs per 64 byte block
+const BLOCK_WORDS: usize = 8;
+
+#[inline(always)]
+const fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+/// Computes a single ChaCha20 block as specified in RFC 8439, section 2.3.
+///
+/// # Arguments
+///
+/// * `key` - The 256-bit key as little endian words.
+/// * `counter` - The block counter.
+/// * `nonce` - The 96-bit nonce as little endian words.
+///
+/// # Returns
+///
+/// The 16 little endian words of the serialized block.
+pub fn chacha20_block(key: &[u32; 8], counter: u32, nonce: &[u32; 3]) -> [u32; 16] {
+    let mut init = [0u32; 16];
+    init[..4].copy_from_slice(&SIGMA);
+    init[4..12].copy_from_slice(key);
+    init[12] = counter;
+    init[13..].copy_from_slice(nonce);
+
+    let mut state = init;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+    for (s, i) in state.iter_mut().zip(init) {
+        *s = s.wrapping_add(i);
+    }
+    state
+}
+
+/// Generator returning the ChaCha20 keystream. The key is derived from the seed and the nonce
+/// is zero, with the upper half of the 64-bit block counter stored in the first nonce word.
+#[derive(Debug, Clone, Copy)]
+pub struct ChaCha20 {
+    key: [u32; 8],
+    // Counter of the block currently held in `block`
+    counter: u64,
+    block: [u32; 16],
+    // Index of the next `u64` to return from `block`
+    idx: usize,
+}
+
+impl GeneratorTrait for ChaCha20 {
+    #[inline]
+    fn rand(&mut self) -> usize {
+        if self.idx == BLOCK_WORDS {
+            self.counter = self.counter.wrapping_add(1);
+            self.refill();
+            self.idx = 0;
+        }
+        let lo = self.block[2 * self.idx];
+        let hi = self.block[2 * self.idx + 1];
+        self.idx += 1;
+        (u64::from(hi) << 32 | u64::from(lo)) as usize
+    }
+
+    fn set_seed(&mut self, seed: usize) {
+        *self = Self::new(seed);
+    }
+
+    fn get_state(&self) -> Vec<u64> {
+        self.key
+            .chunks_exact(2)
+            .map(|w| u64::from(w[1]) << 32 | u64::from(w[0]))
+            .chain([self.counter, self.idx as u64])
+            .collect()
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let words: [u64; 6] = state_words(state);
+        for (k, w) in self.key.chunks_exact_mut(2).zip(words) {
+            k[0] = w as u32;
+            k[1] = (w >> 32) as u32;
+        }
+        self.counter = words[4];
+        self.idx = words[5] as usize;
+        self.refill();
+    }
+}
+
+impl ChaCha20 {
+    pub fn new(seed: usize) -> Self {
+        let seeds: Seeds = get_seeds!(seed, 4);
+        let mut key = [0u32; 8];
+        for (k, s) in key.chunks_exact_mut(2).zip([
+            seeds.state_w,
+            seeds.state_x,
+            seeds.state_y,
+            seeds.state_z,
+        ]) {
+            k[0] = s as u32;
+            k[1] = (s as u64 >> 32) as u32;
+        }
+        let mut ret = Self {
+            key,
+            counter: 0,
+            block: [0; 16],
+            idx: 0,
+        };
+        ret.refill();
+        ret
+    }
+
+    // Computes the block for the current counter
+    fn refill(&mut self) {
+        let nonce = [(self.counter >> 32) as u32, 0, 0];
+        self.block = chacha20_block(&self.key, self.counter as u32, &nonce);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chacha20_block_rfc8439() {
+        // Test vector from RFC 8439, section 2.3.2
+        let key: [u32; 8] = core::array::from_fn(|i| {
+            u32::from_le_bytes(core::array::from_fn(|j| (4 * i + j) as u8))
+        });
+        let nonce = [0x0900_0000, 0x4a00_0000, 0x0000_0000];
+        assert_eq!(
+            chacha20_block(&key, 1, &nonce),
+            [
+                0xe4e7_f110,
+                0x1559_3bd1,
+                0x1fdd_0f50,
+                0xc471_20a3,
+                0xc7f4_d1c7,
+                0x0368_c033,
+                0x9aaa_2204,
+                0x4e6c_d4c3,
+                0x4664_82d2,
+                0x09aa_9f07,
+                0x05d7_c214,
+                0xa202_8bd9,
+                0xd19c_12b5,
+                0xb94e_16de,
+                0xe883_d0cb,
+                0x4e3c_50a2,
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Lehmer64 {
@@ -18,6 +18,15 @@ impl GeneratorTrait for Lehmer64 {
         let seeds: Seeds = get_seeds!(seed, 1);
         self.state = u128::from(seeds.state_w as u64);
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![self.state as u64, (self.state >> 64) as u64]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [lo, hi] = state_words(state);
+        self.state = (u128::from(hi) << 64) | u128::from(lo);
+    }
 }
 
 impl Lehmer64 {
@@ -1,9 +1,14 @@
 #![feature(test)]
 extern crate test;
 use clap::ValueEnum;
+use core::fmt;
 use core::ops::Deref;
+use core::str::FromStr;
 
+pub mod bulk;
+pub mod chacha;
 pub mod lehmer;
+pub mod pcg;
 pub mod romuduojr;
 pub mod romutrio;
 pub mod seed;
@@ -13,7 +18,9 @@ pub mod wyhash;
 pub mod xorshift;
 pub mod xorshiro128ss;
 pub mod xorshiro256ss;
+use chacha::ChaCha20;
 use lehmer::Lehmer64;
+use pcg::Pcg64;
 use romuduojr::RomuDuoJr;
 use romutrio::RomuTrio;
 use shishua::ShiShua;
@@ -34,6 +41,151 @@ const SPECIAL_CHAR: [char; 30] = [
 pub trait GeneratorTrait {
     fn rand(&mut self) -> usize;
     fn set_seed(&mut self, seed: usize);
+
+    /// Returns the complete internal state, which `set_state` restores exactly. Unlike a seed,
+    /// the state captures a generator mid-run. Generators that do not support snapshots return
+    /// an empty state.
+    fn get_state(&self) -> Vec<u64> {
+        Vec::new()
+    }
+
+    /// Restores a state previously returned by `get_state` of the same kind of generator.
+    /// Generators that do not support snapshots ignore the state.
+    ///
+    /// # Panics
+    ///
+    /// If the number of words in `state` does not match the generator.
+    fn set_state(&mut self, _state: &[u64]) {}
+
+    /// Advances the generator by a fixed, large number of steps, e.g. 2^64 calls to `rand`, to
+    /// split one sequence into non-overlapping streams. Generators that do not support jumping
+    /// are left untouched.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the generator jumped, `false` if jumping is not supported.
+    fn jump(&mut self) -> bool {
+        false
+    }
+
+    /// Fills `buf` with the little endian bytes of consecutive `rand` calls. The last call is
+    /// truncated if `buf` is not a multiple of its size. Generators with an internal output
+    /// buffer may override this to copy from it directly, as long as the bytes stay the same.
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        bulk::fill(buf, || self.rand());
+    }
+}
+
+/// Converts a state passed to `GeneratorTrait::set_state` into a fixed number of words.
+pub(crate) fn state_words<const N: usize>(state: &[u64]) -> [u64; N] {
+    state.try_into().unwrap_or_else(|_| {
+        panic!(
+            "Expected a generator state of {N} words, got {}",
+            state.len()
+        )
+    })
+}
+
+/// Integer types `Rng::rand_range` and friends can draw from. Values are mapped to a `u64` that
+/// preserves their order, so the span of any range fits into a `u64`, including ranges of
+/// signed integers that cross 0.
+pub trait RangeBound: Copy + PartialOrd + fmt::Display {
+    /// Maps the value to a `u64`, such that `a < b` implies `a.to_ordered() < b.to_ordered()`.
+    fn to_ordered(self) -> u64;
+    /// Reverses `to_ordered`.
+    fn from_ordered(val: u64) -> Self;
+}
+
+macro_rules! impl_range_bound_unsigned {
+    ($($t:ty),*) => {$(
+        impl RangeBound for $t {
+            #[inline]
+            fn to_ordered(self) -> u64 {
+                self as u64
+            }
+
+            #[inline]
+            fn from_ordered(val: u64) -> Self {
+                val as Self
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_range_bound_signed {
+    ($($t:ty),*) => {$(
+        impl RangeBound for $t {
+            // Flipping the sign bit moves negative values below positive ones
+            #[inline]
+            fn to_ordered(self) -> u64 {
+                (self as i64 as u64) ^ (1 << 63)
+            }
+
+            #[inline]
+            fn from_ordered(val: u64) -> Self {
+                (val ^ (1 << 63)) as i64 as Self
+            }
+        }
+    )*};
+}
+
+impl_range_bound_unsigned!(u8, u16, u32, u64, usize);
+impl_range_bound_signed!(i8, i16, i32, i64, isize);
+
+/// A user supplied generator that can drive a `Generator::External`, e.g. a hardware RNG or a
+/// source replaying a pre-recorded sequence. It is implemented for every `GeneratorTrait` that
+/// is `Clone + Send + Sync + 'static`. Cloning a `Generator::External` clones the underlying
+/// generator, so sources that must not be duplicated should share their state, e.g. via an `Arc`.
+///
+/// # Example
+///
+/// ```
+/// use prng::{Generator, GeneratorTrait, Rng};
+///
+/// #[derive(Clone)]
+/// struct Replay {
+///     values: Vec<usize>,
+///     pos: usize,
+/// }
+///
+/// impl GeneratorTrait for Replay {
+///     fn rand(&mut self) -> usize {
+///         let val = self.values[self.pos % self.values.len()];
+///         self.pos += 1;
+///         val
+///     }
+///
+///     fn set_seed(&mut self, _seed: usize) {
+///         self.pos = 0;
+///     }
+/// }
+///
+/// let replay = Replay { values: vec![1, 2, 3], pos: 0 };
+/// let mut prng = Rng::new(Generator::External(Box::new(replay)));
+/// assert_eq!((prng.rand(), prng.rand()), (1, 2));
+/// let mut copy = prng.clone();
+/// assert_eq!((prng.rand(), copy.rand()), (3, 3));
+/// ```
+pub trait ExternalGenerator: GeneratorTrait + Send + Sync {
+    fn clone_box(&self) -> Box<dyn ExternalGenerator>;
+}
+
+impl<T: GeneratorTrait + Clone + Send + Sync + 'static> ExternalGenerator for T {
+    fn clone_box(&self) -> Box<dyn ExternalGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn ExternalGenerator> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl fmt::Debug for dyn ExternalGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ExternalGenerator")
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +201,9 @@ pub enum Generator {
     Lehmer64(Lehmer64),
     Wyhash64(Wyhash64),
     ShiShua(ShiShua),
+    ChaCha20(ChaCha20),
+    Pcg64(Pcg64),
+    External(Box<dyn ExternalGenerator>),
 }
 
 impl Default for Generator {
@@ -68,6 +223,11 @@ pub enum Generators {
     Lehmer64,
     Wyhash64,
     Shishua,
+    Chacha20,
+    Pcg64,
+    // A user supplied `Generator::External`, which cannot be selected by name
+    #[value(skip)]
+    External,
 }
 
 impl Default for Generators {
@@ -76,6 +236,43 @@ impl Default for Generators {
     }
 }
 
+impl Generators {
+    /// All generators that can be selected by name, i.e., every variant except `External`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::Generators;
+    ///
+    /// assert!(Generators::all().contains(&Generators::Chacha20));
+    /// assert!(!Generators::all().contains(&Generators::External));
+    /// ```
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Xorshift64,
+            Self::Romuduojr,
+            Self::Romutrio,
+            Self::Splitmix64,
+            Self::Xorshiro128ss,
+            Self::Xorshiro256ss,
+            Self::Lehmer64,
+            Self::Wyhash64,
+            Self::Shishua,
+            Self::Chacha20,
+            Self::Pcg64,
+        ]
+    }
+}
+
+impl FromStr for Generators {
+    type Err = String;
+
+    /// Parses a generator from its name, ignoring the case, e.g. `romuduojr` or `RomuDuoJr`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s.trim(), true).map_err(|_| format!("Unknown generator: {s}"))
+    }
+}
+
 // If we ever add more methods / more generators and find this too boilerplatey to write,
 // then we should try to look into the `enum_dispatch` crate.
 impl GeneratorTrait for Generator {
@@ -90,6 +287,9 @@ impl GeneratorTrait for Generator {
             Self::Lehmer64(g) => g.rand(),
             Self::Wyhash64(g) => g.rand(),
             Self::ShiShua(g) => g.rand(),
+            Self::ChaCha20(g) => g.rand(),
+            Self::Pcg64(g) => g.rand(),
+            Self::External(g) => g.rand(),
         }
     }
 
@@ -104,6 +304,77 @@ impl GeneratorTrait for Generator {
             Self::Lehmer64(g) => g.set_seed(seed),
             Self::Wyhash64(g) => g.set_seed(seed),
             Self::ShiShua(g) => g.set_seed(seed),
+            Self::ChaCha20(g) => g.set_seed(seed),
+            Self::Pcg64(g) => g.set_seed(seed),
+            Self::External(g) => g.set_seed(seed),
+        }
+    }
+
+    fn get_state(&self) -> Vec<u64> {
+        match self {
+            Self::Xorshift64(g) => g.get_state(),
+            Self::RomuDuoJr(g) => g.get_state(),
+            Self::RomuTrio(g) => g.get_state(),
+            Self::SplitMix64(g) => g.get_state(),
+            Self::XorShiro128ss(g) => g.get_state(),
+            Self::XorShiro256ss(g) => g.get_state(),
+            Self::Lehmer64(g) => g.get_state(),
+            Self::Wyhash64(g) => g.get_state(),
+            Self::ShiShua(g) => g.get_state(),
+            Self::ChaCha20(g) => g.get_state(),
+            Self::Pcg64(g) => g.get_state(),
+            Self::External(g) => g.get_state(),
+        }
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        match self {
+            Self::Xorshift64(g) => g.set_state(state),
+            Self::RomuDuoJr(g) => g.set_state(state),
+            Self::RomuTrio(g) => g.set_state(state),
+            Self::SplitMix64(g) => g.set_state(state),
+            Self::XorShiro128ss(g) => g.set_state(state),
+            Self::XorShiro256ss(g) => g.set_state(state),
+            Self::Lehmer64(g) => g.set_state(state),
+            Self::Wyhash64(g) => g.set_state(state),
+            Self::ShiShua(g) => g.set_state(state),
+            Self::ChaCha20(g) => g.set_state(state),
+            Self::Pcg64(g) => g.set_state(state),
+            Self::External(g) => g.set_state(state),
+        }
+    }
+
+    fn jump(&mut self) -> bool {
+        match self {
+            Self::Xorshift64(g) => g.jump(),
+            Self::RomuDuoJr(g) => g.jump(),
+            Self::RomuTrio(g) => g.jump(),
+            Self::SplitMix64(g) => g.jump(),
+            Self::XorShiro128ss(g) => g.jump(),
+            Self::XorShiro256ss(g) => g.jump(),
+            Self::Lehmer64(g) => g.jump(),
+            Self::Wyhash64(g) => g.jump(),
+            Self::ShiShua(g) => g.jump(),
+            Self::ChaCha20(g) => g.jump(),
+            Self::Pcg64(g) => g.jump(),
+            Self::External(g) => g.jump(),
+        }
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        match self {
+            Self::Xorshift64(g) => g.fill_bytes(buf),
+            Self::RomuDuoJr(g) => g.fill_bytes(buf),
+            Self::RomuTrio(g) => g.fill_bytes(buf),
+            Self::SplitMix64(g) => g.fill_bytes(buf),
+            Self::XorShiro128ss(g) => g.fill_bytes(buf),
+            Self::XorShiro256ss(g) => g.fill_bytes(buf),
+            Self::Lehmer64(g) => g.fill_bytes(buf),
+            Self::Wyhash64(g) => g.fill_bytes(buf),
+            Self::ShiShua(g) => g.fill_bytes(buf),
+            Self::ChaCha20(g) => g.fill_bytes(buf),
+            Self::Pcg64(g) => g.fill_bytes(buf),
+            Self::External(g) => g.fill_bytes(buf),
         }
     }
 }
@@ -138,6 +409,65 @@ where
         self.generator.set_seed(seed);
     }
 
+    /// Takes a snapshot of the internal state of the generator, e.g. to replay a run from a
+    /// point in the middle later on.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<u64>` holding the state, to be passed to `restore`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::romutrio::RomuTrio;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::RomuTrio(RomuTrio::new(0x1337)));
+    ///
+    /// let _ = prng.rand();
+    /// let snapshot = prng.snapshot();
+    /// let a = prng.rand();
+    /// prng.restore(&snapshot);
+    /// assert_eq!(prng.rand(), a);
+    /// ```
+    pub fn snapshot(&self) -> Vec<u64> {
+        self.generator.get_state()
+    }
+
+    /// Restores the internal state of the generator from a `snapshot` of the same kind of
+    /// generator.
+    ///
+    /// # Panics
+    ///
+    /// If the snapshot was taken from a different kind of generator.
+    pub fn restore(&mut self, snapshot: &[u64]) {
+        self.generator.set_state(snapshot);
+    }
+
+    /// Jumps the generator ahead, so that e.g. worker threads sharing a seed draw from
+    /// independent streams. See `GeneratorTrait::jump`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the generator jumped, `false` if jumping is not supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::xorshiro128ss::XorShiro128ss;
+    /// use prng::xorshift::Xorshift64;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::XorShiro128ss(XorShiro128ss::new(0x1337)));
+    /// let mut other = prng.clone();
+    /// assert!(other.jump());
+    /// assert_ne!(prng.rand(), other.rand());
+    ///
+    /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0x1337)));
+    /// assert!(!prng.jump());
+    /// ```
+    pub fn jump(&mut self) -> bool {
+        self.generator.jump()
+    }
+
     /// Sets the generator that will be used to generate random numbers.
     pub fn set_generator(mut self, generator: G) -> Self {
         self.generator = generator;
@@ -247,8 +577,7 @@ where
     ///
     /// # Type Parameters
     ///
-    /// * `T`: The type of the output value, which must implement `Add`, `Sub`, `Rem`, `PartialOrd`,
-    ///   `PartialEq`, `Display`, `Copy`, and `From<usize>`.
+    /// * `T`: The integer type of the output value, see `RangeBound`.
     ///
     /// # Arguments
     ///
@@ -275,14 +604,7 @@ where
     #[inline]
     pub fn rand_exp<T>(&mut self, min: T, max: T) -> T
     where
-        T: core::ops::Add<Output = T>
-            + core::ops::Sub<Output = T>
-            + core::ops::Rem<Output = T>
-            + core::cmp::PartialOrd
-            + core::cmp::PartialEq
-            + core::fmt::Display
-            + Copy
-            + From<usize>,
+        T: RangeBound,
     {
         if !self.exponential {
             return self.rand_range(min, max);
@@ -424,17 +746,12 @@ where
     /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
     /// let num = prng.rand_range::<usize>(usize::MIN, usize::MAX);
     /// assert!(num < usize::MAX && num > usize::MIN);
+    /// let num = prng.rand_range(-100i64, 100);
+    /// assert!((-100..100).contains(&num));
     #[inline]
     pub fn rand_range<T>(&mut self, min: T, max: T) -> T
     where
-        T: core::ops::Add<Output = T>
-            + core::ops::Sub<Output = T>
-            + core::ops::Rem<Output = T>
-            + core::cmp::PartialOrd
-            + core::cmp::PartialEq
-            + core::fmt::Display
-            + Copy
-            + From<usize>,
+        T: RangeBound,
     {
         assert!(
             max >= min,
@@ -443,14 +760,59 @@ where
         if min == max {
             return min;
         }
-        min + T::from(self.rand()).rem(max - min)
+        let min_ord = min.to_ordered();
+        let span = max.to_ordered() - min_ord;
+        T::from_ordered(min_ord + self.rand() as u64 % span)
+    }
+
+    /// Generate a random `T` in the range [low, high].
+    ///
+    /// # Arguments
+    ///
+    /// * `low` - The lower bound of the range (inclusive).
+    /// * `high` - The upper bound of the range (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// A random `T` in the specified range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is less than `min`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::xorshift::Xorshift64;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+    /// let num = prng.rand_range_inclusive(-100i64, 100);
+    /// assert!((-100..=100).contains(&num));
+    /// let num = prng.rand_range_inclusive(i8::MIN, i8::MAX);
+    /// ```
+    #[inline]
+    pub fn rand_range_inclusive<T>(&mut self, min: T, max: T) -> T
+    where
+        T: RangeBound,
+    {
+        assert!(
+            max >= min,
+            "Failed bounds check in `rand_range_inclusive: max {max} < min {min}"
+        );
+        let min_ord = min.to_ordered();
+        let span = max.to_ordered() - min_ord;
+        let offset = match span.checked_add(1) {
+            Some(len) => self.rand() as u64 % len,
+            None => self.rand() as u64,
+        };
+        T::from_ordered(min_ord + offset)
     }
 
     /// Generate a random byte with the current generator.
     ///
     /// # Returns
     ///
-    /// A random `u8`.
+    /// A random `u8`, uniformly distributed over the full range `0..=255`.
     ///
     /// # Example
     ///
@@ -458,11 +820,11 @@ where
     /// use prng::xorshift::Xorshift64;
     /// use prng::{Generator, Rng};
     /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
-    /// let b = prng.rand_byte();
-    /// assert!(b < 255 && b > 0);
+    /// let b: u8 = prng.rand_byte();
+    /// ```
     #[inline]
     pub fn rand_byte(&mut self) -> u8 {
-        (self.rand() % 255) as u8
+        self.rand() as u8
     }
 
     /// Picks a random item from a given iterable `entries` of `T` items
@@ -541,6 +903,56 @@ where
         &entries[idx]
     }
 
+    /// Picks a random item from `entries`, where each item is chosen with a probability
+    /// proportional to its weight in `weights`. Items with a weight of 0 are never chosen.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T`: The type of the items in the slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries`: The slice containing the items to choose from.
+    /// * `weights`: The weight of each item in `entries`.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the randomly chosen item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entries` and `weights` differ in length or if the total weight is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::xorshift::Xorshift64;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+    ///
+    /// let choices = vec!['a', 'b', 'c'];
+    /// let random_pick = prng.pick_weighted(&choices, &[1, 0, 3]);
+    /// assert_ne!(random_pick, &'b');
+    /// ```
+    #[inline]
+    pub fn pick_weighted<'a, T>(&mut self, entries: &'a [T], weights: &[u32]) -> &'a T {
+        assert_eq!(
+            entries.len(),
+            weights.len(),
+            "Every entry needs exactly one weight"
+        );
+        let total: usize = weights.iter().map(|&w| w as usize).sum();
+        assert!(total > 0, "Cannot pick with a total weight of 0");
+        let mut draw = self.rand_range(0, total);
+        for (entry, &weight) in entries.iter().zip(weights) {
+            if draw < weight as usize {
+                return entry;
+            }
+            draw -= weight as usize;
+        }
+        unreachable!()
+    }
+
     /// Generates a random boolean value with equal probability of being `true` or `false`.
     ///
     /// # Returns
@@ -590,6 +1002,43 @@ where
         0 == self.rand_range(0, prob)
     }
 
+    /// Generates a random boolean value that is `true` with a probability of
+    /// `numerator / denominator`, e.g. `bool_ratio(3, 4)` for a 75% chance.
+    ///
+    /// # Arguments
+    ///
+    /// * `numerator`: The number of outcomes out of `denominator` that result in `true`.
+    /// * `denominator`: The total number of outcomes.
+    ///
+    /// # Returns
+    ///
+    /// A random boolean value with the specified probability of being `true`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `denominator` is `0` or less than `numerator`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::xorshift::Xorshift64;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0)));
+    ///
+    /// let random_bool = prng.bool_ratio(3, 4);
+    /// assert!(prng.bool_ratio(1, 1));
+    /// assert!(!prng.bool_ratio(0, 1));
+    /// ```
+    #[inline]
+    pub fn bool_ratio(&mut self, numerator: u32, denominator: u32) -> bool {
+        assert!(0 < denominator, "Denominator must be greater than 0");
+        assert!(
+            numerator <= denominator,
+            "Numerator {numerator} must not exceed denominator {denominator}"
+        );
+        self.rand_range(0, denominator) < numerator
+    }
+
     /// Generates a random byte vector of the specified size.
     ///
     /// # Arguments
@@ -613,10 +1062,39 @@ where
     #[inline]
     pub fn rand_byte_vec(&mut self, size: usize) -> Vec<u8> {
         let mut v = vec![0_u8; size];
-        v.fill_with(|| self.rand_byte());
+        self.rand_bytes_into(&mut v);
         v
     }
 
+    /// Fills an existing buffer with random bytes, without allocating. The bytes are the little
+    /// endian bytes of consecutive `rand` calls, which `ShiShua` copies straight out of its
+    /// output buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf`: The buffer to fill.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use prng::xorshift::Xorshift64;
+    /// use prng::{Generator, Rng};
+    /// let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(0x1337)));
+    /// let mut clone = prng.clone();
+    ///
+    /// let mut buf = [0u8; 13];
+    /// prng.rand_bytes_into(&mut buf);
+    /// let expected: Vec<u8> = std::iter::repeat_with(|| clone.rand().to_le_bytes())
+    ///     .flatten()
+    ///     .take(13)
+    ///     .collect();
+    /// assert_eq!(buf.to_vec(), expected);
+    /// ```
+    #[inline]
+    pub fn rand_bytes_into(&mut self, buf: &mut [u8]) {
+        self.generator.fill_bytes(buf);
+    }
+
     /// Generates a vector of unique random usize values in the specified range.
     ///
     /// # Arguments
@@ -787,6 +1265,218 @@ mod tests {
 
     const SEED: usize = 0xb3959f04cb8af237;
 
+    fn generator(g: &Generators, seed: usize) -> Generator {
+        match g {
+            Generators::Xorshift64 => Generator::Xorshift64(Xorshift64::new(seed)),
+            Generators::Romuduojr => Generator::RomuDuoJr(RomuDuoJr::new(seed)),
+            Generators::Romutrio => Generator::RomuTrio(RomuTrio::new(seed)),
+            Generators::Splitmix64 => Generator::SplitMix64(SplitMix64::new(seed)),
+            Generators::Xorshiro128ss => Generator::XorShiro128ss(XorShiro128ss::new(seed)),
+            Generators::Xorshiro256ss => Generator::XorShiro256ss(XorShiro256ss::new(seed)),
+            Generators::Lehmer64 => Generator::Lehmer64(Lehmer64::new(seed)),
+            Generators::Wyhash64 => Generator::Wyhash64(Wyhash64::new(seed)),
+            Generators::Shishua => Generator::ShiShua(ShiShua::new(seed)),
+            Generators::Chacha20 => Generator::ChaCha20(ChaCha20::new(seed)),
+            Generators::Pcg64 => Generator::Pcg64(Pcg64::new(seed)),
+            Generators::External => unreachable!("External generators have no fixed algorithm"),
+        }
+    }
+
+    /// Hashes the first `n` outputs of generator `g` seeded with `seed` using FNV-1a, which
+    /// unlike `DefaultHasher` is stable across Rust releases.
+    fn generator_fingerprint(g: &Generators, seed: usize, n: usize) -> u64 {
+        let mut generator = generator(g, seed);
+        (0..n)
+            .flat_map(|_| (generator.rand() as u64).to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+                (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    #[test]
+    fn test_rand_byte_uniform() {
+        const DRAWS: usize = 1 << 21;
+        for g in Generators::value_variants() {
+            let mut prng = Rng::new(generator(g, SEED));
+            let mut counts = [0usize; 256];
+            for _ in 0..DRAWS {
+                counts[prng.rand_byte() as usize] += 1;
+            }
+            let expected = DRAWS / 256;
+            for (b, &count) in counts.iter().enumerate() {
+                assert!(
+                    count.abs_diff(expected) < expected / 20,
+                    "{g:?}: {b:#04x} drawn {count} times, expected about {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pick_weighted() {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        let entries = [0usize, 1, 2, 3];
+        let weights = [1, 0, 100, 1];
+        let mut counts = [0usize; 4];
+        for _ in 0..102_000 {
+            counts[*prng.pick_weighted(&entries, &weights)] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        for (i, &weight) in weights.iter().enumerate() {
+            let expected = 1000 * weight as usize;
+            assert!(
+                counts[i].abs_diff(expected) <= expected / 10,
+                "{i}: picked {} times, expected about {expected}",
+                counts[i]
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "total weight of 0")]
+    fn test_pick_weighted_all_zero() {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        prng.pick_weighted(&[1, 2], &[0, 0]);
+    }
+
+    #[test]
+    fn test_generators_all() {
+        // clap lists every variant except the skipped `External`
+        assert_eq!(Generators::all(), Generators::value_variants());
+
+        for g in Generators::all() {
+            let name = g.to_possible_value().unwrap().get_name().to_string();
+            assert_eq!(name.parse::<Generators>().as_ref(), Ok(g));
+            assert_eq!(name.to_uppercase().parse::<Generators>().as_ref(), Ok(g));
+        }
+        assert!("external".parse::<Generators>().is_err());
+        assert!("mersenne".parse::<Generators>().is_err());
+    }
+
+    #[test]
+    fn test_rand_range_signed() {
+        fn check<T: RangeBound + Default + core::fmt::Debug>(min: T, max: T) {
+            let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+            let (mut neg, mut pos) = (false, false);
+            for _ in 0..1000 {
+                let exclusive = prng.rand_range(min, max);
+                assert!(exclusive >= min && exclusive < max, "{exclusive:?}");
+                let inclusive = prng.rand_range_inclusive(min, max);
+                assert!(inclusive >= min && inclusive <= max, "{inclusive:?}");
+                neg |= exclusive < T::default() || inclusive < T::default();
+                pos |= exclusive > T::default() || inclusive > T::default();
+            }
+            assert!(neg && pos, "{min:?}..={max:?}");
+        }
+        check(-100i8, 100);
+        check(i8::MIN, i8::MAX);
+        check(-1000i16, 1000);
+        check(i16::MIN, i16::MAX);
+        check(-100_000i32, 100_000);
+        check(i32::MIN, i32::MAX);
+        check(-100i64, 100);
+        check(i64::MIN, i64::MAX);
+
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            seen[(prng.rand_range_inclusive(-2i32, 2) + 2) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(prng.rand_range_inclusive(-7i64, -7), -7);
+    }
+
+    #[test]
+    fn test_rand_bytes_into() {
+        for g in Generators::value_variants() {
+            let mut prng = Rng::new(generator(g, SEED));
+            let mut clone = prng.clone();
+            for size in [0, 1, 7, 8, 9, 15, 16, 17, 100] {
+                let mut buf = vec![0xaa; size];
+                prng.rand_bytes_into(&mut buf);
+                let expected: Vec<u8> = std::iter::repeat_with(|| clone.rand().to_le_bytes())
+                    .flatten()
+                    .take(size)
+                    .collect();
+                assert_eq!(buf, expected, "{g:?}, size: {size}");
+                // Both generators consumed the same number of `rand` calls
+                assert_eq!(prng.rand(), clone.rand(), "{g:?}, size: {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bool_ratio() {
+        const DRAWS: usize = 100_000;
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        for (numerator, denominator) in [(0, 1), (1, 1), (3, 4), (1, 3)] {
+            let hits = (0..DRAWS)
+                .filter(|_| prng.bool_ratio(numerator, denominator))
+                .count();
+            let expected = DRAWS * numerator as usize / denominator as usize;
+            assert!(
+                hits.abs_diff(expected) <= DRAWS / 100,
+                "{numerator}/{denominator}: {hits} hits, expected about {expected}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "must not exceed denominator")]
+    fn test_bool_ratio_invalid() {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        prng.bool_ratio(5, 4);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        for g in Generators::value_variants() {
+            let mut prng = Rng::new(generator(g, SEED));
+            // Odd counts leave ShiShua in the middle of its output buffer
+            for _ in 0..37 {
+                prng.rand();
+            }
+            let snapshot = prng.snapshot();
+            assert!(!snapshot.is_empty(), "{g:?}");
+            let expected = (0..101).map(|_| prng.rand()).collect::<Vec<_>>();
+            prng.restore(&snapshot);
+            assert_eq!(
+                (0..101).map(|_| prng.rand()).collect::<Vec<_>>(),
+                expected,
+                "{g:?}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected a generator state of 2 words")]
+    fn test_restore_mismatched_state() {
+        let snapshot = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED))).snapshot();
+        Rng::new(Generator::RomuDuoJr(RomuDuoJr::new(SEED))).restore(&snapshot);
+    }
+
+    #[test]
+    fn test_generator_fingerprints() {
+        // Changing any of these breaks the reproducibility of runs with a fixed seed. Only
+        // update them if a generator's algorithm is changed on purpose.
+        let expected = [
+            (Generators::Xorshift64, 0x6e53_5b10_6c36_f453),
+            (Generators::Romuduojr, 0xeaaf_15fb_30db_67e3),
+            (Generators::Romutrio, 0x9f3d_ab3c_c66b_f8ca),
+            (Generators::Splitmix64, 0x3c8e_b970_bd2b_073b),
+            (Generators::Xorshiro128ss, 0xc91e_0fb4_92da_a543),
+            (Generators::Xorshiro256ss, 0x9e95_6f9a_fb80_2f63),
+            (Generators::Lehmer64, 0x330a_4c61_e78c_3c1a),
+            (Generators::Wyhash64, 0xee3e_edb2_075a_27b9),
+            (Generators::Shishua, 0xf16a_7386_6b0a_d53b),
+            (Generators::Chacha20, 0xac66_22a5_d719_8194),
+            (Generators::Pcg64, 0xb678_19b2_1303_e20d),
+        ];
+        for (g, fingerprint) in expected {
+            assert_eq!(generator_fingerprint(&g, SEED, 1024), fingerprint, "{g:?}");
+        }
+    }
+
     #[bench]
     pub fn xorshift64_bench(b: &mut Bencher) {
         let mut prng = Xorshift64::new(SEED);
@@ -866,4 +1556,46 @@ mod tests {
             }
         });
     }
+
+    #[bench]
+    pub fn chacha20_bench(b: &mut Bencher) {
+        let mut prng = ChaCha20::new(SEED);
+        b.iter(|| {
+            for _ in 0..1_000_000 {
+                black_box(prng.rand());
+            }
+        });
+    }
+
+    #[bench]
+    pub fn pcg64_bench(b: &mut Bencher) {
+        let mut prng = Pcg64::new(SEED);
+        b.iter(|| {
+            for _ in 0..1_000_000 {
+                black_box(prng.rand());
+            }
+        });
+    }
+
+    #[bench]
+    pub fn rand_byte_vec_bench(b: &mut Bencher) {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        b.iter(|| {
+            for _ in 0..1_000 {
+                black_box(prng.rand_byte_vec(1024));
+            }
+        });
+    }
+
+    #[bench]
+    pub fn rand_bytes_into_bench(b: &mut Bencher) {
+        let mut prng = Rng::new(Generator::Xorshift64(Xorshift64::new(SEED)));
+        let mut buf = [0u8; 1024];
+        b.iter(|| {
+            for _ in 0..1_000 {
+                prng.rand_bytes_into(&mut buf);
+                black_box(&buf);
+            }
+        });
+    }
 }
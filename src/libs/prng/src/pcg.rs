@@ -0,0 +1,132 @@
+use crate::get_seeds;
+use crate::seed::Seeds;
+use crate::{state_words, GeneratorTrait};
+
+const PCG_MULTIPLIER: u128 = 0x2360_ED05_1FC6_5DA4_4385_DF64_9FCC_F645;
+
+/// Implements PCG-XSL-RR 128/64 (`pcg64`) from <https://www.pcg-random.org>
+#[derive(Debug, Clone, Copy)]
+pub struct Pcg64 {
+    state: u128,
+    // Selects the stream, always odd
+    inc: u128,
+}
+
+impl GeneratorTrait for Pcg64 {
+    #[inline]
+    fn rand(&mut self) -> usize {
+        self.step();
+        let rot = (self.state >> 122) as u32;
+        let xsl = ((self.state >> 64) as u64) ^ (self.state as u64);
+        xsl.rotate_right(rot) as usize
+    }
+
+    fn set_seed(&mut self, seed: usize) {
+        *self = Self::new(seed);
+    }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![
+            (self.state >> 64) as u64,
+            self.state as u64,
+            (self.inc >> 64) as u64,
+            self.inc as u64,
+        ]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state_hi, state_lo, inc_hi, inc_lo] = state_words(state);
+        self.state = u128::from(state_hi) << 64 | u128::from(state_lo);
+        self.inc = u128::from(inc_hi) << 64 | u128::from(inc_lo);
+    }
+
+    fn jump(&mut self) -> bool {
+        self.advance(1 << 64);
+        true
+    }
+}
+
+impl Pcg64 {
+    pub fn new(seed: usize) -> Self {
+        let seeds: Seeds = get_seeds!(seed, 4);
+        Self::with_stream(
+            (seeds.state_w as u128) << 64 | seeds.state_x as u128,
+            (seeds.state_y as u128) << 64 | seeds.state_z as u128,
+        )
+    }
+
+    /// Seeds the generator like `pcg64_srandom_r` of the reference implementation.
+    ///
+    /// # Arguments
+    ///
+    /// * `init_state` - The initial state.
+    /// * `init_seq` - The stream to use. Generators on different streams produce different
+    ///   sequences, even for the same initial state.
+    pub const fn with_stream(init_state: u128, init_seq: u128) -> Self {
+        let mut ret = Self {
+            state: 0,
+            inc: init_seq << 1 | 1,
+        };
+        ret.step();
+        ret.state = ret.state.wrapping_add(init_state);
+        ret.step();
+        ret
+    }
+
+    /// Advances the state by `delta` steps in O(log delta), like `pcg64_advance_r`.
+    const fn advance(&mut self, mut delta: u128) {
+        let (mut cur_mult, mut cur_plus) = (PCG_MULTIPLIER, self.inc);
+        let (mut acc_mult, mut acc_plus) = (1u128, 0u128);
+        while delta > 0 {
+            if delta & 1 != 0 {
+                acc_mult = acc_mult.wrapping_mul(cur_mult);
+                acc_plus = acc_plus.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+            }
+            cur_plus = cur_mult.wrapping_add(1).wrapping_mul(cur_plus);
+            cur_mult = cur_mult.wrapping_mul(cur_mult);
+            delta >>= 1;
+        }
+        self.state = acc_mult.wrapping_mul(self.state).wrapping_add(acc_plus);
+    }
+
+    #[inline(always)]
+    const fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(self.inc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcg64_reference() {
+        // First outputs of `pcg64_random_r` in the reference C implementation seeded with 42, 54
+        let mut prng = Pcg64::with_stream(42, 54);
+        let expected = [
+            0x86b1_da1d_7206_2b68,
+            0x1304_aa46_c985_3d39,
+            0xa367_0e9e_0dd5_0358,
+            0xf909_0e52_9a7d_ae00,
+            0xc85b_9fd8_3799_6f2c,
+            0x6061_21f8_e391_9196,
+        ];
+        for val in expected {
+            assert_eq!(prng.rand(), val);
+        }
+    }
+
+    #[test]
+    fn test_pcg64_advance() {
+        let mut prng = Pcg64::with_stream(42, 54);
+        let mut jumped = prng;
+        jumped.advance(1000);
+        for _ in 0..1000 {
+            prng.rand();
+        }
+        assert_eq!(prng.rand(), jumped.rand());
+    }
+}
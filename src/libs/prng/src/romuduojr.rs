@@ -1,6 +1,6 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RomuDuoJr {
@@ -22,6 +22,16 @@ impl GeneratorTrait for RomuDuoJr {
         self.state_x = seeds.state_w;
         self.state_y = seeds.state_x;
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![self.state_x as u64, self.state_y as u64]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state_x, state_y] = state_words(state);
+        self.state_x = state_x as usize;
+        self.state_y = state_y as usize;
+    }
 }
 
 impl RomuDuoJr {
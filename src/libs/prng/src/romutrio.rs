@@ -1,6 +1,6 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RomuTrio {
@@ -27,6 +27,21 @@ impl GeneratorTrait for RomuTrio {
         self.state_y = seeds.state_x;
         self.state_z = seeds.state_y;
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![
+            self.state_x as u64,
+            self.state_y as u64,
+            self.state_z as u64,
+        ]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state_x, state_y, state_z] = state_words(state);
+        self.state_x = state_x as usize;
+        self.state_y = state_y as usize;
+        self.state_z = state_z as usize;
+    }
 }
 
 impl RomuTrio {
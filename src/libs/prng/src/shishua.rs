@@ -1,6 +1,6 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
 use packed_simd_2::{u32x8, u64x4, IntoBits};
 
 const PHI: [u64; 16] = [
@@ -24,6 +24,8 @@ const PHI: [u64; 16] = [
 
 pub const STATE_LANES: usize = u64x4::lanes();
 const STATE_SIZE: usize = 4;
+// Number of words of the state returned by `get_state`
+const SHISHUA_STATE_WORDS: usize = (2 * STATE_SIZE + 1) * STATE_LANES + 2;
 // Original values from the blog post
 const STEPS: usize = 5;
 const ROUNDS: usize = 4;
@@ -109,6 +111,26 @@ impl ShiShua {
         output
     }
 
+    // Returns the next word of the output buffer
+    #[inline(always)]
+    fn next_word(&mut self) -> u64 {
+        // If we finish reading from 1 lane we get to the next
+        if self.buffer_idx % 3 == 0 {
+            self.buffer_idx = 0;
+            self.arr_idx += 1;
+        }
+        // If we consumed all lanes and indices, we roll new values.
+        if self.arr_idx % 3 == 0 && self.buffer_idx % 3 == 0 {
+            self.buffer_idx = 0;
+            self.arr_idx = 0;
+            self.round_unpack();
+        }
+        let out = self.output[self.arr_idx].extract(self.buffer_idx);
+        self.buffer_idx += 1;
+
+        out
+    }
+
     #[inline(always)]
     fn round(&mut self) -> [u64x4; STATE_SIZE] {
         const fn correct_index(index: u32) -> u32 {
@@ -194,21 +216,16 @@ impl ShiShua {
 impl GeneratorTrait for ShiShua {
     #[inline]
     fn rand(&mut self) -> usize {
-        // If we finish reading from 1 lane we get to the next
-        if self.buffer_idx % 3 == 0 {
-            self.buffer_idx = 0;
-            self.arr_idx += 1;
-        }
-        // If we consumed all lanes and indices, we roll new values.
-        if self.arr_idx % 3 == 0 && self.buffer_idx % 3 == 0 {
-            self.buffer_idx = 0;
-            self.arr_idx = 0;
-            self.round_unpack();
-        }
-        let out = self.output[self.arr_idx].extract(self.buffer_idx) as usize;
-        self.buffer_idx += 1;
+        self.next_word() as usize
+    }
 
-        out
+    /// Copies the output lanes into `buf` directly, without a `rand` call per word.
+    #[inline]
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(std::mem::size_of::<u64>()) {
+            let len = chunk.len();
+            chunk.copy_from_slice(&self.next_word().to_le_bytes()[..len]);
+        }
     }
 
     fn set_seed(&mut self, seed: usize) {
@@ -240,4 +257,29 @@ impl GeneratorTrait for ShiShua {
             ),
         ];
     }
+
+    /// The state consists of the lanes of `state`, `output` and `counter`, followed by the
+    /// buffer and array index of the next output.
+    fn get_state(&self) -> Vec<u64> {
+        self.state
+            .iter()
+            .chain(&self.output)
+            .chain([&self.counter])
+            .flat_map(|v| (0..STATE_LANES).map(|i| v.extract(i)))
+            .chain([self.buffer_idx as u64, self.arr_idx as u64])
+            .collect()
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let words: [u64; SHISHUA_STATE_WORDS] = state_words(state);
+        let mut vectors = words
+            .chunks_exact(STATE_LANES)
+            .map(|w| u64x4::new(w[0], w[1], w[2], w[3]));
+        for v in self.state.iter_mut().chain(&mut self.output) {
+            *v = vectors.next().unwrap();
+        }
+        self.counter = vectors.next().unwrap();
+        self.buffer_idx = words[SHISHUA_STATE_WORDS - 2] as usize;
+        self.arr_idx = words[SHISHUA_STATE_WORDS - 1] as usize;
+    }
 }
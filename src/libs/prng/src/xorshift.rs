@@ -1,6 +1,6 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Xorshift64 {
@@ -21,6 +21,15 @@ impl GeneratorTrait for Xorshift64 {
         let seeds: Seeds = get_seeds!(seed, 1);
         self.state = seeds.state_w;
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![self.state as u64]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state] = state_words(state);
+        self.state = state as usize;
+    }
 }
 
 impl Xorshift64 {
@@ -1,6 +1,9 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
+
+// Jump polynomial advancing the state by 2^64 steps, from the reference implementation
+const JUMP: [u64; 2] = [0xdf90_0294_d8f5_54a5, 0x1708_65df_4b32_01fc];
 
 #[derive(Debug, Clone, Copy)]
 pub struct XorShiro128ss {
@@ -25,6 +28,32 @@ impl GeneratorTrait for XorShiro128ss {
         self.state_x = seeds.state_w;
         self.state_y = seeds.state_x;
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![self.state_x as u64, self.state_y as u64]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state_x, state_y] = state_words(state);
+        self.state_x = state_x as usize;
+        self.state_y = state_y as usize;
+    }
+
+    fn jump(&mut self) -> bool {
+        let (mut s0, mut s1) = (0, 0);
+        for j in JUMP {
+            for b in 0..64 {
+                if j & (1 << b) != 0 {
+                    s0 ^= self.state_x;
+                    s1 ^= self.state_y;
+                }
+                self.rand();
+            }
+        }
+        self.state_x = s0;
+        self.state_y = s1;
+        true
+    }
 }
 
 impl XorShiro128ss {
@@ -1,6 +1,14 @@
 use crate::get_seeds;
 use crate::seed::Seeds;
-use crate::GeneratorTrait;
+use crate::{state_words, GeneratorTrait};
+
+// Jump polynomial advancing the state by 2^128 steps, from the reference implementation
+const JUMP: [u64; 4] = [
+    0x180e_c6d3_3cfd_0aba,
+    0xd5a6_1266_f0c9_392c,
+    0xa958_2618_e03f_c9aa,
+    0x39ab_dc45_29b1_661c,
+];
 
 const fn rol64(x: u64, k: i32) -> u64 {
     (x << k) | (x >> (64 - k))
@@ -18,7 +26,7 @@ impl GeneratorTrait for XorShiro256ss {
     #[inline]
     fn rand(&mut self) -> usize {
         let res = rol64(self.state_x.wrapping_mul(5) as u64, 7).wrapping_mul(9) as usize;
-        let t = self.state_y.wrapping_shl(17);
+        let t = self.state_x.wrapping_shl(17);
         self.state_y ^= self.state_w;
         self.state_z ^= self.state_x;
         self.state_x ^= self.state_y;
@@ -35,6 +43,43 @@ impl GeneratorTrait for XorShiro256ss {
         self.state_y = seeds.state_y;
         self.state_z = seeds.state_z;
     }
+
+    fn get_state(&self) -> Vec<u64> {
+        vec![
+            self.state_w as u64,
+            self.state_x as u64,
+            self.state_y as u64,
+            self.state_z as u64,
+        ]
+    }
+
+    fn set_state(&mut self, state: &[u64]) {
+        let [state_w, state_x, state_y, state_z] = state_words(state);
+        self.state_w = state_w as usize;
+        self.state_x = state_x as usize;
+        self.state_y = state_y as usize;
+        self.state_z = state_z as usize;
+    }
+
+    fn jump(&mut self) -> bool {
+        let (mut s0, mut s1, mut s2, mut s3) = (0, 0, 0, 0);
+        for j in JUMP {
+            for b in 0..64 {
+                if j & (1 << b) != 0 {
+                    s0 ^= self.state_w;
+                    s1 ^= self.state_x;
+                    s2 ^= self.state_y;
+                    s3 ^= self.state_z;
+                }
+                self.rand();
+            }
+        }
+        self.state_w = s0;
+        self.state_x = s1;
+        self.state_y = s2;
+        self.state_z = s3;
+        true
+    }
 }
 
 impl XorShiro256ss {
@@ -48,3 +93,49 @@ impl XorShiro256ss {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_state(state: &[u64]) -> XorShiro256ss {
+        let mut prng = XorShiro256ss::new(1);
+        prng.set_state(state);
+        prng
+    }
+
+    #[test]
+    fn test_xorshiro256ss_reference() {
+        // First outputs of `next` in the reference C implementation with the state 1, 2, 3, 4
+        let mut prng = from_state(&[1, 2, 3, 4]);
+        let expected = [0x2d00, 0, 0x5a00_7080, 0x10e0_0000_0000_9d80];
+        for val in expected {
+            assert_eq!(prng.rand(), val);
+        }
+    }
+
+    #[test]
+    fn test_xorshiro256ss_jump() {
+        // State and outputs after `jump` in the reference C implementation with the state 1, 2,
+        // 3, 4
+        let mut prng = from_state(&[1, 2, 3, 4]);
+        assert!(prng.jump());
+        assert_eq!(
+            prng.get_state(),
+            [
+                0x8c7a_1539_56b5_f3d1,
+                0x701f_1a71_3401_d85e,
+                0x6527_f66a_6546_9085,
+                0x8386_b786_c440_8050,
+            ]
+        );
+        let expected = [
+            0xbbd2_f312_2984_43d8,
+            0x62e5_7db2_d570_6577,
+            0x34d1_8903_74a6_d72b,
+        ];
+        for val in expected {
+            assert_eq!(prng.rand(), val);
+        }
+    }
+}
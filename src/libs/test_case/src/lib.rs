@@ -1,5 +1,6 @@
 use errors::{Error, Result};
 use num_traits::{Euclid, PrimInt, WrappingSub};
+use std::collections::HashMap;
 use std::io::Read;
 
 #[derive(Debug, Clone, Copy)]
@@ -9,6 +10,19 @@ pub enum Encoding {
     UTF16,
 }
 
+/// A node of an input-driven nested structure as produced by `TestCase::consume_nested`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nested<T> {
+    Leaf(T),
+    List(Vec<Nested<T>>),
+}
+
+// Hard limit of the nesting depth of `TestCase::consume_nested`, which recurses once per level
+const MAX_NESTING_DEPTH: usize = 256;
+
+/// A stream of fuzzer provided bytes that harnesses consume typed values from.
+/// None of the `consume_*` methods panic on any input, be it empty, truncated or otherwise
+/// malformed data, or out of range arguments. They either return a total value or an `Err`.
 #[derive(Debug)]
 pub struct TestCase {
     // Actual data of the test case
@@ -17,6 +31,8 @@ pub struct TestCase {
     pub size: usize,
     //  Data pointer to the current position in the data
     pub data_ptr: usize,
+    // Number of bits of the byte at `data_ptr` already consumed by `consume_bits`
+    pub bit_ptr: u8,
     // Energy of the test case, used when a power schedule is used
     pub energy: usize,
     // Indices of the test cases that have been accessed/used by the fuzzer
@@ -27,8 +43,9 @@ impl Default for TestCase {
     fn default() -> Self {
         Self {
             data: Vec::with_capacity(4096),
-            size: 4096,
+            size: 0,
             data_ptr: 0,
+            bit_ptr: 0,
             energy: 0,
             accessed: Vec::new(),
         }
@@ -37,14 +54,73 @@ impl Default for TestCase {
 
 impl TestCase {
     pub fn new(data: &[u8]) -> Self {
+        Self::from_vec(data.to_vec())
+    }
+
+    /// Creates a new `TestCase` that takes ownership of `data` without cloning it.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A `Vec<u8>` holding the test case data.
+    ///
+    /// # Returns
+    ///
+    /// A new `TestCase` wrapping `data`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let tc = TestCase::from_vec(vec![0x42, 0x24]);
+    /// assert_eq!(tc.size, 2);
+    /// assert_eq!(tc.data, vec![0x42, 0x24]);
+    /// ```
+    pub fn from_vec(data: Vec<u8>) -> Self {
         Self {
-            data: data.to_vec(),
             size: data.len(),
+            data,
             data_ptr: 0,
+            bit_ptr: 0,
             energy: 0,
             accessed: Vec::new(),
         }
     }
+
+    /// Creates a new `TestCase` whose data pointer starts at `start`, e.g. to skip a fixed
+    /// size header before consuming structured data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A `&[u8]` holding the test case data.
+    /// * `start` - A `usize` indicating the offset the first consume starts at.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<TestCase>` which is `Ok(TestCase)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If `start` lies beyond the end of `data`, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new_with_offset(&vec![0x7f, 0x45, 0x4c, 0x46, 0x42], 4).unwrap();
+    /// assert_eq!(tc.consume_byte().unwrap(), 0x42);
+    /// assert!(TestCase::new_with_offset(&vec![0x42], 2).is_err());
+    /// ```
+    pub fn new_with_offset(data: &[u8], start: usize) -> Result<Self> {
+        if start > data.len() {
+            return Err(Error::new("Offset lies beyond the end of the data"));
+        }
+        let mut tc = Self::new(data);
+        tc.data_ptr = start;
+        Ok(tc)
+    }
+
     /// Returns the data pointer.
     ///
     /// # Returns
@@ -85,6 +161,136 @@ impl TestCase {
         self.size
     }
 
+    /// Returns the number of bytes that are left to be consumed without advancing the data pointer.
+    /// A byte partially consumed by `consume_bits` does not count.
+    ///
+    /// # Returns
+    ///
+    /// A `usize` representing the number of bytes left in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x42, 0x24, 0x13]);
+    /// assert_eq!(tc.remaining(), 3);
+    /// let _ = tc.consume_byte();
+    /// assert_eq!(tc.remaining(), 2);
+    /// ```
+    pub const fn remaining(&self) -> usize {
+        let partial = if self.bit_ptr != 0 { 1 } else { 0 };
+        self.size.saturating_sub(self.data_ptr + partial)
+    }
+
+    /// Checks whether all bytes of the stream have been consumed.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no bytes are left to be consumed, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x42]);
+    /// assert!(!tc.is_exhausted());
+    /// let _ = tc.consume_byte();
+    /// assert!(tc.is_exhausted());
+    /// ```
+    pub const fn is_exhausted(&self) -> bool {
+        self.remaining() == 0 && self.bit_ptr == 0
+    }
+
+    /// Returns the next byte that `consume_byte` would return without advancing the data pointer.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<u8>` which is `Ok(u8)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If the stream is exhausted, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x42, 0x24]);
+    /// assert_eq!(tc.peek_byte().unwrap(), 0x42);
+    /// assert_eq!(tc.consume_byte().unwrap(), 0x42);
+    /// assert_eq!(tc.peek_byte().unwrap(), 0x24);
+    /// ```
+    pub fn peek_byte(&self) -> Result<u8> {
+        if self.remaining() == 0 {
+            return Err(Error::new("Not enough data left to fullfil request"));
+        }
+        self.data
+            .get(self.size - self.remaining())
+            .copied()
+            .ok_or_else(|| Error::new("Not enough data left to fullfil request"))
+    }
+
+    /// Returns the current position in the stream, to return to it later on via `seek`. The
+    /// position is byte granular, so bits of a byte partially consumed by `consume_bits` are
+    /// not recorded.
+    ///
+    /// # Returns
+    ///
+    /// A `usize` representing the current data pointer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x42, 0x24]);
+    /// let _ = tc.consume_byte();
+    /// assert_eq!(tc.mark(), 1);
+    /// ```
+    pub const fn mark(&self) -> usize {
+        self.data_ptr
+    }
+
+    /// Moves the data pointer to `pos`, e.g. to re-read data from a position returned by `mark`.
+    /// A byte partially consumed by `consume_bits` is discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - A `usize` indicating the new position in the stream.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<()>` which is `Ok(())` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If `pos` lies beyond the end of the stream, an `Err(Error)` is returned and the data
+    /// pointer is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0x42, 0x24]);
+    /// let start = tc.mark();
+    /// let len = tc.consume_byte().unwrap();
+    /// tc.seek(start).unwrap();
+    /// assert_eq!(tc.consume_bytes(1 + len as usize).unwrap(), vec![0x02, 0x42, 0x24]);
+    /// assert!(tc.seek(4).is_err());
+    /// ```
+    pub fn seek(&mut self, pos: usize) -> Result<()> {
+        if pos > self.size {
+            return Err(Error::new("Position lies beyond the end of the data"));
+        }
+        self.data_ptr = pos;
+        self.bit_ptr = 0;
+        Ok(())
+    }
+
     /// Sets the accessed indices.
     ///
     /// # Arguments
@@ -128,7 +334,16 @@ impl TestCase {
     /// A `Result<usize>` containing the maximum length or an error if the requested size is not valid.
     fn _get_max(&mut self, len: usize) -> Result<usize> {
         self.is_size_sane(len)?;
-        Ok(len.min(self.size - self.data_ptr))
+        Ok(len.min(self.remaining()))
+    }
+
+    /// Skips the rest of a byte partially consumed by `consume_bits`, so that byte oriented
+    /// consumers start at the next byte boundary.
+    fn align_to_byte(&mut self) {
+        if self.bit_ptr != 0 {
+            self.bit_ptr = 0;
+            self.data_ptr += 1;
+        }
     }
 
     /// Checks if the requested size is sane.
@@ -141,7 +356,9 @@ impl TestCase {
     ///
     /// A `Result<()>` containing an error if the requested size is not sane.
     fn is_size_sane(&mut self, requested: usize) -> Result<()> {
-        if requested + self.data_ptr > self.size {
+        debug_assert_eq!(self.size, self.data.len(), "size out of sync with data");
+        self.align_to_byte();
+        if requested > self.remaining() {
             return Err(Error::new("Not enough data left to fullfil request"));
         }
         Ok(())
@@ -175,6 +392,112 @@ impl TestCase {
         Err(Error::new("Failed to consume bool from stream"))
     }
 
+    /// Consumes a single byte from the stream and maps it to a `bool` that is `true` with a
+    /// probability of approximately `numerator / denominator`. The byte is compared against
+    /// the threshold `256 * numerator / denominator`, so the same data always yields the same
+    /// result. Ratios of at least 1 always yield `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `numerator` - A `u32` indicating the numerator of the probability of `true`.
+    /// * `denominator` - A `u32` indicating the denominator of the probability of `true`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<bool>` which is `Ok(bool)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If `denominator` is 0, an `Err(Error)` is returned without consuming any data.
+    /// * If the stream is exhausted, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x00, 0x01, 0xff]);
+    /// assert_eq!(tc.consume_bool_weighted(1, 128).unwrap(), true);
+    /// assert_eq!(tc.consume_bool_weighted(1, 128).unwrap(), true);
+    /// assert_eq!(tc.consume_bool_weighted(1, 128).unwrap(), false);
+    /// assert_eq!(tc.data_ptr, 3);
+    /// ```
+    pub fn consume_bool_weighted(&mut self, numerator: u32, denominator: u32) -> Result<bool> {
+        if denominator == 0 {
+            return Err(Error::new("denominator must not be 0"));
+        }
+        let byte = self.consume_byte()?;
+        Ok(u64::from(byte) * u64::from(denominator) < 256 * u64::from(numerator))
+    }
+
+    /// Consumes a single byte from the stream and maps it to one of `N` variants, e.g. to select
+    /// the next operation of a structured harness. The variant only depends on the byte at the
+    /// current stream position, so it composes with the other `consume_*` methods. As a single
+    /// byte is consumed, only the first 256 variants can be selected if `N` is larger than that.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<usize>` which is `Ok(usize)` holding the variant in `0..N` if the operation is
+    /// successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If `N` is 0, an `Err(Error)` is returned without consuming any data.
+    /// * If the stream is exhausted, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0x07]);
+    /// assert_eq!(tc.consume_enum::<3>().unwrap(), 2);
+    /// assert_eq!(tc.consume_enum::<3>().unwrap(), 1);
+    /// assert!(tc.consume_enum::<3>().is_err());
+    /// assert_eq!(tc.data_ptr, 2);
+    /// ```
+    pub fn consume_enum<const N: usize>(&mut self) -> Result<usize> {
+        if N == 0 {
+            return Err(Error::new("Cannot select one of 0 variants"));
+        }
+        Ok(usize::from(self.consume_byte()?) % N)
+    }
+
+    /// Consumes a single byte from the stream and picks one of `choices` with it, as done by
+    /// `consume_enum`.
+    ///
+    /// # Arguments
+    ///
+    /// * `choices` - A `&[T]` holding the choices to pick from.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<&T>` which is `Ok(&T)` referencing the picked choice if the operation is
+    /// successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If `choices` is empty, an `Err(Error)` is returned without consuming any data.
+    /// * If the stream is exhausted, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x05]);
+    /// let method = tc.consume_pick(&["GET", "POST", "PUT", "DELETE"]).unwrap();
+    /// assert_eq!(*method, "POST");
+    /// assert!(tc.consume_pick::<u8>(&[]).is_err());
+    /// assert_eq!(tc.data_ptr, 1);
+    /// ```
+    pub fn consume_pick<'a, T>(&mut self, choices: &'a [T]) -> Result<&'a T> {
+        if choices.is_empty() {
+            return Err(Error::new("Cannot pick from empty choices"));
+        }
+        Ok(&choices[usize::from(self.consume_byte()?) % choices.len()])
+    }
+
     /// Consumes `num` `bool`s from the stream.
     ///
     /// # Arguments
@@ -234,6 +557,102 @@ impl TestCase {
         Ok(ret)
     }
 
+    /// Consumes `n` bits from the stream, most significant bit first, e.g. to parse bitfields.
+    /// Consecutive calls continue within the current byte, while all byte oriented `consume_*`
+    /// methods first skip the rest of a partially consumed byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - A `usize` indicating the number of bits to consume, within `1..=64`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<u64>` which is `Ok(u64)` holding the bits in its `n` least significant bits if
+    /// the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If `n` is not within `1..=64`, an `Err(Error)` is returned.
+    /// * If fewer than `n` bits are left, an `Err(Error)` is returned.
+    ///
+    /// In both cases no data is consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0b1011_0101, 0b1100_0000, 0x42]);
+    /// assert_eq!(tc.consume_bits(3).unwrap(), 0b101);
+    /// assert_eq!(tc.consume_bits(7).unwrap(), 0b10101_11);
+    /// // The rest of the second byte is skipped
+    /// assert_eq!(tc.consume_byte().unwrap(), 0x42);
+    /// assert!(tc.consume_bits(1).is_err());
+    /// ```
+    pub fn consume_bits(&mut self, n: usize) -> Result<u64> {
+        if !(1..=64).contains(&n) {
+            return Err(Error::new("Number of bits must be within 1..=64"));
+        }
+        let available = self
+            .size
+            .saturating_sub(self.data_ptr)
+            .saturating_mul(8)
+            .saturating_sub(usize::from(self.bit_ptr));
+        if n > available {
+            return Err(Error::new("Not enough bits left to fullfil request"));
+        }
+        let mut ret = 0u64;
+        let mut left = n;
+        while left > 0 {
+            let in_byte = 8 - usize::from(self.bit_ptr);
+            let take = left.min(in_byte);
+            let bits = (self.data[self.data_ptr] >> (in_byte - take)) & (0xff >> (8 - take));
+            ret = (ret << take) | u64::from(bits);
+            left -= take;
+            self.bit_ptr += take as u8;
+            if self.bit_ptr == 8 {
+                self.bit_ptr = 0;
+                self.data_ptr += 1;
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Consumes a single `u8` from the stream that is biased toward the boundary values `0x00`
+    /// and `0xff`. A selector byte is consumed first: values below `0x40` yield `0x00`, values
+    /// below `0x80` yield `0xff`, and all others consume a second byte that is returned as is.
+    /// Each boundary value is therefore returned with a probability slightly above 1/4.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<u8>` which is `Ok(u8)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If the stream runs out of data, an `Err(Error)` is returned and the data pointer is left
+    /// where it was before the call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x10, 0x50, 0x80, 0x42]);
+    /// assert_eq!(tc.consume_byte_biased().unwrap(), 0x00);
+    /// assert_eq!(tc.consume_byte_biased().unwrap(), 0xff);
+    /// assert_eq!(tc.consume_byte_biased().unwrap(), 0x42);
+    /// assert_eq!(tc.data_ptr, 4);
+    /// ```
+    pub fn consume_byte_biased(&mut self) -> Result<u8> {
+        self.align_to_byte();
+        let start = self.data_ptr;
+        match self.consume_byte()? {
+            0x00..=0x3f => Ok(0x00),
+            0x40..=0x7f => Ok(0xff),
+            _ => self.consume_byte().inspect_err(|_| self.data_ptr = start),
+        }
+    }
+
     /// Consumes `num` `u8`s from the stream.
     ///
     /// # Arguments
@@ -289,7 +708,35 @@ impl TestCase {
     /// assert_eq!(tc.data_ptr, 2);
     /// ```
     pub fn consume_remaining_as_bytes(&mut self) -> Result<Vec<u8>> {
-        self.consume_bytes(self.size - self.data_ptr)
+        self.consume_bytes(self.remaining())
+    }
+
+    /// Consumes `N` `u8`s from the stream into a fixed-size array.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<[u8; N]>` which is `Ok([u8; N])` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If fewer than `N` bytes are left in the stream, an `Err(Error)` is returned and nothing is consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x42, 0x24, 0x13]);
+    /// assert_eq!(tc.consume_array::<2>().unwrap(), [0x42, 0x24]);
+    /// assert_eq!(tc.data_ptr, 2);
+    /// assert!(tc.consume_array::<2>().is_err());
+    /// ```
+    pub fn consume_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.is_size_sane(N)?;
+        let mut arr = [0u8; N];
+        arr.copy_from_slice(&self.data[self.data_ptr..self.data_ptr + N]);
+        self.data_ptr += N;
+        Ok(arr)
     }
 
     /// Consumes a `String` of the specified length and encoding from the stream.
@@ -320,7 +767,7 @@ impl TestCase {
     pub fn consume_str(&mut self, len: usize, encoding: Encoding) -> Result<String> {
         let end = match encoding {
             Encoding::UTF8 | Encoding::UTF8ASCII => self._get_max(len)?,
-            Encoding::UTF16 => self._get_max(len * 2)?,
+            Encoding::UTF16 => self._get_max(len.saturating_mul(2))?,
         };
         let slice = &mut self.data[self.data_ptr..self.data_ptr + end];
         let s = match encoding {
@@ -332,11 +779,7 @@ impl TestCase {
                     .collect::<Vec<_>>();
                 String::from_utf8_lossy(&slice).to_string()
             }
-            Encoding::UTF16 => {
-                let utf16_slice =
-                    unsafe { std::slice::from_raw_parts(slice.as_ptr().cast::<u16>(), end) };
-                String::from_utf16_lossy(utf16_slice)
-            }
+            Encoding::UTF16 => String::from_utf16_lossy(&Self::to_utf16(slice)),
         };
 
         self.data_ptr += end;
@@ -344,6 +787,14 @@ impl TestCase {
         Ok(s)
     }
 
+    /// Reinterprets `bytes` as native endian UTF16 code units, ignoring a trailing odd byte.
+    fn to_utf16(bytes: &[u8]) -> Vec<u16> {
+        bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect()
+    }
+
     /// Consumes the remaining data in the stream as a string with the specified encoding.
     ///
     /// # Arguments
@@ -370,7 +821,97 @@ impl TestCase {
     /// assert_eq!(tc.data_ptr, 7);
     /// ```
     pub fn consume_remaining_as_str(&mut self, encoding: Encoding) -> Result<String> {
-        self.consume_str(self.size - self.data_ptr, encoding)
+        self.consume_str(self.remaining(), encoding)
+    }
+
+    /// Consumes a `usize` in `[0, max]` using the smallest integer width able to hold `max`.
+    fn _consume_usize_upto(&mut self, max: usize) -> Result<usize> {
+        if u8::try_from(max).is_ok() {
+            Ok(self.consume_int_range::<u8>(true, 0, max as u8)? as usize)
+        } else if u16::try_from(max).is_ok() {
+            Ok(self.consume_int_range::<u16>(true, 0, max as u16)? as usize)
+        } else {
+            self.consume_int_range::<usize>(true, 0, max)
+        }
+    }
+
+    /// Consumes a string whose length is driven by the stream and bounded by `max_len`.
+    /// The length in `[0, max_len]` is read first, using the smallest integer width able to
+    /// hold `max_len`, and is clamped to the data that is left. The string is consumed after.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - A `usize` indicating the maximum length of the `String` in code units, i.e.,
+    ///   bytes for `UTF8` and `UTF8ASCII` and 2 byte units for `UTF16`.
+    /// * `encoding` - The `Encoding` variant to interpret the consumed bytes.
+    /// * `strict` - A `bool` indicating whether invalid sequences are an error or replaced lossily.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<String>` which is `Ok(String)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If the length cannot be consumed, an `Err(Error)` is returned.
+    /// * If `strict` is set and the bytes are not valid `UTF8` or `UTF16`, an `Err(Error)` is
+    ///   returned and the data pointer is left where it was before the call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    /// use test_case::Encoding;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0x48, 0x69, 0x21]);
+    /// assert_eq!(tc.consume_string_bounded(8, Encoding::UTF8, true).unwrap(), "Hi");
+    /// assert_eq!(tc.data_ptr, 3);
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0xc3, 0x28]);
+    /// assert!(tc.consume_string_bounded(8, Encoding::UTF8, true).is_err());
+    /// assert_eq!(tc.data_ptr, 0);
+    /// assert_eq!(tc.consume_string_bounded(8, Encoding::UTF8, false).unwrap(), "\u{fffd}(");
+    /// ```
+    pub fn consume_string_bounded(
+        &mut self,
+        max_len: usize,
+        encoding: Encoding,
+        strict: bool,
+    ) -> Result<String> {
+        self.align_to_byte();
+        let start = self.data_ptr;
+        let len = self._consume_usize_upto(max_len)?;
+        let unit = match encoding {
+            Encoding::UTF8 | Encoding::UTF8ASCII => 1,
+            Encoding::UTF16 => 2,
+        };
+        let len = len.min(self.remaining() / unit);
+
+        if !strict {
+            return self.consume_str(len, encoding);
+        }
+
+        let slice = &self.data[self.data_ptr..self.data_ptr + len * unit];
+        let s = match encoding {
+            Encoding::UTF8 => std::str::from_utf8(slice)
+                .map(str::to_owned)
+                .map_err(Error::from),
+            Encoding::UTF16 => {
+                String::from_utf16(&Self::to_utf16(slice)).map_err(|e| Error::new(&e.to_string()))
+            }
+            // Every byte is mapped into the printable ASCII range so this can never fail
+            Encoding::UTF8ASCII => return self.consume_str(len, encoding),
+        };
+
+        match s {
+            Ok(s) => {
+                self.data_ptr += len * unit;
+                Ok(s)
+            }
+            Err(e) => {
+                self.data_ptr = start;
+                Err(e)
+            }
+        }
     }
 
     /// Consumes a single integer of type `T` from the stream with the specified endianness.
@@ -439,7 +980,10 @@ impl TestCase {
         is_little_endian: bool,
         num: usize,
     ) -> Result<Vec<T>> {
-        let max = std::cmp::min(num, self._get_max(std::mem::size_of::<T>() * num)?);
+        let max = std::cmp::min(
+            num,
+            self._get_max(std::mem::size_of::<T>().saturating_mul(num))?,
+        );
         let mut nums: Vec<T> = vec![T::from(0).ok_or(Error::ConversionError)?; max];
         (0..nums.len()).for_each(|n| {
             if let Ok(num) = self.consume_int(is_little_endian) {
@@ -485,7 +1029,10 @@ impl TestCase {
         min: T,
         max: T,
     ) -> Result<Vec<T>> {
-        let max_ele = std::cmp::min(num, self._get_max(std::mem::size_of::<T>() * num)?);
+        let max_ele = std::cmp::min(
+            num,
+            self._get_max(std::mem::size_of::<T>().saturating_mul(num))?,
+        );
         let mut nums: Vec<T> = vec![T::from(0).ok_or(Error::ConversionError)?; max_ele];
         (0..nums.len()).for_each(|n| {
             if let Ok(num) = self.consume_int_range(is_little_endian, min, max) {
@@ -508,12 +1055,9 @@ impl TestCase {
     ///
     /// # Errors
     ///
+    /// * If `min` is greater than `max`, an `Err(Error)` is returned without consuming any data.
     /// * If a conversion error occurs, an `Err(ConversionError)` is returned.
     ///
-    /// # Panics
-    ///
-    /// * If `min` is greater than `max`, a panic occurs.
-    ///
     /// # Examples
     ///
     /// ```
@@ -533,26 +1077,31 @@ impl TestCase {
         if max == min {
             return T::from(min).ok_or(Error::ConversionError);
         }
-        assert!(min < max, "min must be less than max");
+        if min > max {
+            return Err(Error::new("min must be less than max"));
+        }
 
         let range = max.wrapping_sub(&min);
 
         if Self::is_signed::<T>() {
-            let signed_min = self._consume_int_s::<T>(is_little_endian)?;
-            let wrapped = if range == T::max_value()
-                || range == T::max_value().wrapping_sub(&T::min_value())
-            {
-                signed_min
+            // Work on the two's complement bits, as the range may exceed the maximum of `T`
+            let bits = std::mem::size_of::<T>() * 8;
+            let mask = u128::MAX >> (128 - bits);
+            let to_i128 = |v: T| v.to_i128().ok_or(Error::ConversionError);
+            let raw = to_i128(self._consume_int_s::<T>(is_little_endian)?)? as u128 & mask;
+            let range = to_i128(max)?.wrapping_sub(to_i128(min)?) as u128;
+            let offset = if range == mask {
+                raw
             } else {
-                signed_min.rem_euclid(&(range + T::from(1).unwrap()))
+                raw % (range + 1)
             };
-            Ok(min + wrapped)
+            T::from(to_i128(min)?.wrapping_add(offset as i128)).ok_or(Error::ConversionError)
         } else {
             let unsigned_min = self._consume_int_u::<T>(is_little_endian)?;
             let wrapped = if range == T::max_value() {
                 unsigned_min
             } else {
-                unsigned_min.rem_euclid(&(range + T::from(1).unwrap()))
+                unsigned_min.rem_euclid(&(range + T::one()))
             };
             Ok(min + wrapped)
         }
@@ -566,69 +1115,58 @@ impl TestCase {
             1 => T::from(vals[0]).ok_or(Error::ConversionError),
             2 => {
                 let ret = if is_little_endian {
-                    u16::from_le_bytes(vals.try_into().unwrap())
+                    u16::from_le_bytes(Self::to_array(&vals)?)
                 } else {
-                    u16::from_be_bytes(vals.try_into().unwrap())
+                    u16::from_be_bytes(Self::to_array(&vals)?)
                 };
                 T::from(ret).ok_or(Error::ConversionError)
             }
             4 => {
                 let ret = if is_little_endian {
-                    u32::from_le_bytes(vals.try_into().unwrap())
+                    u32::from_le_bytes(Self::to_array(&vals)?)
                 } else {
-                    u32::from_be_bytes(vals.try_into().unwrap())
+                    u32::from_be_bytes(Self::to_array(&vals)?)
                 };
                 T::from(ret).ok_or(Error::ConversionError)
             }
             8 => {
                 let ret = if is_little_endian {
-                    u64::from_le_bytes(vals.try_into().unwrap())
+                    u64::from_le_bytes(Self::to_array(&vals)?)
                 } else {
-                    u64::from_be_bytes(vals.try_into().unwrap())
+                    u64::from_be_bytes(Self::to_array(&vals)?)
                 };
                 T::from(ret).ok_or(Error::ConversionError)
             }
             16 => {
                 let ret = if is_little_endian {
-                    u128::from_le_bytes(vals.try_into().unwrap())
+                    u128::from_le_bytes(Self::to_array(&vals)?)
                 } else {
-                    u128::from_be_bytes(vals.try_into().unwrap())
+                    u128::from_be_bytes(Self::to_array(&vals)?)
                 };
                 T::from(ret).ok_or(Error::ConversionError)
             }
-            _ => {
-                unreachable!()
-            }
+            _ => Err(Error::ConversionError),
         }
     }
 
-    /// Consumes a single integer of type `T` from the stream as a signed integer with the specified endianness.
+    /// Consumes a single integer of type `T` from the stream as a signed integer with the specified
+    /// endianness. The bytes are reinterpreted as the two's complement representation of `T`, so
+    /// the whole range of `T` including negative values can be produced.
     fn _consume_int_s<T: PrimInt>(&mut self, is_little_endian: bool) -> Result<T> {
-        let bytes = std::mem::size_of::<T>();
-        let max_val = (1u128 << (bytes * 8 - 1)) - 1;
-        match bytes {
-            1 => {
-                let ret = self._consume_int_u::<u8>(is_little_endian)?;
-                T::from(ret % max_val as u8).ok_or(Error::ConversionError)
-            }
-            2 => {
-                let ret = self._consume_int_u::<u16>(is_little_endian)?;
-                T::from(ret % max_val as u16).ok_or(Error::ConversionError)
-            }
-            4 => {
-                let ret = self._consume_int_u::<u32>(is_little_endian)?;
-                T::from(ret % max_val as u32).ok_or(Error::ConversionError)
-            }
-            8 => {
-                let ret = self._consume_int_u::<u64>(is_little_endian)?;
-                T::from(ret % max_val as u64).ok_or(Error::ConversionError)
-            }
-            16 => {
-                let ret = self._consume_int_u::<u128>(is_little_endian)?;
-                T::from(ret % max_val).ok_or(Error::ConversionError)
-            }
-            _ => unreachable!(),
+        match std::mem::size_of::<T>() {
+            1 => T::from(self._consume_int_u::<u8>(is_little_endian)? as i8),
+            2 => T::from(self._consume_int_u::<u16>(is_little_endian)? as i16),
+            4 => T::from(self._consume_int_u::<u32>(is_little_endian)? as i32),
+            8 => T::from(self._consume_int_u::<u64>(is_little_endian)? as i64),
+            16 => T::from(self._consume_int_u::<u128>(is_little_endian)? as i128),
+            _ => None,
         }
+        .ok_or(Error::ConversionError)
+    }
+
+    /// Converts a consumed slice into a fixed size array, failing instead of panicking on a length mismatch.
+    fn to_array<const N: usize>(vals: &[u8]) -> Result<[u8; N]> {
+        vals.try_into().map_err(|_| Error::ConversionError)
     }
 
     /// Consumes an IEEE 754 floating-point number from the input data.
@@ -644,10 +1182,6 @@ impl TestCase {
     /// to consume those
     /// * Returns an error if the consumed slice dannot be converted to an `f64`
     ///
-    /// # Panics
-    ///
-    /// Panics if the consumed slice cannot be converted to an `f64`.
-    ///
     /// # Example
     ///
     /// ```
@@ -660,28 +1194,279 @@ impl TestCase {
     /// assert_eq!(tc.data_ptr, 8);
     /// ```
     pub fn consume_float(&mut self) -> Result<f64> {
-        if self.data_ptr == self.size {
+        self.align_to_byte();
+        if self.is_exhausted() {
             return Ok(0.0);
         }
-        if self.data_ptr + 8 > self.size {
+        if self.remaining() < 8 {
             let mut cdata = [0u8; 8];
-            let data_slice = &self.data[self.data_ptr..self.data_ptr + (self.size - self.data_ptr)];
+            let data_slice = &self.data[self.data_ptr..self.size];
             let bytes_read = std::io::Cursor::new(data_slice).read(&mut cdata[..])?;
             cdata[bytes_read..].iter_mut().for_each(|c| *c = 0);
             cdata.reverse();
             self.data_ptr = self.size;
             Ok(f64::from_bits(u64::from_le_bytes(cdata)))
         } else {
-            let ret = f64::from_bits(u64::from_le_bytes(
-                self.data[self.data_ptr..self.data_ptr + 8]
-                    .try_into()
-                    .unwrap(),
-            ));
+            let ret = f64::from_bits(u64::from_le_bytes(Self::to_array(
+                &self.data[self.data_ptr..self.data_ptr + 8],
+            )?));
 
             self.data_ptr += 8;
             Ok(ret)
         }
     }
+
+    /// Consumes a single precision IEEE 754 floating-point number from the input data.
+    /// The number is read as is, without any conversion. Like `consume_float`, fewer than 4
+    /// remaining bytes are zero padded and an exhausted stream yields `0.0`.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the consumed number. The consumed number may have a special value (e.g. NaN or infinity).
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if reading data from the test case offers less than 4 bytes and we fail
+    ///   to consume those
+    /// * Returns an error if the consumed slice cannot be converted to an `f32`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    /// let data = [0x00, 0x00, 0x80, 0x3f].to_vec();
+    /// let mut tc = TestCase::new(&data);
+    /// assert_eq!(tc.consume_f32().unwrap(), 1.0);
+    /// assert_eq!(tc.data_ptr, 4);
+    /// ```
+    pub fn consume_f32(&mut self) -> Result<f32> {
+        self.align_to_byte();
+        if self.is_exhausted() {
+            return Ok(0.0);
+        }
+        if self.remaining() < 4 {
+            let mut cdata = [0u8; 4];
+            let data_slice = &self.data[self.data_ptr..self.size];
+            let bytes_read = std::io::Cursor::new(data_slice).read(&mut cdata[..])?;
+            cdata[bytes_read..].iter_mut().for_each(|c| *c = 0);
+            cdata.reverse();
+            self.data_ptr = self.size;
+            Ok(f32::from_bits(u32::from_le_bytes(cdata)))
+        } else {
+            let ret = f32::from_bits(u32::from_le_bytes(Self::to_array(
+                &self.data[self.data_ptr..self.data_ptr + 4],
+            )?));
+
+            self.data_ptr += 4;
+            Ok(ret)
+        }
+    }
+
+    /// Consumes a finite floating-point number within `[min, max]`. The bits consumed by
+    /// `consume_float` are mapped uniformly onto the range instead of being reinterpreted, so
+    /// neither NaN nor infinity is ever returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - A `f64` indicating the lower bound of the range.
+    /// * `max` - A `f64` indicating the upper bound of the range.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<f64>` which is `Ok(f64)` if the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// * If `min` or `max` is not finite or `min` is greater than `max`, an `Err(Error)` is
+    ///   returned without consuming any data.
+    /// * If consuming the underlying `f64` fails, the error is passed on.
+    ///
+    /// If `min` equals `max`, `min` is returned without consuming any data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    /// let mut tc = TestCase::new(&[0xff; 8]);
+    /// assert_eq!(tc.consume_float_in_range(-1.0, 1.0).unwrap(), 1.0);
+    /// assert_eq!(tc.consume_float_in_range(0.5, 0.5).unwrap(), 0.5);
+    /// assert!(tc.consume_float_in_range(1.0, 0.0).is_err());
+    /// assert_eq!(tc.data_ptr, 8);
+    /// ```
+    pub fn consume_float_in_range(&mut self, min: f64, max: f64) -> Result<f64> {
+        if !min.is_finite() || !max.is_finite() {
+            return Err(Error::new("min and max must be finite"));
+        }
+        if min > max {
+            return Err(Error::new("min must be less than max"));
+        }
+        if min == max {
+            return Ok(min);
+        }
+        // The upper 53 bits give a uniform fraction within [0, 1]
+        let bits = self.consume_float()?.to_bits() >> 11;
+        let fraction = bits as f64 / ((1u64 << 53) - 1) as f64;
+        // Interpolating instead of scaling `max - min` avoids overflowing to infinity
+        let ret = min * (1.0 - fraction) + max * fraction;
+        Ok(ret.clamp(min, max))
+    }
+
+    /// Consumes a length-prefixed collection from the stream. A length byte is consumed first
+    /// and capped to the number of remaining bytes, as every element is expected to consume at
+    /// least one byte. `f` is then called up to that many times to consume the elements.
+    /// Consumption stops early at the first element `f` fails on, so the collection never
+    /// holds more elements than the stream can supply.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure consuming a single element from the stream.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<T>>` which is `Ok(Vec<T>)` holding the consumed elements if the operation
+    /// is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If the stream is exhausted before the length byte, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0x41, 0x42, 0x43]);
+    /// assert_eq!(tc.consume_vec_of(|tc| tc.consume_byte()).unwrap(), vec![0x41, 0x42]);
+    /// assert_eq!(tc.data_ptr, 3);
+    /// ```
+    pub fn consume_vec_of<T, F>(&mut self, mut f: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> Result<T>,
+    {
+        let len = usize::from(self.consume_byte()?).min(self.remaining());
+        let mut ret = Vec::with_capacity(len);
+        for _ in 0..len {
+            match f(self) {
+                Ok(element) => ret.push(element),
+                Err(_) => break,
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Consumes an input-driven nested structure (lists of lists) from the stream.
+    /// Before each element a control byte is consumed that decides what comes next:
+    /// `0` closes the current list, `1` opens a nested list, and everything else
+    /// consumes a single element via `leaf`. Once `depth_budget` nested lists are open,
+    /// further opening requests are treated as leaves. The structure ends when the stream is exhausted.
+    /// The depth is capped at 256 regardless of `depth_budget`.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth_budget` - The maximum nesting depth.
+    /// * `leaf` - A closure consuming a single element from the stream.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<Nested<T>>>` holding the elements of the top level list.
+    ///
+    /// # Errors
+    ///
+    /// If `leaf` fails, its error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::{Nested, TestCase};
+    ///
+    /// let mut tc = TestCase::new(&vec![0x02, 0x41, 0x01, 0x02, 0x42, 0x00, 0x02, 0x43]);
+    /// let nested = tc.consume_nested(4, |tc| tc.consume_byte()).unwrap();
+    /// assert_eq!(
+    ///     nested,
+    ///     vec![
+    ///         Nested::Leaf(0x41),
+    ///         Nested::List(vec![Nested::Leaf(0x42)]),
+    ///         Nested::Leaf(0x43),
+    ///     ]
+    /// );
+    /// ```
+    pub fn consume_nested<F, T>(
+        &mut self,
+        depth_budget: usize,
+        mut leaf: F,
+    ) -> Result<Vec<Nested<T>>>
+    where
+        F: FnMut(&mut Self) -> Result<T>,
+    {
+        self._consume_nested(depth_budget.min(MAX_NESTING_DEPTH), &mut leaf)
+    }
+
+    /// Consumes a selection of `k` distinct indices in `[0, n)`. The indices are picked by a
+    /// partial Fisher-Yates shuffle driven by the stream, so the same data always yields the same
+    /// selection in the same order. Each pick consumes the smallest integer width able to hold
+    /// the number of indices left to choose from. If `k` is larger than `n`, all `n` indices are
+    /// returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - A `usize` indicating the number of indices to choose from.
+    /// * `k` - A `usize` indicating the number of distinct indices to select.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<usize>>` which is `Ok(Vec<usize>)` holding `min(k, n)` distinct indices if
+    /// the operation is successful, or an `Err(Error)` if not.
+    ///
+    /// # Errors
+    ///
+    /// If the stream runs out of data before all indices are selected, an `Err(Error)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use test_case::TestCase;
+    ///
+    /// let mut tc = TestCase::new(&vec![0x03, 0x00, 0x05]);
+    /// assert_eq!(tc.consume_distinct_indices(4, 2).unwrap(), vec![3, 1]);
+    /// assert_eq!(tc.data_ptr, 2);
+    /// ```
+    pub fn consume_distinct_indices(&mut self, n: usize, k: usize) -> Result<Vec<usize>> {
+        let k = k.min(n);
+        // Every pick but the last one consumes at least a byte, so no more can be preallocated
+        let capacity = k.min(self.remaining().saturating_add(1));
+        // Sparse representation of the swapped positions of the virtual array `[0, n)`
+        let mut swapped = HashMap::with_capacity(capacity);
+        let mut indices = Vec::with_capacity(capacity);
+        for i in 0..k {
+            let j = i + self._consume_usize_upto(n - i - 1)?;
+            let picked = *swapped.get(&j).unwrap_or(&j);
+            swapped.insert(j, *swapped.get(&i).unwrap_or(&i));
+            indices.push(picked);
+        }
+        Ok(indices)
+    }
+
+    fn _consume_nested<F, T>(&mut self, depth_budget: usize, leaf: &mut F) -> Result<Vec<Nested<T>>>
+    where
+        F: FnMut(&mut Self) -> Result<T>,
+    {
+        let mut nodes = Vec::new();
+        while !self.is_exhausted() {
+            match self.consume_byte()? {
+                0 => break,
+                1 if depth_budget > 0 => {
+                    nodes.push(Nested::List(self._consume_nested(depth_budget - 1, leaf)?));
+                }
+                _ => {
+                    if self.is_exhausted() {
+                        break;
+                    }
+                    nodes.push(Nested::Leaf(leaf(self)?));
+                }
+            }
+        }
+        Ok(nodes)
+    }
 }
 
 #[cfg(test)]
@@ -736,16 +1521,17 @@ mod tests {
     #[test]
     fn test_remaining_bytes() {
         let mut tc = setup();
-        assert_eq!(tc.size - tc.data_ptr, 1024);
+        assert_eq!(tc.remaining(), 1024);
+        assert!(!tc.is_exhausted());
         let _ = tc.consume_bool();
-        assert_eq!(tc.size - tc.data_ptr, 1023);
+        assert_eq!(tc.remaining(), 1023);
         let _ = tc.consume_bool();
         let _ = tc.consume_bool();
-        assert_eq!(tc.size - tc.data_ptr, 1021);
+        assert_eq!(tc.remaining(), 1021);
         let _ = tc.consume_bool();
-        assert_eq!(tc.size - tc.data_ptr, 1020);
+        assert_eq!(tc.remaining(), 1020);
         let _ = tc.consume_byte();
-        assert_eq!(tc.size - tc.data_ptr, 1019);
+        assert_eq!(tc.remaining(), 1019);
     }
 
     #[test]
@@ -753,14 +1539,101 @@ mod tests {
         let mut tc = setup();
         let b = tc.consume_booleans(5);
         assert_eq!([false, true, true, false, true], b.unwrap().as_slice());
-        assert_eq!(tc.size - tc.data_ptr, 1019);
+        assert_eq!(tc.remaining(), 1019);
         let _ = tc.consume_float();
-        assert_eq!(tc.size - tc.data_ptr, 1011);
+        assert_eq!(tc.remaining(), 1011);
         let b = tc.consume_booleans(5);
         assert_eq!([false, false, true, false, false], b.unwrap().as_slice());
-        assert_eq!(tc.size - tc.data_ptr, 1006);
+        assert_eq!(tc.remaining(), 1006);
         let _b = tc.consume_booleans(1000);
-        assert_eq!(tc.size - tc.data_ptr, 6);
+        assert_eq!(tc.remaining(), 6);
+    }
+
+    #[test]
+    fn test_consume_bits() {
+        let mut tc = TestCase::new(&[0b1011_0101]);
+        assert_eq!(tc.consume_bits(3).unwrap(), 0b101);
+        assert_eq!((tc.data_ptr, tc.bit_ptr), (0, 3));
+        assert!(!tc.is_exhausted());
+        assert_eq!(tc.remaining(), 0);
+        assert_eq!(tc.consume_bits(5).unwrap(), 0b10101);
+        assert_eq!((tc.data_ptr, tc.bit_ptr), (1, 0));
+        assert!(tc.is_exhausted());
+
+        let data = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x0f];
+        let mut tc = TestCase::new(&data);
+        assert_eq!(tc.consume_bits(4).unwrap(), 0x1);
+        assert_eq!(tc.consume_bits(64).unwrap(), 0x2345_6789_abcd_ef00);
+        assert!(tc.consume_bits(5).is_err());
+        assert_eq!((tc.data_ptr, tc.bit_ptr), (8, 4));
+        assert_eq!(tc.consume_bits(4).unwrap(), 0xf);
+
+        // Byte oriented consumers skip the rest of a partially consumed byte
+        let mut tc = TestCase::new(&[0xff, 0x01, 0x02]);
+        let _ = tc.consume_bits(1).unwrap();
+        assert_eq!(tc.remaining(), 2);
+        assert_eq!(tc.consume_remaining_as_bytes().unwrap(), vec![0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_peek_mark_seek() {
+        let mut tc = setup();
+        let _ = tc.consume_bytes(3).unwrap();
+        let mark = tc.mark();
+        let peeked = tc.peek_byte().unwrap();
+        let first = tc.consume_bytes(16).unwrap();
+        assert_eq!(first[0], peeked);
+        tc.seek(mark).unwrap();
+        assert_eq!(tc.peek_byte().unwrap(), peeked);
+        assert_eq!(tc.consume_bytes(16).unwrap(), first);
+
+        // Seeking discards pending bits
+        let _ = tc.consume_bits(3).unwrap();
+        assert_eq!(tc.peek_byte().unwrap(), tc.data[tc.mark() + 1]);
+        tc.seek(mark).unwrap();
+        assert_eq!(tc.bit_ptr, 0);
+        assert_eq!(tc.consume_byte().unwrap(), peeked);
+
+        let size = tc.size;
+        assert!(tc.seek(size + 1).is_err());
+        assert_eq!(tc.mark(), mark + 1);
+        tc.seek(size).unwrap();
+        assert!(tc.peek_byte().is_err());
+        assert!(tc.is_exhausted());
+    }
+
+    #[test]
+    fn test_consume_bool_weighted() {
+        let data = (0..=255).cycle().take(256 * 16).collect::<Vec<u8>>();
+        for (numerator, denominator) in [(1, 10), (1, 2), (3, 4), (1, 1000), (5, 3)] {
+            let mut tc = TestCase::new(&data);
+            let mut hits = 0;
+            while let Ok(b) = tc.consume_bool_weighted(numerator, denominator) {
+                hits += usize::from(b);
+            }
+            let expected = data.len() as f64 * (f64::from(numerator) / f64::from(denominator));
+            assert!((hits as f64 - expected.min(data.len() as f64)).abs() <= 16.0);
+        }
+
+        let mut tc = TestCase::new(&[0x00]);
+        assert!(tc.consume_bool_weighted(1, 0).is_err());
+        assert!(!tc.consume_bool_weighted(0, 1).unwrap());
+        assert!(tc.consume_bool_weighted(1, 2).is_err());
+    }
+
+    #[test]
+    fn test_new_with_offset() {
+        let data = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x00, 0x02];
+        let mut tc = TestCase::new_with_offset(&data, 4).unwrap();
+        assert_eq!(tc.data_ptr, 4);
+        assert_eq!(tc.remaining(), 3);
+        assert_eq!(tc.consume_int::<u16>(true).unwrap(), 0x0001);
+        assert_eq!(tc.consume_remaining_as_bytes().unwrap(), vec![0x02]);
+
+        let mut tc = TestCase::new_with_offset(&data, data.len()).unwrap();
+        assert!(tc.is_exhausted());
+        assert!(tc.consume_byte().is_err());
+        assert!(TestCase::new_with_offset(&data, data.len() + 1).is_err());
     }
 
     #[test]
@@ -768,10 +1641,19 @@ mod tests {
         let mut tc = setup();
         for i in 0..128 {
             assert_eq!(tc.consume_byte().unwrap(), tc.data[i]);
-            assert_eq!(tc.size - tc.data_ptr, 1024 - 1 - i);
+            assert_eq!(tc.remaining(), 1024 - 1 - i);
         }
     }
 
+    #[test]
+    fn test_default_is_empty() {
+        let mut tc = TestCase::default();
+        assert_eq!(tc.size, tc.data.len());
+        assert!(tc.is_exhausted());
+        assert!(tc.consume_byte().is_err());
+        assert!(tc.consume_bytes(16).is_err());
+    }
+
     #[test]
     fn test_consume_bytes() {
         let mut tc = setup();
@@ -781,11 +1663,69 @@ mod tests {
             let ret = tc.consume_bytes(step);
             assert!(ret.is_ok());
             assert_eq!(ret.unwrap(), tc.data[i..i + 16]);
-            assert_eq!(tc.size - tc.data_ptr, 1024 - (step * ctr));
+            assert_eq!(tc.remaining(), 1024 - (step * ctr));
             ctr += 1;
         }
     }
 
+    #[test]
+    fn test_consume_array() {
+        let mut tc = setup();
+        let guid = tc.consume_array::<16>();
+        assert_eq!(guid.unwrap(), tc.data[..16]);
+        assert_eq!(tc.data_ptr, 16);
+
+        // Exact length
+        reset_with_data(&mut tc, vec![0x42, 0x24, 0x13, 0x37]);
+        assert_eq!(tc.consume_array::<4>().unwrap(), [0x42, 0x24, 0x13, 0x37]);
+        assert!(tc.is_exhausted());
+
+        // Shorter than the remaining data
+        reset_with_data(&mut tc, vec![0x42, 0x24, 0x13, 0x37]);
+        assert_eq!(tc.consume_array::<3>().unwrap(), [0x42, 0x24, 0x13]);
+        assert_eq!(tc.remaining(), 1);
+
+        // Longer than the remaining data
+        reset_with_data(&mut tc, vec![0x42, 0x24, 0x13, 0x37]);
+        assert!(tc.consume_array::<5>().is_err());
+        assert_eq!(tc.data_ptr, 0);
+        assert_eq!(tc.consume_array::<0>().unwrap(), []);
+    }
+
+    #[test]
+    fn test_consume_nested() {
+        fn depth(nodes: &[Nested<u8>]) -> usize {
+            nodes
+                .iter()
+                .map(|n| match n {
+                    Nested::Leaf(_) => 0,
+                    Nested::List(l) => 1 + depth(l),
+                })
+                .max()
+                .unwrap_or(0)
+        }
+
+        // Opening requests beyond the depth budget are treated as leaves
+        let mut tc = TestCase::new(&[0x01, 0x01, 0x41, 0x02, 0x42]);
+        let nested = tc.consume_nested(1, |tc| tc.consume_byte()).unwrap();
+        assert_eq!(
+            nested,
+            vec![Nested::List(vec![Nested::Leaf(0x41), Nested::Leaf(0x42)])]
+        );
+        assert!(tc.is_exhausted());
+
+        // Same input yields the same structure
+        let mut tc = setup();
+        let first = tc.consume_nested(3, |tc| tc.consume_byte()).unwrap();
+        assert!(depth(&first) <= 3);
+        tc.data_ptr = 0;
+        assert_eq!(first, tc.consume_nested(3, |tc| tc.consume_byte()).unwrap());
+
+        // Errors of the leaf consumer are propagated
+        let mut tc = TestCase::new(&[0x02, 0x42]);
+        assert!(tc.consume_nested(2, |tc| tc.consume_bytes(2)).is_err());
+    }
+
     #[test]
     fn test_consume_rem_bytes() {
         let mut tc = setup();
@@ -793,6 +1733,7 @@ mod tests {
         assert!(b.is_ok());
         assert_eq!(b.unwrap(), tc.data[..]);
         assert_eq!(tc.data_ptr, tc.size);
+        assert!(tc.is_exhausted());
         let b = tc.consume_bytes(16);
         assert!(b.is_err());
         match b {
@@ -856,7 +1797,7 @@ mod tests {
         let mut tc = setup();
         let b = tc.consume_int::<i8>(true);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 0xb);
+        assert_eq!(b.unwrap(), -0x76);
         assert_eq!(tc.data_ptr, 1);
         let b = tc.consume_int::<i16>(true);
         assert!(b.is_ok());
@@ -868,11 +1809,11 @@ mod tests {
         assert_eq!(tc.data_ptr, 7);
         let b = tc.consume_int::<i64>(true);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 0x7288aadaf3aa9b5f);
+        assert_eq!(b.unwrap(), -0xd7755250c5564a2);
         assert_eq!(tc.data_ptr, 15);
         let b = tc.consume_int::<i128>(true);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 0x5090c91c7f1aacb813cff2b1beba6c9c);
+        assert_eq!(b.unwrap(), -0x2f6f36e380e55347ec300d4e41459365);
         assert_eq!(tc.data_ptr, 31);
     }
 
@@ -881,7 +1822,7 @@ mod tests {
         let mut tc = setup();
         let b = tc.consume_int::<i8>(false);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 0xb);
+        assert_eq!(b.unwrap(), -0x76);
         assert_eq!(tc.data_ptr, 1);
         let b = tc.consume_int::<i16>(false);
         assert!(b.is_ok());
@@ -897,16 +1838,89 @@ mod tests {
         assert_eq!(tc.data_ptr, 15);
         let b = tc.consume_int::<i128>(false);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 0x1b6cbabeb1f2cf13b8ac1a7f1cc990d1);
+        assert_eq!(b.unwrap(), -0x649345414e0d30ec4753e580e3366f30);
         assert_eq!(tc.data_ptr, 31);
     }
 
+    #[test]
+    fn test_consume_int_s_all_ones() {
+        let mut tc = TestCase::new(&[0xff; 31]);
+        assert_eq!(tc.consume_int::<i8>(true).unwrap(), -1);
+        assert_eq!(tc.consume_int::<i16>(false).unwrap(), -1);
+        assert_eq!(tc.consume_int::<i32>(true).unwrap(), -1);
+        assert_eq!(tc.consume_int::<i64>(false).unwrap(), -1);
+        assert_eq!(tc.consume_int::<i128>(true).unwrap(), -1);
+        assert!(tc.is_exhausted());
+
+        let mut tc = TestCase::new(&[0x80, 0x7f]);
+        assert_eq!(tc.consume_int::<i8>(true).unwrap(), i8::MIN);
+        assert_eq!(tc.consume_int::<i8>(true).unwrap(), i8::MAX);
+        let mut tc = TestCase::new(&[0xff, 0x00]);
+        assert_eq!(
+            tc.consume_int_range::<i8>(true, i8::MIN, i8::MAX).unwrap(),
+            127
+        );
+        assert_eq!(tc.consume_int_range::<i8>(true, -10, -5).unwrap(), -10);
+    }
+
+    #[test]
+    fn test_consume_float_in_range() {
+        let ranges = [
+            (0.0, 1.0),
+            (-1e300, 1e300),
+            (f64::MIN, f64::MAX),
+            (-5.5, -5.25),
+        ];
+        for fill in [0x00, 0xff, 0x7f, 0x80] {
+            for (min, max) in ranges {
+                for len in [0, 3, 8] {
+                    let mut tc = TestCase::new(&vec![fill; len]);
+                    let val = tc.consume_float_in_range(min, max).unwrap();
+                    assert!(val.is_finite() && (min..=max).contains(&val));
+                }
+            }
+        }
+        let mut tc = TestCase::new(&[0x00; 8]);
+        assert_eq!(tc.consume_float_in_range(-2.0, 2.0).unwrap(), -2.0);
+        assert!(tc.consume_float_in_range(0.0, f64::INFINITY).is_err());
+
+        let mut tc = TestCase::new(&[0x00, 0x00, 0xc0, 0x7f, 0x00, 0x00, 0x80, 0xbf, 0x01]);
+        assert!(tc.consume_f32().unwrap().is_nan());
+        assert_eq!(tc.consume_f32().unwrap(), -1.0);
+        assert_eq!(tc.data_ptr, 8);
+        assert_eq!(tc.consume_f32().unwrap().to_bits(), 0x0100_0000);
+        assert!(tc.is_exhausted());
+    }
+
+    #[test]
+    fn test_consume_vec_of() {
+        for len in 0..16 {
+            for count in [0x00, 0x03, 0x07, 0xff] {
+                let mut data = vec![count];
+                data.extend((0..len).map(|i| i as u8));
+                let mut tc = TestCase::new(&data);
+                let vals = tc.consume_vec_of(|tc| tc.consume_int::<u16>(true)).unwrap();
+                assert_eq!(vals.len(), usize::from(count).min(len / 2));
+                assert!(tc.data_ptr <= tc.size);
+            }
+        }
+        let mut tc = TestCase::new(&[0x02, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00]);
+        assert_eq!(
+            tc.consume_vec_of(|tc| tc.consume_int::<u16>(true)).unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(tc.remaining(), 2);
+        assert!(TestCase::new(&[])
+            .consume_vec_of(|tc| tc.consume_byte())
+            .is_err());
+    }
+
     #[test]
     fn test_consume_int_range() {
         let mut tc = setup();
         let b = tc.consume_int_range::<i8>(false, 80, 120);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 91);
+        assert_eq!(b.unwrap(), 95);
         assert_eq!(tc.data_ptr, 1);
         let b = tc.consume_int_range::<i16>(false, 80, 256);
         assert!(b.is_ok());
@@ -922,7 +1936,7 @@ mod tests {
         assert_eq!(tc.data_ptr, 15);
         let b = tc.consume_int_range::<i128>(false, 10, 15);
         assert!(b.is_ok());
-        assert_eq!(b.unwrap(), 13);
+        assert_eq!(b.unwrap(), 14);
         assert_eq!(tc.data_ptr, 31);
         let b = tc.consume_int_range::<u8>(false, 253, 255);
         assert!(b.is_ok());
@@ -953,7 +1967,7 @@ mod tests {
         assert!(b.is_ok());
         assert_eq!(
             b.unwrap(),
-            [91, 105, 93, 107, 94, 93, 95, 92, 108, 82, 114, 89]
+            [95, 105, 93, 107, 94, 93, 95, 92, 112, 86, 118, 93]
         );
         assert_eq!(tc.data_ptr, 12);
         let b = tc.consume_ints_range::<u32>(false, 8, u32::MIN, 1025);
@@ -980,6 +1994,126 @@ mod tests {
         assert_eq!(tc.data_ptr, 80);
     }
 
+    #[test]
+    fn test_consume_byte_biased() {
+        // Xorshift generated bytes so selector and raw bytes are not correlated
+        let mut state = 0xdeadbeefcafebabe_u64;
+        let data = (0..64 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        let mut tc = TestCase::new(&data);
+        let mut counts = [0usize; 256];
+        let mut total = 0;
+        while let Ok(b) = tc.consume_byte_biased() {
+            counts[b as usize] += 1;
+            total += 1;
+        }
+        // A uniform distribution would yield each value in 1/256 of all cases
+        assert!(counts[0x00] * 8 > total);
+        assert!(counts[0xff] * 8 > total);
+        assert!(counts.iter().all(|&c| c > 0));
+
+        let mut tc = TestCase::new(&[0x80]);
+        assert!(tc.consume_byte_biased().is_err());
+        assert_eq!(tc.data_ptr, 0);
+    }
+
+    #[test]
+    fn test_consume_distinct_indices() {
+        let mut tc = setup();
+        for (n, k) in [(1, 1), (8, 3), (10, 10), (300, 16), (70_000, 32)] {
+            let indices = tc.consume_distinct_indices(n, k).unwrap();
+            assert_eq!(indices.len(), k);
+            assert!(indices.iter().all(|&i| i < n));
+            let mut sorted = indices.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            assert_eq!(sorted.len(), k);
+        }
+
+        // Same data, same selection
+        let a = setup().consume_distinct_indices(100, 20).unwrap();
+        let b = setup().consume_distinct_indices(100, 20).unwrap();
+        assert_eq!(a, b);
+
+        // `k > n` selects every index exactly once
+        let mut all = tc.consume_distinct_indices(5, 9).unwrap();
+        all.sort_unstable();
+        assert_eq!(all, vec![0, 1, 2, 3, 4]);
+        assert_eq!(tc.consume_distinct_indices(0, 3).unwrap(), vec![]);
+
+        let mut tc = TestCase::new(&[0x01]);
+        assert!(tc.consume_distinct_indices(10, 2).is_err());
+    }
+
+    #[test]
+    fn test_consume_string_bounded() {
+        let mut tc = TestCase::new(&[0x05, b'h', b'e', b'l', b'l', b'o', b'!']);
+        let s = tc.consume_string_bounded(5, Encoding::UTF8, true);
+        assert_eq!(s.unwrap(), "hello");
+        assert_eq!(tc.data_ptr, 6);
+
+        // The length is bounded by `max_len` ...
+        let mut tc = TestCase::new(&[0xff, b'a', b'b', b'c', b'd']);
+        let s = tc.consume_string_bounded(3, Encoding::UTF8, true);
+        assert_eq!(s.unwrap(), "abc");
+        assert_eq!(tc.data_ptr, 4);
+
+        // ... and by the data that is left
+        let mut tc = TestCase::new(&[0x10, b'a', b'b']);
+        let s = tc.consume_string_bounded(0x20, Encoding::UTF8, true);
+        assert_eq!(s.unwrap(), "ab");
+        assert_eq!(tc.data_ptr, 3);
+
+        // A `max_len` above `u8::MAX` reads a wider length
+        let mut tc = TestCase::new(&[0x02, 0x00, b'o', b'k']);
+        let s = tc.consume_string_bounded(0x1000, Encoding::UTF8, true);
+        assert_eq!(s.unwrap(), "ok");
+        assert_eq!(tc.data_ptr, 4);
+
+        let mut tc = TestCase::new(&[]);
+        assert!(tc.consume_string_bounded(8, Encoding::UTF8, true).is_err());
+    }
+
+    #[test]
+    fn test_consume_string_bounded_invalid_utf8() {
+        // Truncated multi-byte sequence, lone continuation byte and an overlong encoding
+        for invalid in [
+            &[0xe2, 0x82][..],
+            &[0x80],
+            &[0xc0, 0xaf],
+            &[b'a', 0xff, b'b'],
+        ] {
+            let mut data = vec![invalid.len() as u8];
+            data.extend_from_slice(invalid);
+
+            let mut tc = TestCase::new(&data);
+            assert!(tc.consume_string_bounded(16, Encoding::UTF8, true).is_err());
+            assert_eq!(tc.data_ptr, 0);
+
+            let s = tc.consume_string_bounded(16, Encoding::UTF8, false);
+            assert!(s.unwrap().contains('\u{fffd}'));
+            assert_eq!(tc.data_ptr, data.len());
+        }
+
+        // Unpaired surrogate in UTF16
+        let mut data = vec![0x01];
+        data.extend_from_slice(&0xd800u16.to_ne_bytes());
+        let mut tc = TestCase::new(&data);
+        assert!(tc
+            .consume_string_bounded(16, Encoding::UTF16, true)
+            .is_err());
+        assert_eq!(tc.data_ptr, 0);
+        let s = tc.consume_string_bounded(16, Encoding::UTF16, false);
+        assert_eq!(s.unwrap(), "\u{fffd}");
+        assert_eq!(tc.data_ptr, 3);
+    }
+
     #[test]
     fn test_consume_rem_str() {
         let mut tc = setup();
@@ -997,7 +2131,7 @@ mod tests {
     #[test]
     fn test_consume_float() {
         let mut tc = setup();
-        tc.data = [0, 0, 0, 0, 0, 0, 0xf0, 0x3f, 0xa].to_vec();
+        reset_with_data(&mut tc, [0, 0, 0, 0, 0, 0, 0xf0, 0x3f, 0xa].to_vec());
         let b = tc.consume_float();
         assert!(b.is_ok());
         assert_eq!(b.unwrap(), 1.0);
@@ -1121,6 +2255,79 @@ mod tests {
         assert_eq!(b.unwrap(), 1.7889445e-317);
     }
 
+    #[test]
+    fn test_consume_int_range_min_greater_than_max() {
+        let mut tc = setup();
+        assert!(tc.consume_int_range::<u8>(true, 2, 1).is_err());
+        assert!(tc.consume_int_range::<i64>(false, 0, i64::MIN).is_err());
+        assert_eq!(tc.data_ptr, 0);
+        assert_eq!(
+            tc.consume_ints_range::<u16>(true, 4, 9, 3).unwrap(),
+            vec![0; 4]
+        );
+    }
+
+    #[test]
+    fn test_consumers_never_panic() {
+        fn consume_all(data: &[u8]) {
+            let fresh = || TestCase::new(data);
+            let _ = fresh().consume_bool();
+            let _ = fresh().consume_booleans(usize::MAX);
+            let _ = fresh().consume_bool_weighted(u32::MAX, 0);
+            let _ = fresh().consume_bool_weighted(u32::MAX, 1);
+            let _ = fresh().consume_byte();
+            let _ = fresh().consume_bits(0);
+            let _ = fresh().consume_bits(64);
+            let _ = fresh().consume_bits(65);
+            let _ = fresh().consume_enum::<0>();
+            let _ = fresh().consume_enum::<{ usize::MAX }>();
+            let _ = fresh().consume_pick::<u8>(&[]);
+            let _ = fresh().consume_byte_biased();
+            let _ = fresh().consume_bytes(usize::MAX);
+            let _ = fresh().consume_remaining_as_bytes();
+            let _ = fresh().consume_array::<3>();
+            for encoding in [Encoding::UTF8, Encoding::UTF8ASCII, Encoding::UTF16] {
+                let _ = fresh().consume_str(usize::MAX, encoding);
+                let _ = fresh().consume_remaining_as_str(encoding);
+                let _ = fresh().consume_string_bounded(usize::MAX, encoding, true);
+                let _ = fresh().consume_string_bounded(usize::MAX, encoding, false);
+            }
+            let _ = fresh().consume_int::<i8>(true);
+            let _ = fresh().consume_int::<u128>(false);
+            let _ = fresh().consume_ints::<u64>(true, usize::MAX);
+            let _ = fresh().consume_ints_range::<i128>(true, usize::MAX, i128::MIN, i128::MAX);
+            let _ = fresh().consume_int_range::<i8>(true, i8::MIN, i8::MAX);
+            let _ = fresh().consume_int_range::<i16>(false, -1, i16::MAX);
+            let _ = fresh().consume_int_range::<i32>(true, i32::MIN, 0);
+            let _ = fresh().consume_int_range::<u64>(false, 0, u64::MAX);
+            let _ = fresh().consume_int_range::<usize>(true, usize::MAX, 0);
+            let _ = fresh().consume_float();
+            let _ = fresh().consume_f32();
+            let _ = fresh().consume_float_in_range(f64::MIN, f64::MAX);
+            let _ = fresh().consume_float_in_range(f64::NAN, 1.0);
+            let _ = fresh().consume_nested(usize::MAX, |tc| tc.consume_byte());
+            let _ = fresh().consume_vec_of(|tc| tc.consume_int::<u64>(true));
+            let _ = fresh().consume_distinct_indices(usize::MAX, usize::MAX);
+            let _ = fresh().consume_distinct_indices(0, usize::MAX);
+        }
+
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        let random = (0..4096)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        for len in [0, 1, 2, 3, 7, 9, 17, 4096] {
+            consume_all(&random[..len]);
+            consume_all(&vec![0x00; len]);
+            consume_all(&vec![0x01; len]);
+            consume_all(&vec![0xff; len]);
+        }
+    }
+
     fn reset_with_data(tc: &mut TestCase, data: Vec<u8>) {
         tc.data = data;
         tc.size = tc.data.len();
@@ -1210,6 +2417,7 @@ mod tests {
             data,
             size,
             data_ptr: 0,
+            bit_ptr: 0,
             energy: 0,
             accessed: Vec::new(),
         }
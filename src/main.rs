@@ -1,27 +1,49 @@
-use clap::{builder::PossibleValuesParser, Parser};
+use clap::{
+    builder::{PossibleValuesParser, TypedValueParser},
+    Parser,
+};
 use errors::Result;
-use executor::{spawn_workers, FuzzerConfig, FuzzerStats};
+use executor::{
+    dry_run, install_stop_handler, join_workers, replay, serve_metrics, spawn_workers,
+    CorpusFormat, FuzzerConfig, FuzzerStats, RandomSeedConfig, SeedSizeDist,
+};
 use grammar_mutator::GrammarTemplate;
 use prng::Generators;
-use std::time::Instant;
+use std::{os::unix::process::ExitStatusExt, path::PathBuf};
+
+// Number of test cases a dry run prints when no dump directory is given
+const DRY_RUN_PRINTED: usize = 10;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Clargs {
     #[clap(
         last(true),
-        required(true),
-        help = "Target binary to fuzz including args. e.g. ./target -a -b -c. Append @@ to fuzz from file. e.g. ./target -a -b -c @@."
+        required_unless_present_any = ["harness", "dry_run"],
+        help = "Target binary to fuzz including args. e.g. ./target -a -b -c. Use @@ to fuzz from file, also within an argument. e.g. ./target -a -b -c @@ or ./target --in=@@."
     )]
     target: Vec<String>,
     #[clap(
         short,
         long,
         default_value = "./.corpus",
-        help = "A directory containing a seed corpus."
+        help = "A directory containing a seed corpus. This may also be a crash directory of a previous run."
     )]
     #[arg(short = 'i')]
     corpus_dir: String,
+    #[clap(
+        long,
+        default_value = "hantu",
+        help = "Layout of the corpus directory, allows importing the queue or corpus of AFL or libFuzzer"
+    )]
+    #[arg(value_parser = PossibleValuesParser::new(CorpusFormat::NAMES).map(|s| s.parse::<CorpusFormat>().unwrap()))]
+    corpus_format: CorpusFormat,
+    #[clap(
+        long,
+        default_value = "1048576",
+        help = "Maximum size in bytes of a seed in the corpus directory, larger files are skipped"
+    )]
+    max_seed_size: usize,
     #[clap(
         short,
         long,
@@ -30,6 +52,11 @@ struct Clargs {
     )]
     #[arg(short = 'o')]
     crash_dir: String,
+    #[clap(
+        long,
+        help = "Favor crashes found in the corpus directory when picking test cases to mutate"
+    )]
+    focus_crashes: bool,
     #[clap(short, long, default_value = None, help = "An optional dictionary file of newline separated entries that are used in the mutator")]
     user_dict: Option<String>,
     #[clap(short, long, default_value = None, help = "Maximum number of iterations to run for")]
@@ -47,6 +74,19 @@ struct Clargs {
     grammar_mutator: Option<String>,
     #[clap(long, help = "Enable the optional ni mutator")]
     ni_mutator: bool,
+    #[clap(
+        long,
+        value_delimiter = ',',
+        conflicts_with = "disable_mutators",
+        help = "Comma separated list of the only standard mutators to use, e.g. change_bit,negate_byte"
+    )]
+    only_mutators: Vec<String>,
+    #[clap(
+        long,
+        value_delimiter = ',',
+        help = "Comma separated list of standard mutators to disable, e.g. truncate,splice"
+    )]
+    disable_mutators: Vec<String>,
     #[clap(
         long,
         help = "Enforce the generated test cases to only contain printable characters"
@@ -64,12 +104,100 @@ struct Clargs {
         help = "Maximum length of a test case when generating new ones"
     )]
     max_length: usize,
+    #[clap(
+        long,
+        default_value = None,
+        help = "Number of iterations spent mutating each new crash to find related crashes"
+    )]
+    crash_exploration: Option<usize>,
+    #[clap(
+        long,
+        default_value = "128",
+        help = "Number of random seeds added to the corpus, 0 disables them"
+    )]
+    random_seeds: usize,
+    #[clap(
+        long,
+        default_value = "uniform:98304",
+        help = "Size distribution of the random seeds: uniform:<max>, fixed:<n> or exponential:<mean>"
+    )]
+    random_seed_size: SeedSizeDist,
     #[clap(
         long,
         default_value = None,
         help = "Maximum time in minutes to run for"
     )]
     max_time: Option<usize>,
+    #[clap(
+        long,
+        default_value = None,
+        help = "Serve Prometheus metrics via HTTP on 127.0.0.1 on the given port"
+    )]
+    metrics_port: Option<u16>,
+    #[clap(
+        long,
+        value_name = "KEY=VALUE",
+        value_parser = parse_env,
+        help = "Environment variable to set for the target, may be given multiple times"
+    )]
+    env: Vec<(String, String)>,
+    #[clap(
+        long,
+        default_value = None,
+        help = "Limit the address space of the target to the given number of MiB"
+    )]
+    mem_limit_mb: Option<u64>,
+    #[clap(
+        long,
+        conflicts_with = "persistent",
+        help = "Run a target instrumented with the AFL forkserver through its forkserver, requires @@"
+    )]
+    forkserver: bool,
+    #[clap(
+        long,
+        default_value = None,
+        help = "Append the stats as one JSON object per line to the given file on every update"
+    )]
+    stats_file: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Pass an AFL style edge bitmap to the target via __AFL_SHM_ID and keep test cases covering new edges"
+    )]
+    coverage: bool,
+    #[clap(
+        long,
+        requires = "harness",
+        help = "Call the harness given via --harness in-process instead of spawning the target for every test case"
+    )]
+    persistent: bool,
+    #[clap(
+        long,
+        default_value = None,
+        requires = "persistent",
+        help = "Shared library exporting LLVMFuzzerTestOneInput that is fuzzed in persistent mode"
+    )]
+    harness: Option<String>,
+    #[clap(
+        long,
+        default_value = None,
+        conflicts_with = "persistent",
+        help = "Run the target once on the given input, e.g. a saved crash, print how it exited and quit"
+    )]
+    replay: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = None,
+        conflicts_with = "replay",
+        help = "Only generate the given number of test cases without running the target, see --dump-dir"
+    )]
+    dry_run: Option<usize>,
+    #[clap(
+        long,
+        default_value = None,
+        requires = "dry_run",
+        help = "Directory to write the test cases of --dry-run to instead of printing the first ones as hex"
+    )]
+    dump_dir: Option<PathBuf>,
     #[clap(
         short,
         long,
@@ -79,17 +207,32 @@ struct Clargs {
     batch_sz: usize,
 }
 
+fn parse_env(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("Expected KEY=VALUE, got {s}"))
+}
+
 impl From<Clargs> for FuzzerConfig {
     fn from(args: Clargs) -> Self {
-        Self::default()
-            .set_target(args.target)
+        let config = if args.target.is_empty() {
+            Self::default()
+        } else {
+            Self::default().set_target(args.target)
+        };
+        config
             .set_corpus_dir(&args.corpus_dir)
+            .set_corpus_format(args.corpus_format)
+            .set_max_seed_size(Some(args.max_seed_size))
+            .set_focus_crashes(args.focus_crashes)
             .set_crash_dir(&args.crash_dir)
             .set_threads(args.threads)
             .set_batch_sz(args.batch_sz)
             .set_seed(args.seed)
             .set_generator(args.prng)
             .set_ni_mutator(args.ni_mutator)
+            .set_only_mutators(args.only_mutators)
+            .set_disabled_mutators(args.disable_mutators)
             .set_dict(args.user_dict)
             .set_max_iter(args.max_iter)
             .set_grammar(args.grammar_mutator)
@@ -97,41 +240,111 @@ impl From<Clargs> for FuzzerConfig {
             .set_mutation_passes(args.mutation_passes)
             .set_max_length(args.max_length)
             .set_max_time(args.max_time)
+            .set_crash_exploration(args.crash_exploration)
+            .set_random_seeds(RandomSeedConfig {
+                count: args.random_seeds,
+                size_dist: args.random_seed_size,
+            })
+            .set_env(args.env)
+            .set_mem_limit_mb(args.mem_limit_mb)
+            .set_forkserver(args.forkserver)
+            .set_metrics_port(args.metrics_port)
+            .set_stats_file(args.stats_file)
+            .set_coverage(args.coverage)
+            .set_persistent(args.persistent)
+            .set_harness_path(args.harness)
     }
 }
 
 fn main() -> Result<()> {
-    let fuzzer_config: FuzzerConfig = Clargs::parse().into();
+    let args = Clargs::parse();
+    if let Some(replay_file) = &args.replay {
+        // Only the target is needed, so the corpus and crash directories may not exist
+        let config = FuzzerConfig::default()
+            .set_target(args.target)
+            .set_env(args.env)
+            .set_mem_limit_mb(args.mem_limit_mb);
+        let input = std::fs::read(replay_file)?;
+        let status = replay(&config, &input)?;
+        match (status.code(), status.signal()) {
+            (Some(code), _) => println!("[HANTU] Target exited with code {code}"),
+            (_, Some(signal)) => println!("[HANTU] Target terminated by signal {signal}"),
+            _ => println!("[HANTU] Target exited with {status}"),
+        }
+        return Ok(());
+    }
+
+    let dry_run_args = args.dry_run.map(|count| (count, args.dump_dir.clone()));
+    let fuzzer_config: FuzzerConfig = args.into();
+
+    if let Some((count, dump_dir)) = dry_run_args {
+        if let Some(dump_dir) = dump_dir {
+            std::fs::create_dir_all(&dump_dir)?;
+            dry_run(&fuzzer_config, count, |i, tc| {
+                std::fs::write(dump_dir.join(format!("{i:06}")), tc)?;
+                Ok(())
+            })?;
+            println!("[HANTU] Wrote {count} test cases to {}", dump_dir.display());
+        } else {
+            dry_run(&fuzzer_config, count.min(DRY_RUN_PRINTED), |_, tc| {
+                let hex = tc.iter().map(|b| format!("{b:02x}")).collect::<String>();
+                println!("{hex}");
+                Ok(())
+            })?;
+        }
+        return Ok(());
+    }
     let fuzzer_stats = FuzzerStats::new().to_arc();
     println!("[HANTU] Using fuzing config: {fuzzer_config:#?}");
 
-    spawn_workers(&fuzzer_config, &fuzzer_stats).unwrap_or_else(|e| {
+    if let Some(port) = fuzzer_config.metrics_port {
+        serve_metrics(&fuzzer_stats, port)?;
+        println!("[HANTU] Serving metrics on http://127.0.0.1:{port}/metrics");
+    }
+
+    install_stop_handler(&fuzzer_stats)?;
+    let workers = spawn_workers(&fuzzer_config, &fuzzer_stats).unwrap_or_else(|e| {
         panic!("Error spawning workers: {e}");
     });
-    let start_time = Instant::now();
 
-    std::thread::sleep(std::time::Duration::from_secs(1));
-    loop {
-        let elapsed = start_time.elapsed().as_secs_f64();
-        let iterations = fuzzer_stats.get_iterations();
-        let crashes = fuzzer_stats.get_crashes();
-        let execs_per_sec = iterations as f64 / elapsed;
+    let report = || -> Result<(f64, usize)> {
+        let stats = fuzzer_stats.snapshot();
         println!(
-            "[{:10.6}] Iterations: {:10} - exec/sec: {:8.1} - crashes: {:5}",
-            elapsed, iterations, execs_per_sec, crashes
+            "[{:10.6}] Iterations: {:10} - exec/sec: {:8.1} - crashes: {:5} ({:5} unique) - max size: {:8}",
+            stats.elapsed,
+            stats.iterations,
+            stats.execs_per_sec,
+            stats.crashes,
+            stats.unique_crashes,
+            stats.max_seen_size
         );
+        if let Some(stats_file) = &fuzzer_config.stats_file {
+            stats.append_to(stats_file)?;
+        }
+        Ok((stats.elapsed, stats.iterations))
+    };
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    while !fuzzer_stats.is_stopping() {
+        let (elapsed, iterations) = report()?;
         if let Some(max_iter) = fuzzer_config.max_iter {
             if iterations >= max_iter {
                 println!("[HANTU] Max iterations reached: {}", max_iter);
-                break Ok(());
+                break;
             }
         }
         if let Some(max_time) = fuzzer_config.max_time {
             if elapsed >= max_time as f64 * 60.0 {
                 println!("[HANTU] Max time reached: {} minute(s)", max_time);
-                break Ok(());
+                break;
             }
         }
         std::thread::sleep(std::time::Duration::from_secs(3));
     }
+
+    println!("[HANTU] Stopping workers");
+    fuzzer_stats.request_stop();
+    let res = join_workers(workers);
+    report()?;
+    res
 }
@@ -0,0 +1,25 @@
+// Generates test cases without a target and checks they are dumped to the given directory.
+use std::{env, fs, process::Command};
+
+#[test]
+fn test_dry_run() {
+    let dir = env::temp_dir().join(format!("hantu_dry_run_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("corpus")).unwrap();
+    fs::write(dir.join("corpus/seed"), b"hantu").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hantu"))
+        .arg("--corpus-dir")
+        .arg(dir.join("corpus"))
+        .arg("--crash-dir")
+        .arg(dir.join("crashes"))
+        .args(["--random-seeds", "0", "--dry-run", "10", "--dump-dir", "tmp"])
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let dumped = fs::read_dir(dir.join("tmp")).unwrap().count();
+    assert_eq!(dumped, 10);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,37 @@
+// Replays inputs against a target that crashes on inputs containing "crash".
+use std::{env, fs, os::unix::fs::PermissionsExt, process::Command};
+
+#[test]
+fn test_replay() {
+    let dir = env::temp_dir().join(format!("hantu_replay_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let target = dir.join("target.sh");
+    fs::write(
+        &target,
+        "#!/bin/sh\ngrep -q crash \"$1\" && kill -SEGV $$\nexit 3\n",
+    )
+    .unwrap();
+    fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(dir.join("crash"), b"a crash input").unwrap();
+    fs::write(dir.join("benign"), b"a benign input").unwrap();
+
+    let replay = |input: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_hantu"))
+            .arg("--replay")
+            .arg(dir.join(input))
+            .arg("--")
+            .arg(&target)
+            .arg("@@")
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let out = replay("crash");
+    assert!(out.contains("Target terminated by signal 11"), "{out}");
+    let out = replay("benign");
+    assert!(out.contains("Target exited with code 3"), "{out}");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,51 @@
+// Runs the fuzzer briefly and checks the stats it appends to the stats file.
+use std::{env, fs, process::Command};
+
+#[test]
+fn test_stats_file() {
+    let dir = env::temp_dir().join(format!("hantu_stats_file_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("corpus")).unwrap();
+    fs::write(dir.join("corpus/seed"), b"hantu").unwrap();
+    let stats_file = dir.join("stats.jsonl");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_hantu"))
+        .arg("--corpus-dir")
+        .arg(dir.join("corpus"))
+        .arg("--crash-dir")
+        .arg(dir.join("crashes"))
+        .arg("--stats-file")
+        .arg(&stats_file)
+        .args([
+            "--max-iter",
+            "200",
+            "--batch-sz",
+            "50",
+            "--random-seeds",
+            "0",
+        ])
+        .args(["--only-mutators", "change_bit", "--", "/bin/true", "@@"])
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let stats = fs::read_to_string(&stats_file).unwrap();
+    assert!(stats.lines().count() >= 2);
+    for line in stats.lines() {
+        let stats: serde_json::Value = serde_json::from_str(line).unwrap();
+        for key in [
+            "elapsed",
+            "iterations",
+            "execs_per_sec",
+            "crashes",
+            "unique_crashes",
+            "max_seen_size",
+        ] {
+            assert!(stats[key].is_number(), "Missing {key} in {line}");
+        }
+    }
+    let last: serde_json::Value = serde_json::from_str(stats.lines().last().unwrap()).unwrap();
+    assert_eq!(last["iterations"], 200);
+    fs::remove_dir_all(&dir).unwrap();
+}